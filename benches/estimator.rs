@@ -0,0 +1,126 @@
+use fidget_spinner::core::{Configuration, Parameters, RpmEstimator};
+use fidget_spinner::synthetic::Propeller;
+
+const WIDTH: u16 = 128;
+const HEIGHT: u16 = 128;
+const DURATION: u64 = 1_000_000; // µs
+
+fn propeller() -> Propeller {
+    Propeller {
+        width: WIDTH,
+        height: HEIGHT,
+        jitter: 20,
+        noise_rate: 1.0,
+        ..Default::default()
+    }
+}
+
+fn configuration() -> Configuration {
+    Configuration {
+        width: WIDTH,
+        height: HEIGHT,
+        ..Default::default()
+    }
+}
+
+fn event_loop(criterion: &mut criterion::Criterion) {
+    let events = propeller().events(0, DURATION).unwrap();
+    let parameters = Parameters::default();
+    let mut group = criterion.benchmark_group("event_loop");
+    group.throughput(criterion::Throughput::Elements(events.len() as u64));
+    group.sample_size(10);
+    // a single sample per second isolates the per-event work
+    group.bench_function("sparse_samples", |bencher| {
+        bencher.iter_batched(
+            || {
+                RpmEstimator::new(Configuration {
+                    sampling_frequency: 1.0,
+                    ..configuration()
+                })
+                .unwrap()
+            },
+            |mut estimator| estimator.process(&events, &parameters).unwrap(),
+            criterion::BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("default", |bencher| {
+        bencher.iter_batched(
+            || RpmEstimator::new(configuration()).unwrap(),
+            |mut estimator| estimator.process(&events, &parameters).unwrap(),
+            criterion::BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+fn sort(criterion: &mut criterion::Criterion) {
+    let mut group = criterion.benchmark_group("select_most_active");
+    for timelines in [1 << 10, 1 << 14, 1 << 16] {
+        // deterministic pseudo-random activities, some of them zero
+        let activities_and_indices: Vec<(f64, usize)> = (0..timelines)
+            .map(|index| (((index * 7919) % 1009) as f64 / 16.0, index))
+            .collect();
+        group.bench_with_input(
+            criterion::BenchmarkId::from_parameter(timelines),
+            &activities_and_indices,
+            |bencher, activities_and_indices| {
+                bencher.iter_batched_ref(
+                    || activities_and_indices.clone(),
+                    |activities_and_indices| {
+                        fidget_spinner::core::select_most_active(activities_and_indices, 100, None)
+                    },
+                    criterion::BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+// Each iteration evaluates exactly one sample (ranking, transforms, and peak detection), from
+// a state saved in the middle of a sampling window.
+fn fft(criterion: &mut criterion::Criterion) {
+    let parameters = Parameters::default();
+    let mut group = criterion.benchmark_group("sample");
+    for most_active_timelines in [16, 64, 256] {
+        let configuration = Configuration {
+            most_active_timelines,
+            ..configuration()
+        };
+        let sampling_period = (1e6 / configuration.sampling_frequency) as u64; // µs
+        let mut estimator = RpmEstimator::new(configuration).unwrap();
+        let events = propeller()
+            .events(0, DURATION + sampling_period / 2)
+            .unwrap();
+        estimator.process(&events, &parameters).unwrap();
+        let state = estimator.to_state();
+        let next_events = propeller()
+            .events(
+                DURATION + sampling_period / 2,
+                DURATION + sampling_period * 2,
+            )
+            .unwrap();
+        let next_event = next_events
+            .iter()
+            .find(|event| event.t > DURATION + sampling_period)
+            .copied()
+            .unwrap();
+        group.bench_function(
+            criterion::BenchmarkId::from_parameter(most_active_timelines),
+            |bencher| {
+                bencher.iter_batched(
+                    || RpmEstimator::from_state(&state).unwrap(),
+                    |mut estimator| {
+                        estimator.process(&[next_event], &parameters).unwrap();
+                        assert_eq!(estimator.samples().len(), 1);
+                    },
+                    criterion::BatchSize::SmallInput,
+                );
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion::criterion_group!(benches, event_loop, sort, fft);
+criterion::criterion_main!(benches);
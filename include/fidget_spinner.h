@@ -0,0 +1,66 @@
+#ifndef FIDGET_SPINNER_H
+#define FIDGET_SPINNER_H
+
+// C interface of the RPM estimator, built with
+// cargo build --release --no-default-features --features capi
+// Handles are not thread-safe, but distinct handles may be used concurrently.
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct rpm rpm;
+
+typedef struct {
+    uint64_t t; // µs (unless the state sets another time unit), timestamps must be monotonic
+    uint16_t x;
+    uint16_t y;
+    uint8_t on; // 0 for OFF events, any other value for ON events
+} rpm_event;
+
+typedef struct {
+    uint64_t t; // µs, end of the sample window
+    float rpm;
+    float confidence;
+    float smoothed_rpm;
+    float phase; // rad, NaN if no frequency or flip was detected
+} rpm_sample;
+
+// Creates an estimator with the default configuration, returns NULL if the dimensions are
+// invalid. The handle must be released with rpm_destroy.
+rpm* rpm_create(uint16_t width, uint16_t height);
+
+// Restores an estimator saved with RpmCalculator.save_state (Python), which gives access to the
+// full configuration. Returns NULL if the state is invalid. state must point to length bytes.
+rpm* rpm_create_from_state(const uint8_t* state, size_t length);
+
+// rpm may be NULL.
+void rpm_destroy(rpm* rpm);
+
+void rpm_reset(rpm* rpm);
+
+// events must point to length events (it may be NULL if length is 0). Returns 0 on success and
+// -1 on error (see rpm_error). The default thresholds are 10 (amplitude) and 0.4
+// (autocorrelation).
+int32_t rpm_process_events(
+    rpm* rpm,
+    const rpm_event* events,
+    size_t length,
+    float amplitude_threshold,
+    float autocorrelation_threshold);
+
+// Writes the most recent sample and returns 1, or returns 0 if no sample has been computed yet.
+int32_t rpm_latest(const rpm* rpm, rpm_sample* sample);
+
+// Message of the last error (empty if there was none), valid until the next call with the
+// same handle.
+const char* rpm_error(const rpm* rpm);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -11,11 +11,10 @@ import scipy.ndimage
 from . import extension
 from . import ui
 
-FFT_FREQUENCY: float = 512.0  # must be the same as FFT_FREQUENCY in src/lib.rs
-FFT_SAMPLES: int = 1024  # must be the same as FFT_SAMPLES in src/lib.rs
+FFT_FREQUENCY: float = 512.0
+FFT_SAMPLES: int = 1024
 SAMPLING_FREQUENCY: float = 10.0
 RPMS_LENGTH: int = 300
-# must be the same as SAMPLING_FREQUENCY in src/lib.rs
 
 MINIMUM_FILTER_SIZE: int = 5
 DEFAULT_MAXIMUM_LATENCY: int = 3000
@@ -60,7 +59,11 @@ def camera_thread_target(
     event_display: ui.EventDisplay,
     draw: Draw,
 ):
-    rpm_calculator = extension.RpmCalculator()
+    rpm_calculator = extension.RpmCalculator(
+        fft_samples=FFT_SAMPLES,
+        fft_frequency=FFT_FREQUENCY,
+        sampling_frequency=SAMPLING_FREQUENCY,
+    )
     spectrum = np.zeros(FFT_SAMPLES // 2, dtype=np.float32)
     autocorrelation = np.zeros(FFT_SAMPLES // 2, dtype=np.float32)
     autocorrelation_detections = np.zeros(4, dtype=np.float32)
@@ -96,8 +99,11 @@ def camera_thread_target(
                     if first or new_rpms is not None:
                         first = False
                         if new_rpms is not None:
+                            # NaN samples (warmup, gaps) would spread through the filter
+                            new_rpms = new_rpms[~np.isnan(new_rpms["rpm"])]
+                        if new_rpms is not None and len(new_rpms) > 0:
                             rpms[0 : -len(new_rpms)] = rpms[len(new_rpms) :]
-                            rpms[-len(new_rpms) :] = new_rpms
+                            rpms[-len(new_rpms) :] = new_rpms["rpm"]
                         scipy.ndimage.minimum_filter(
                             rpms,
                             size=MINIMUM_FILTER_SIZE,
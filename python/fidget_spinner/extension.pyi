@@ -1,17 +1,633 @@
+import os
 import typing
 
 import numpy
 import numpy.typing
 
+# a structured array (any shape and strides, read in C order) or a list or tuple of arrays
+Events = typing.Union[numpy.ndarray, list[numpy.ndarray], tuple[numpy.ndarray, ...]]
+
+class Aedat4Reader:
+    def __init__(self, path: typing.Union[str, os.PathLike]) -> None: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    def __iter__(self) -> "Aedat4Reader": ...
+    def __next__(self) -> numpy.ndarray: ...
+
+class AedatReader:
+    def __init__(
+        self, path: typing.Union[str, os.PathLike], chunk_length: int = 65536
+    ) -> None: ...
+    @property
+    def version(self) -> str: ...
+    @property
+    def width(self) -> typing.Optional[int]: ...
+    @property
+    def height(self) -> typing.Optional[int]: ...
+    def __iter__(self) -> "AedatReader": ...
+    def __next__(self) -> tuple[numpy.ndarray, numpy.ndarray]: ...
+
+class ArrowRecordBatch:
+    def __len__(self) -> int: ...
+    def __arrow_c_schema__(self) -> typing.Any: ...
+    def __arrow_c_array__(
+        self, requested_schema: typing.Optional[typing.Any] = None
+    ) -> tuple[typing.Any, typing.Any]: ...
+    def __arrow_c_stream__(
+        self, requested_schema: typing.Optional[typing.Any] = None
+    ) -> typing.Any: ...
+
+class Cluster:
+    @property
+    def rpm(self) -> float: ...
+    @property
+    def frequency(self) -> float: ...
+    @property
+    def confidence(self) -> float: ...
+    @property
+    def x0(self) -> int: ...
+    @property
+    def y0(self) -> int: ...
+    @property
+    def x1(self) -> int: ...
+    @property
+    def y1(self) -> int: ...
+    @property
+    def timelines(self) -> int: ...
+
+class DatReader:
+    def __init__(
+        self, path: typing.Union[str, os.PathLike], chunk_length: int = 65536
+    ) -> None: ...
+    @property
+    def version(self) -> int: ...
+    @property
+    def event_type(self) -> int: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    def __iter__(self) -> "DatReader": ...
+    def __next__(self) -> numpy.ndarray: ...
+
+class EsReader:
+    def __init__(
+        self, path: typing.Union[str, os.PathLike], chunk_length: int = 65536
+    ) -> None: ...
+    @property
+    def version(self) -> str: ...
+    @property
+    def event_type(self) -> typing.Literal["generic", "dvs", "atis", "color"]: ...
+    @property
+    def width(self) -> typing.Optional[int]: ...
+    @property
+    def height(self) -> typing.Optional[int]: ...
+    def __iter__(self) -> "EsReader": ...
+    def __next__(self) -> numpy.ndarray: ...
+
+class Evt3Reader:
+    def __init__(
+        self, path: typing.Union[str, os.PathLike], chunk_length: int = 65536
+    ) -> None: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    @property
+    def encoding(self) -> typing.Literal["EVT2", "EVT3"]: ...
+    def __iter__(self) -> "Evt3Reader": ...
+    def __next__(self) -> tuple[numpy.ndarray, numpy.ndarray]: ...
+
+class Geometry:
+    @property
+    def x(self) -> float: ...
+    @property
+    def y(self) -> float: ...
+    @property
+    def semi_major(self) -> float: ...
+    @property
+    def semi_minor(self) -> float: ...
+    @property
+    def angle(self) -> float: ...
+    @property
+    def tilt(self) -> float: ...
+
+class LiveRpm:
+    def __init__(
+        self,
+        callback: typing.Callable[[], typing.Optional[Events]],
+        config: typing.Optional["RpmConfig"] = None,
+        *,
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+        queue_capacity: int = 64,
+        strict: bool = True,
+        **configuration: typing.Any,
+    ) -> None: ...
+    def config(self) -> "RpmConfig": ...
+    def is_running(self) -> bool: ...
+    def latest_rpm(self) -> typing.Optional[float]: ...
+    def latest_smoothed_rpm(self) -> typing.Optional[float]: ...
+    def latest_confidence(self) -> typing.Optional[float]: ...
+    def latest_spectrum(self) -> numpy.typing.NDArray[numpy.float32]: ...
+    def samples(self) -> int: ...
+    def dropped_packets(self) -> int: ...
+    def stop(self) -> None: ...
+
+class MultiRpmCalculator:
+    def __init__(
+        self,
+        cameras: list[tuple[int, int]],
+        offsets: typing.Optional[list[int]] = None,
+        **configuration: typing.Any,
+    ) -> None: ...
+    @property
+    def calculator(self) -> RpmCalculator: ...
+    @property
+    def offsets(self) -> list[int]: ...
+    def process(
+        self,
+        packets: list[Events],
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+        strict: bool = True,
+    ) -> typing.Optional[numpy.ndarray]: ...
+    def flush(
+        self,
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+    ) -> typing.Optional[numpy.ndarray]: ...
+    def reset(self) -> None: ...
+
+# requires the "publisher" feature
+class Publisher:
+    def __init__(
+        self,
+        address: str = "127.0.0.1:5555",
+        format: typing.Literal["json", "msgpack"] = "json",
+    ) -> None: ...
+    @property
+    def address(self) -> str: ...
+    def is_running(self) -> bool: ...
+    def close(self) -> None: ...
+
+class RadialBin:
+    @property
+    def radius(self) -> float: ...
+    @property
+    def rpm(self) -> float: ...
+    @property
+    def frequency(self) -> float: ...
+    @property
+    def confidence(self) -> float: ...
+    @property
+    def timelines(self) -> int: ...
+
 class RpmCalculator:
-    def __init__(self) -> None: ...
+    def __init__(
+        self,
+        config: typing.Optional["RpmConfig"] = None,
+        *,
+        record_spectrogram: bool = False,
+        record_flips: bool = False,
+        **configuration: typing.Any,
+    ) -> None: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    @property
+    def fft_samples(self) -> int: ...
+    @property
+    def frequency_resolution(self) -> float: ...
+    @property
+    def sampling_frequency(self) -> typing.Optional[float]: ...
+    @property
+    def max_measurable_rpm(self) -> float: ...
+    def config(self) -> "RpmConfig": ...
+    # samples have a NaN rpm (and smoothed rpm) during warmup and for windows that end more than
+    # fft_samples / fft_frequency after the last accepted event (stopped rotor or gap in the
+    # stream), consumers should skip them or hold the previous value
     def process(
         self,
-        events: numpy.ndarray,
-        spectrum: numpy.typing.NDArray[numpy.float32],
-        autocorrelation: numpy.typing.NDArray[numpy.float32],
-        autocorrelation_detections: numpy.typing.NDArray[numpy.float32],
-        amplitude_threshold: float,
-        autocorrelation_threshold: float,
-        frequency_multiplier: float,
-    ) -> typing.Optional[list[float]]: ...
+        events: Events,
+        spectrum: typing.Optional[numpy.typing.NDArray[numpy.float32]] = None,
+        autocorrelation: typing.Optional[numpy.typing.NDArray[numpy.float32]] = None,
+        autocorrelation_detections: typing.Optional[
+            numpy.typing.NDArray[numpy.float32]
+        ] = None,
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+        strict: bool = True,
+    ) -> typing.Optional[numpy.ndarray]: ...
+    def process_async(
+        self,
+        events: Events,
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+        strict: bool = True,
+    ) -> typing.Awaitable[typing.Optional[numpy.ndarray]]: ...
+    def flush(
+        self,
+        t_end: typing.Optional[int] = None,
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+    ) -> typing.Optional[numpy.ndarray]: ...
+    def process_file(
+        self,
+        path: typing.Union[str, os.PathLike],
+        chunk_length: typing.Optional[int] = None,
+        amplitude_threshold: float = 10.0,
+        autocorrelation_threshold: float = 0.4,
+        frequency_multiplier: float = 1.0,
+        progress: typing.Optional[
+            typing.Callable[[int, typing.Optional[int]], typing.Any]
+        ] = None,
+    ) -> numpy.ndarray: ...
+    def confidences(self) -> list[float]: ...
+    def smoothed_rpms(self) -> list[float]: ...
+    def phases(self) -> list[typing.Optional[float]]: ...
+    def shaft_frequencies(self) -> list[float]: ...
+    def blade_counts(self) -> list[int]: ...
+    def on_ratios(self) -> list[float]: ...
+    def event_rates(self) -> list[float]: ...
+    def flip_rates(self) -> list[tuple[float, float]]: ...
+    def spreads(self) -> list[float]: ...
+    def tracks(self) -> list[typing.Optional[tuple[float, float, float]]]: ...
+    def geometry(self) -> list[typing.Optional[Geometry]]: ...
+    def tip_speeds(self) -> list[float]: ...
+    def low_frequency_estimates(self) -> list[bool]: ...
+    def imbalance_indices(self) -> list[float]: ...
+    def directions(
+        self,
+    ) -> list[typing.Optional[typing.Literal["clockwise", "counterclockwise"]]]: ...
+    def degradations(
+        self,
+    ) -> list[
+        list[typing.Literal["fewer_timelines", "skipped_windows", "coarse_grid"]]
+    ]: ...
+    def changes(
+        self,
+    ) -> list[
+        tuple[
+            int,
+            typing.Literal["acceleration", "deceleration", "stop", "start", "reversal"],
+        ]
+    ]: ...
+    def timelines_counts(self) -> list[int]: ...
+    def flicker_frequency(self) -> typing.Optional[float]: ...
+    def spectrum(self) -> numpy.typing.NDArray[numpy.float32]: ...
+    def frequencies(
+        self, rpm: bool = False, frequency_multiplier: float = 1.0
+    ) -> numpy.typing.NDArray[numpy.float64]: ...
+    def autocorrelation(self) -> numpy.typing.NDArray[numpy.float32]: ...
+    def autocorrelation_detections(self) -> numpy.typing.NDArray[numpy.float32]: ...
+    def timeline_spectra(
+        self,
+    ) -> tuple[numpy.typing.NDArray[numpy.float32], list[tuple[int, int]]]: ...
+    def timeline_overflows(self) -> int: ...
+    def stats(self) -> Statistics: ...
+    def enable_profiling(self) -> None: ...
+    def disable_profiling(self) -> None: ...
+    def profile(
+        self,
+    ) -> dict[typing.Literal["process", "ranking", "transform", "peak"], "Timing"]: ...
+    def render(self, t_window: int) -> numpy.typing.NDArray[numpy.uint8]: ...
+    def strobe_image(self, bins: int) -> numpy.typing.NDArray[numpy.uint8]: ...
+    def activity_map(self) -> numpy.typing.NDArray[numpy.float32]: ...
+    def reset(self) -> None: ...
+    def set_callback(
+        self,
+        callback: typing.Optional[typing.Callable[..., None]],
+        spectrum: bool = False,
+    ) -> None: ...
+    def export_results(
+        self,
+        path: typing.Union[str, os.PathLike],
+        format: typing.Literal["csv", "parquet"] = "csv",
+    ) -> None: ...
+    def results_to_arrow(self) -> ArrowRecordBatch: ...
+    def __getstate__(self) -> bytes: ...
+    def __setstate__(self, state: bytes) -> None: ...
+    def save_state(self, path: typing.Union[str, os.PathLike]) -> None: ...
+    def load_state(self, path: typing.Union[str, os.PathLike]) -> None: ...
+    def clone(self) -> "RpmCalculator": ...
+    def __copy__(self) -> "RpmCalculator": ...
+    def __deepcopy__(self, memo: typing.Any) -> "RpmCalculator": ...
+    def process_triggers(
+        self,
+        triggers: numpy.ndarray,
+        pulses_per_revolution: int = 1,
+        source: typing.Optional[int] = None,
+        strict: bool = True,
+    ) -> list[tuple[float, typing.Optional[float], typing.Optional[float]]]: ...
+    def process_imu(
+        self,
+        imu: numpy.ndarray,
+        strict: bool = True,
+    ) -> list[tuple[float, typing.Optional[float], typing.Optional[float]]]: ...
+    def set_roi(self, x0: int, y0: int, x1: int, y1: int) -> None: ...
+    def clear_roi(self) -> None: ...
+    def roi(self) -> tuple[int, int, int, int]: ...
+    def add_region(self, name: str, x0: int, y0: int, x1: int, y1: int) -> None: ...
+    def remove_region(self, name: str) -> None: ...
+    def regions(self) -> list[tuple[str, int, int, int, int]]: ...
+    def region_rpms(self) -> list[dict[str, float]]: ...
+    def set_mask(
+        self, mask: typing.Optional[numpy.typing.NDArray[numpy.bool_]]
+    ) -> None: ...
+    def set_scale(
+        self,
+        meters_per_pixel: typing.Optional[float] = None,
+        *,
+        rotor_radius: typing.Optional[float] = None,
+    ) -> None: ...
+    def set_alarm(
+        self,
+        min_rpm: typing.Optional[float] = None,
+        max_rpm: typing.Optional[float] = None,
+        hysteresis: float = 0.0,
+        callback: typing.Optional[
+            typing.Callable[[int, typing.Literal["normal", "low", "high"]], typing.Any]
+        ] = None,
+    ) -> None: ...
+    @property
+    def degradation(
+        self,
+    ) -> typing.Literal["none", "fewer_timelines", "skipped_windows", "coarse_grid"]: ...
+    @property
+    def alarm_state(self) -> typing.Literal["normal", "low", "high"]: ...
+    def alarm_transitions(
+        self,
+    ) -> list[tuple[int, typing.Literal["normal", "low", "high"]]]: ...
+    def save_hdf5(self, path: typing.Union[str, os.PathLike]) -> None: ...
+    def attach_publisher(self, publisher: Publisher) -> None: ...
+    def detach_publisher(self) -> None: ...
+    def hot_pixels(self) -> list[tuple[int, int]]: ...
+    def set_hot_pixel(self, x: int, y: int, hot: typing.Optional[bool]) -> None: ...
+    def estimate_blade_count(
+        self, maximum_blade_count: int = 8
+    ) -> typing.Optional[tuple[int, float]]: ...
+    def clusters(self) -> list[list[Cluster]]: ...
+    def all_flips(self) -> numpy.ndarray: ...
+    def flips(self, x: int, y: int) -> numpy.ndarray: ...
+    def radial_profiles(self) -> list[list[RadialBin]]: ...
+
+
+class RpmConfig:
+    def __init__(
+        self,
+        width: int = 1280,
+        height: int = 720,
+        fft_samples: int = 1024,
+        fft_frequency: float = 512.0,
+        timestamp_policy: typing.Literal["skip", "clamp", "error"] = "skip",
+        sampling_frequency: float = 10.0,
+        events_per_sample: typing.Optional[int] = None,
+        method: typing.Literal[
+            "autocorrelation", "harmonic_product", "lombscargle"
+        ] = "autocorrelation",
+        harmonics: int = 5,
+        window_duration: typing.Optional[float] = None,
+        hop_duration: typing.Optional[float] = None,
+        hot_pixel_ratio: typing.Optional[float] = None,
+        hot_pixel_window: int = 1000000,
+        refractory_period: typing.Optional[int] = None,
+        noise_filter_window: typing.Optional[int] = None,
+        noise_filter_neighbours: int = 1,
+        spatial_downsampling: int = 4,
+        anti_aliasing: bool = False,
+        most_active_timelines: int = 32,
+        window_function: typing.Literal[
+            "rectangular", "hann", "hamming", "blackman_harris"
+        ] = "rectangular",
+        zero_padding: int = 1,
+        peak_interpolation: bool = False,
+        smoothing: typing.Literal["none", "exponential", "kalman"] = "none",
+        smoothing_alpha: float = 0.2,
+        process_noise: float = 10.0,
+        measurement_noise: float = 1.0,
+        activity_tau: int = 10000,
+        ranking: typing.Literal["activity", "count"] = "activity",
+        ranking_window: int = 100000,
+        timeline_length: int = 256,
+        mode: typing.Literal["rotation", "vibration"] = "rotation",
+        warmup_events: int = 0,
+        warmup_timelines: int = 0,
+        activity_fraction: typing.Optional[float] = None,
+        strobe_length: int = 0,
+        flicker_rejection: typing.Optional[float] = None,
+        time_unit: typing.Union[typing.Literal["us", "ns", "ms"], float] = "us",
+        wrap_period: typing.Optional[int] = None,
+        roi_tracking: bool = False,
+        radial_bins: int = 0,
+        low_frequency: typing.Literal["off", "on", "adaptive"] = "off",
+        low_frequency_rate: float = 100.0,
+        refine: bool = False,
+        change_threshold: typing.Optional[float] = None,
+        spectral_smoothing: typing.Optional[float] = None,
+        sign_check_radius: int = 1,
+        sign_check_tolerance: int = 0,
+        sign_detection: typing.Literal["neighbourhood", "transition"] = "neighbourhood",
+        debounce_events: int = 2,
+        max_latency_ms: typing.Optional[float] = None,
+        subsampling_threshold: typing.Optional[float] = None,
+        subsampling: typing.Literal["random", "stratified"] = "stratified",
+        blades: typing.Optional[int] = None,
+        flips_capacity: int = 4096,
+    ) -> None: ...
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    @property
+    def fft_samples(self) -> int: ...
+    @property
+    def fft_frequency(self) -> float: ...
+    @property
+    def timestamp_policy(self) -> typing.Literal["skip", "clamp", "error"]: ...
+    @property
+    def sampling_frequency(self) -> float: ...
+    @property
+    def events_per_sample(self) -> typing.Optional[int]: ...
+    @property
+    def method(
+        self,
+    ) -> typing.Literal["autocorrelation", "harmonic_product", "lombscargle"]: ...
+    @property
+    def harmonics(self) -> int: ...
+    @property
+    def window_duration(self) -> typing.Optional[float]: ...
+    @property
+    def hot_pixel_ratio(self) -> typing.Optional[float]: ...
+    @property
+    def hot_pixel_window(self) -> int: ...
+    @property
+    def refractory_period(self) -> typing.Optional[int]: ...
+    @property
+    def noise_filter_window(self) -> typing.Optional[int]: ...
+    @property
+    def noise_filter_neighbours(self) -> int: ...
+    @property
+    def spatial_downsampling(self) -> int: ...
+    @property
+    def anti_aliasing(self) -> bool: ...
+    @property
+    def most_active_timelines(self) -> int: ...
+    @property
+    def window_function(
+        self,
+    ) -> typing.Literal["rectangular", "hann", "hamming", "blackman_harris"]: ...
+    @property
+    def zero_padding(self) -> int: ...
+    @property
+    def peak_interpolation(self) -> bool: ...
+    @property
+    def smoothing(self) -> typing.Literal["none", "exponential", "kalman"]: ...
+    @property
+    def smoothing_alpha(self) -> float: ...
+    @property
+    def process_noise(self) -> float: ...
+    @property
+    def measurement_noise(self) -> float: ...
+    @property
+    def activity_tau(self) -> int: ...
+    @property
+    def ranking(self) -> typing.Literal["activity", "count"]: ...
+    @property
+    def ranking_window(self) -> int: ...
+    @property
+    def timeline_length(self) -> int: ...
+    @property
+    def mode(self) -> typing.Literal["rotation", "vibration"]: ...
+    @property
+    def warmup_events(self) -> int: ...
+    @property
+    def warmup_timelines(self) -> int: ...
+    @property
+    def activity_fraction(self) -> typing.Optional[float]: ...
+    @property
+    def strobe_length(self) -> int: ...
+    @property
+    def flicker_rejection(self) -> typing.Optional[float]: ...
+    @property
+    def time_unit(self) -> typing.Union[typing.Literal["us", "ns", "ms"], float]: ...
+    @property
+    def wrap_period(self) -> typing.Optional[int]: ...
+    @property
+    def roi_tracking(self) -> bool: ...
+    @property
+    def radial_bins(self) -> int: ...
+    @property
+    def low_frequency(self) -> typing.Literal["off", "on", "adaptive"]: ...
+    @property
+    def low_frequency_rate(self) -> float: ...
+    @property
+    def refine(self) -> bool: ...
+    @property
+    def change_threshold(self) -> typing.Optional[float]: ...
+    @property
+    def spectral_smoothing(self) -> typing.Optional[float]: ...
+    @property
+    def sign_check_radius(self) -> int: ...
+    @property
+    def sign_check_tolerance(self) -> int: ...
+    @property
+    def sign_detection(self) -> typing.Literal["neighbourhood", "transition"]: ...
+    @property
+    def debounce_events(self) -> int: ...
+    @property
+    def max_latency_ms(self) -> typing.Optional[float]: ...
+    @property
+    def subsampling_threshold(self) -> typing.Optional[float]: ...
+    @property
+    def subsampling(self) -> typing.Literal["random", "stratified"]: ...
+    @property
+    def blades(self) -> typing.Optional[int]: ...
+    @property
+    def flips_capacity(self) -> int: ...
+
+class Statistics:
+    @property
+    def events(self) -> int: ...
+    @property
+    def out_of_order_events(self) -> int: ...
+    @property
+    def subsampled_events(self) -> int: ...
+    @property
+    def noise_filtered_events(self) -> int: ...
+    @property
+    def masked_events(self) -> int: ...
+    @property
+    def hot_pixel_events(self) -> int: ...
+    @property
+    def accepted_events(self) -> int: ...
+    @property
+    def active_pixels(self) -> int: ...
+    @property
+    def timeline_overflows(self) -> int: ...
+    @property
+    def flicker_timelines(self) -> int: ...
+    @property
+    def neighbourhood_flips(self) -> int: ...
+    @property
+    def transition_flips(self) -> int: ...
+    @property
+    def dropped_flips(self) -> int: ...
+    @property
+    def processing_time(self) -> float: ...
+
+class Timing:
+    @property
+    def calls(self) -> int: ...
+    @property
+    def total_time(self) -> float: ...
+    @property
+    def maximum_time(self) -> float: ...
+
+def analyze(
+    events: typing.Union[Events, str, os.PathLike],
+    config: typing.Optional[RpmConfig] = None,
+    *,
+    amplitude_threshold: float = 10.0,
+    autocorrelation_threshold: float = 0.4,
+    frequency_multiplier: float = 1.0,
+    strict: bool = True,
+    **configuration: typing.Any,
+) -> dict[str, numpy.ndarray]: ...
+def crop(
+    events: numpy.ndarray,
+    x0: int,
+    y0: int,
+    x1: int,
+    y1: int,
+    strict: bool = True,
+) -> numpy.ndarray: ...
+def spatial_downsample(
+    events: numpy.ndarray, factor: int, strict: bool = True
+) -> numpy.ndarray: ...
+def time_slice(
+    events: numpy.ndarray, t0: int, t1: int, strict: bool = True
+) -> numpy.ndarray: ...
+def write_es(
+    path: typing.Union[str, os.PathLike],
+    events: numpy.ndarray,
+    width: typing.Optional[int] = None,
+    height: typing.Optional[int] = None,
+    strict: bool = True,
+) -> None: ...
+def write_dat(
+    path: typing.Union[str, os.PathLike],
+    events: numpy.ndarray,
+    width: typing.Optional[int] = None,
+    height: typing.Optional[int] = None,
+    strict: bool = True,
+) -> None: ...
@@ -0,0 +1,119 @@
+"""Compares python/fidget_spinner/extension.pyi with the compiled extension.
+
+The stubs are written by hand (they use Literal types and callbacks that cannot be inferred
+from the Rust signatures). This script reports classes, methods, properties, and parameter
+names that are missing from either side. Run it after `maturin develop`:
+
+    python scripts/check_stubs.py
+"""
+
+import ast
+import inspect
+import pathlib
+import sys
+import typing
+
+import fidget_spinner.extension
+
+STUBS_PATH = (
+    pathlib.Path(__file__).resolve().parent.parent
+    / "python"
+    / "fidget_spinner"
+    / "extension.pyi"
+)
+
+# defined only if the extension is built with the "publisher" feature
+OPTIONAL = {
+    "Publisher",
+    "RpmCalculator.attach_publisher",
+    "RpmCalculator.detach_publisher",
+}
+
+
+def stub_parameters(function: ast.FunctionDef) -> list[str]:
+    arguments = function.args
+    names = [
+        argument.arg
+        for argument in arguments.posonlyargs + arguments.args + arguments.kwonlyargs
+    ]
+    if len(names) > 0 and names[0] in ("self", "cls"):
+        names = names[1:]
+    return names
+
+
+def runtime_parameters(function: typing.Any) -> typing.Optional[list[str]]:
+    try:
+        signature = inspect.signature(function)
+    except (TypeError, ValueError):
+        return None
+    return [
+        name
+        for name, parameter in signature.parameters.items()
+        if name not in ("self", "cls")
+        and parameter.kind
+        not in (inspect.Parameter.VAR_POSITIONAL, inspect.Parameter.VAR_KEYWORD)
+    ]
+
+
+def is_property(function: ast.FunctionDef) -> bool:
+    return any(
+        isinstance(decorator, ast.Name) and decorator.id == "property"
+        for decorator in function.decorator_list
+    )
+
+
+def check_class(class_node: ast.ClassDef, errors: list[str]):
+    runtime_class = getattr(fidget_spinner.extension, class_node.name, None)
+    if runtime_class is None:
+        if class_node.name not in OPTIONAL:
+            errors.append(f"{class_node.name} is not defined by the extension")
+        return
+    stub_names = set()
+    for node in class_node.body:
+        if not isinstance(node, ast.FunctionDef):
+            continue
+        stub_names.add(node.name)
+        if node.name == "__init__":
+            runtime = runtime_parameters(runtime_class)
+        elif not hasattr(runtime_class, node.name):
+            if f"{class_node.name}.{node.name}" not in OPTIONAL:
+                errors.append(
+                    f"{class_node.name}.{node.name} is not defined by the extension"
+                )
+            continue
+        elif is_property(node):
+            continue
+        else:
+            runtime = runtime_parameters(getattr(runtime_class, node.name))
+        stub = stub_parameters(node)
+        if runtime is not None and runtime != stub:
+            errors.append(
+                f"{class_node.name}.{node.name} parameters differ (stub: {stub}, extension: {runtime})"
+            )
+    for name in dir(runtime_class):
+        if not name.startswith("_") and name not in stub_names:
+            errors.append(f"{class_node.name}.{name} is missing from the stubs")
+
+
+def main() -> int:
+    module = ast.parse(STUBS_PATH.read_text())
+    errors: list[str] = []
+    stub_classes = set()
+    for node in module.body:
+        if isinstance(node, ast.ClassDef):
+            stub_classes.add(node.name)
+            check_class(node, errors)
+    for name in dir(fidget_spinner.extension):
+        if (
+            not name.startswith("_")
+            and inspect.isclass(getattr(fidget_spinner.extension, name))
+            and name not in stub_classes
+        ):
+            errors.append(f"{name} is missing from the stubs")
+    for error in errors:
+        print(error)
+    return 1 if len(errors) > 0 else 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -0,0 +1,333 @@
+use std::io::BufRead;
+use std::io::Read;
+
+pub const DEFAULT_CHUNK_LENGTH: usize = 1 << 16; // events (AEDAT 2.0 only)
+const AEDAT2_EVENT_SIZE: usize = 8; // bytes
+const AEDAT3_PACKET_HEADER_SIZE: usize = 28; // bytes
+const AEDAT3_END_OF_HEADER: &str = "#!END-HEADER";
+const AEDAT3_POLARITY_EVENT: i16 = 1;
+const AEDAT3_IMU6_EVENT: i16 = 7;
+const AEDAT3_IMU9_EVENT: i16 = 8;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("the file does not start with an AEDAT magic number (\"#!AER-DAT<version>\")")]
+    MagicNumber,
+
+    #[error("unsupported AEDAT version {0} (expected 2.0, 3.0, or 3.1, use Aedat4Reader for 4.0)")]
+    Version(Version),
+
+    #[error(
+        "the packet header specifies an invalid event size {size} for event type {event_type}"
+    )]
+    EventSize { event_type: i16, size: i32 },
+
+    #[error("the packet header specifies a negative event capacity {0}")]
+    EventCapacity(i32),
+
+    #[error("chunk_length must be larger than zero")]
+    ChunkLength,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}.{}", self.major, self.minor)
+    }
+}
+
+// Reads the first line of an AEDAT file ("#!AER-DAT<major>.<minor>\r\n"), which is the same
+// for all versions.
+pub fn read_version<R: BufRead>(reader: &mut R) -> Result<Version, Error> {
+    let mut line = Vec::new();
+    reader.take(32).read_until(b'\n', &mut line)?;
+    let version = line
+        .strip_prefix(b"#!AER-DAT")
+        .and_then(|version| std::str::from_utf8(version).ok())
+        .and_then(|version| version.trim_end().split_once('.'))
+        .ok_or(Error::MagicNumber)?;
+    match (version.0.parse(), version.1.parse()) {
+        (Ok(major), Ok(minor)) => Ok(Version { major, minor }),
+        _ => Err(Error::MagicNumber),
+    }
+}
+
+// Same layout as ArrayType::AedatImu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(C, packed)]
+pub struct Imu {
+    pub t: u64,
+    pub temperature: f32,        // °C
+    pub accelerometer: [f32; 3], // g
+    pub gyroscope: [f32; 3],     // °/s
+    pub magnetometer: [f32; 3],  // µT, zero if the sensor has no magnetometer
+}
+
+pub struct Chunk {
+    pub events: Vec<crate::core::Event>,
+    pub imu: Vec<Imu>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Aedat2Layout {
+    Dvs128,
+    Davis,
+}
+
+// Decodes jAER and cAER recordings (.aedat files) in the AEDAT 2.0 and 3.x formats, see
+// https://docs.inivation.com/software/software-advanced-usage/file-formats/index.html.
+// AEDAT 2.0 addresses are decoded with the DVS128 layout unless the header mentions a DAVIS
+// camera, coordinates are those written by jAER. AEDAT 2.0 IMU samples are skipped since their
+// scale depends on the camera's configuration.
+// 32-bit timestamps wrap every 71 minutes. Only backwards jumps larger than half of that start a
+// new epoch, smaller ones are out-of-order records that keep the current epoch.
+pub(crate) fn unwrap_timestamp(previous_t: &mut u32, overflows: &mut u32, t: u32) -> u64 {
+    if t < *previous_t && *previous_t - t > 1 << 31 {
+        *overflows += 1;
+    } else if t > *previous_t && t - *previous_t > 1 << 31 && *overflows > 0 {
+        // late record from the previous epoch
+        return (((*overflows - 1) as u64) << 32) | t as u64;
+    }
+    *previous_t = t;
+    ((*overflows as u64) << 32) | t as u64
+}
+
+pub struct Decoder {
+    file: std::io::BufReader<std::fs::File>,
+    version: Version,
+    aedat2_layout: Aedat2Layout,
+    width: Option<u16>,
+    height: Option<u16>,
+    chunk_length: usize,
+    buffer: Vec<u8>,
+    previous_t: u32,
+    overflows: u32,
+}
+
+impl Decoder {
+    pub fn new<P: AsRef<std::path::Path>>(path: P, chunk_length: usize) -> Result<Self, Error> {
+        if chunk_length == 0 {
+            return Err(Error::ChunkLength);
+        }
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let version = read_version(&mut file)?;
+        if version.major != 2 && version.major != 3 {
+            return Err(Error::Version(version));
+        }
+        let mut header = String::new();
+        let mut line = String::new();
+        while file.fill_buf()?.first() == Some(&b'#') {
+            line.clear();
+            file.read_line(&mut line)?;
+            header.push_str(&line);
+            if version.major == 3 && line.trim_end() == AEDAT3_END_OF_HEADER {
+                break;
+            }
+        }
+        let header = header.to_ascii_uppercase();
+        let (width, height) = match geometry(&header) {
+            Some((width, height)) => (Some(width), Some(height)),
+            None => (None, None),
+        };
+        Ok(Self {
+            file,
+            version,
+            aedat2_layout: if header.contains("DAVIS") {
+                Aedat2Layout::Davis
+            } else {
+                Aedat2Layout::Dvs128
+            },
+            width,
+            height,
+            chunk_length,
+            buffer: Vec::new(),
+            previous_t: 0,
+            overflows: 0,
+        })
+    }
+
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    // None if the header does not name a known camera.
+    pub fn width(&self) -> Option<u16> {
+        self.width
+    }
+
+    pub fn height(&self) -> Option<u16> {
+        self.height
+    }
+
+    fn next_aedat2_chunk(&mut self) -> Result<Option<Chunk>, Error> {
+        self.buffer.clear();
+        (&mut self.file)
+            .take((self.chunk_length * AEDAT2_EVENT_SIZE) as u64)
+            .read_to_end(&mut self.buffer)?;
+        if self.buffer.len() < AEDAT2_EVENT_SIZE {
+            return Ok(None);
+        }
+        let mut events = Vec::with_capacity(self.buffer.len() / AEDAT2_EVENT_SIZE);
+        for bytes in self.buffer.chunks_exact(AEDAT2_EVENT_SIZE) {
+            // AEDAT 2.0 is big-endian
+            let address = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
+            let t = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+            // only polarity events are decoded, APS and IMU records may go back in time
+            let polarity_event = match self.aedat2_layout {
+                Aedat2Layout::Dvs128 => (address >> 15) & 1 == 0, // bit 15 flags external events
+                Aedat2Layout::Davis => address >> 31 == 0,        // bit 31 flags APS and IMU
+            };
+            if !polarity_event {
+                continue;
+            }
+            let t = unwrap_timestamp(&mut self.previous_t, &mut self.overflows, t);
+            match self.aedat2_layout {
+                Aedat2Layout::Dvs128 => {
+                    events.push(crate::core::Event {
+                        t,
+                        x: ((address >> 1) & 0b1111111) as u16,
+                        y: ((address >> 8) & 0b1111111) as u16,
+                        polarity: if address & 1 == 0 {
+                            neuromorphic_types::DvsPolarity::On
+                        } else {
+                            neuromorphic_types::DvsPolarity::Off
+                        },
+                    });
+                }
+                Aedat2Layout::Davis => {
+                    events.push(crate::core::Event {
+                        t,
+                        x: ((address >> 12) & 0b1111111111) as u16,
+                        y: ((address >> 22) & 0b111111111) as u16,
+                        polarity: if (address >> 11) & 1 == 1 {
+                            neuromorphic_types::DvsPolarity::On
+                        } else {
+                            neuromorphic_types::DvsPolarity::Off
+                        },
+                    });
+                }
+            }
+        }
+        Ok(Some(Chunk {
+            events,
+            imu: Vec::new(),
+        }))
+    }
+
+    // Returns the events of the next polarity or IMU packet (other packets are skipped).
+    fn next_aedat3_chunk(&mut self) -> Result<Option<Chunk>, Error> {
+        loop {
+            let mut header = [0u8; AEDAT3_PACKET_HEADER_SIZE];
+            match self.file.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(None);
+                }
+                Err(error) => return Err(error.into()),
+            }
+            let event_type = i16::from_le_bytes(header[0..2].try_into().unwrap());
+            let size = i32::from_le_bytes(header[4..8].try_into().unwrap());
+            let t_overflow = i32::from_le_bytes(header[12..16].try_into().unwrap()) as u32;
+            let capacity = i32::from_le_bytes(header[16..20].try_into().unwrap());
+            if size <= 0 {
+                return Err(Error::EventSize { event_type, size });
+            }
+            if capacity < 0 {
+                return Err(Error::EventCapacity(capacity));
+            }
+            let length = size as usize * capacity as usize;
+            let minimum_size = match event_type {
+                AEDAT3_POLARITY_EVENT => 8,
+                AEDAT3_IMU6_EVENT => 36,
+                AEDAT3_IMU9_EVENT => 48,
+                _ => {
+                    self.file.seek_relative(length as i64)?;
+                    continue;
+                }
+            };
+            if (size as usize) < minimum_size {
+                return Err(Error::EventSize { event_type, size });
+            }
+            self.buffer.resize(length, 0);
+            self.file.read_exact(&mut self.buffer)?;
+            let mut chunk = Chunk {
+                events: Vec::new(),
+                imu: Vec::new(),
+            };
+            for bytes in self.buffer.chunks_exact(size as usize) {
+                let data = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+                // bit 0 is the validity mark
+                if data & 1 == 0 {
+                    continue;
+                }
+                // timestamps are 31-bit, the packet header stores the overflow counter
+                let t = ((t_overflow as u64) << 31)
+                    | (i32::from_le_bytes(bytes[4..8].try_into().unwrap()) as u32 as u64);
+                if event_type == AEDAT3_POLARITY_EVENT {
+                    chunk.events.push(crate::core::Event {
+                        t,
+                        x: ((data >> 17) & 0b111111111111111) as u16,
+                        y: ((data >> 2) & 0b111111111111111) as u16,
+                        polarity: if (data >> 1) & 1 == 1 {
+                            neuromorphic_types::DvsPolarity::On
+                        } else {
+                            neuromorphic_types::DvsPolarity::Off
+                        },
+                    });
+                } else {
+                    let float = |index: usize| {
+                        f32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap())
+                    };
+                    chunk.imu.push(Imu {
+                        t,
+                        temperature: float(8),
+                        accelerometer: [float(2), float(3), float(4)],
+                        gyroscope: [float(5), float(6), float(7)],
+                        magnetometer: if event_type == AEDAT3_IMU9_EVENT {
+                            [float(9), float(10), float(11)]
+                        } else {
+                            [0.0; 3]
+                        },
+                    });
+                }
+            }
+            return Ok(Some(chunk));
+        }
+    }
+}
+
+impl Iterator for Decoder {
+    type Item = Result<Chunk, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.version.major == 2 {
+            self.next_aedat2_chunk().transpose()
+        } else {
+            self.next_aedat3_chunk().transpose()
+        }
+    }
+}
+
+// jAER and cAER headers name the camera (for instance "# AEChip: ...DAVIS240C" or
+// "#Source 1: DAVIS346B") but not its resolution.
+fn geometry(header: &str) -> Option<(u16, u16)> {
+    [
+        ("DAVIS346", (346, 260)),
+        ("DAVIS240", (240, 180)),
+        ("DAVIS640", (640, 480)),
+        ("DAVIS128", (128, 128)),
+        ("DVS128", (128, 128)),
+        ("DVXPLORER", (640, 480)),
+    ]
+    .into_iter()
+    .find(|(name, _)| header.contains(name))
+    .map(|(_, geometry)| geometry)
+}
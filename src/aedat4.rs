@@ -0,0 +1,326 @@
+use std::io::Read;
+
+pub const MAGIC_NUMBER: &[u8] = b"#!AER-DAT4.0\r\n";
+pub const EVENTS_IDENTIFIER: &[u8] = b"EVTS";
+const EVENT_SIZE: usize = 16; // bytes, flatbuffers struct with padding
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("the file does not start with the AEDAT4 magic number")]
+    MagicNumber,
+
+    #[error("unknown compression {0}")]
+    Compression(i32),
+
+    #[error("invalid flatbuffer ({0})")]
+    Flatbuffer(&'static str),
+
+    #[error("the description is not valid XML ({0})")]
+    Description(#[from] roxmltree::Error),
+
+    #[error("the description of stream \"{0}\" is invalid")]
+    Stream(String),
+
+    #[error("the file has no event stream")]
+    NoEventStream,
+
+    #[error("the packet from stream {0} is not an event packet")]
+    PacketIdentifier(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Lz4,
+    Zstd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stream {
+    pub id: u32,
+    pub width: u16,
+    pub height: u16,
+}
+
+pub struct Packet {
+    pub stream_id: u32,
+    pub events: Vec<crate::core::Event>,
+}
+
+// Iterates over the event packets of an AEDAT4 file (packets from other streams, such as
+// frames or IMU samples, are skipped).
+pub struct Decoder {
+    file: std::io::BufReader<std::fs::File>,
+    position: u64,
+    end: Option<u64>,
+    compression: Compression,
+    streams: Vec<Stream>,
+    raw_buffer: Vec<u8>,
+    buffer: Vec<u8>,
+}
+
+impl Decoder {
+    pub fn new<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        // the magic number is the first header line of all AEDAT versions
+        if crate::aedat::read_version(&mut file).ok()
+            != Some(crate::aedat::Version { major: 4, minor: 0 })
+        {
+            return Err(Error::MagicNumber);
+        }
+        let mut length_bytes = [0u8; 4];
+        file.read_exact(&mut length_bytes)?;
+        let mut ioheader = vec![0u8; u32::from_le_bytes(length_bytes) as usize];
+        file.read_exact(&mut ioheader)?;
+        let table = Table::root(&ioheader, 0)?;
+        let compression = match table.i32(0, 0)? {
+            0 => Compression::None,
+            1 | 2 => Compression::Lz4,
+            3 | 4 => Compression::Zstd,
+            compression => return Err(Error::Compression(compression)),
+        };
+        let data_table_position = table.i64(1, -1)?;
+        let description = table.string(2)?.unwrap_or("");
+        Ok(Self {
+            file,
+            position: (MAGIC_NUMBER.len() + 4 + ioheader.len()) as u64,
+            end: if data_table_position < 0 {
+                None
+            } else {
+                Some(data_table_position as u64)
+            },
+            compression,
+            streams: parse_description(description)?,
+            raw_buffer: Vec::new(),
+            buffer: Vec::new(),
+        })
+    }
+
+    pub fn streams(&self) -> &[Stream] {
+        &self.streams
+    }
+
+    fn next_packet(&mut self) -> Result<Option<Packet>, Error> {
+        loop {
+            if let Some(end) = self.end {
+                if self.position >= end {
+                    return Ok(None);
+                }
+            }
+            let mut header = [0u8; 8];
+            match self.file.read_exact(&mut header) {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    return Ok(None);
+                }
+                Err(error) => return Err(error.into()),
+            }
+            let stream_id = u32::from_le_bytes(header[0..4].try_into().unwrap());
+            let size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+            self.position += 8 + size as u64;
+            if !self.streams.iter().any(|stream| stream.id == stream_id) {
+                self.file.seek_relative(size as i64)?;
+                continue;
+            }
+            self.raw_buffer.resize(size as usize, 0);
+            self.file.read_exact(&mut self.raw_buffer)?;
+            let buffer = match self.compression {
+                Compression::None => &self.raw_buffer,
+                Compression::Lz4 => {
+                    self.buffer.clear();
+                    lz4::Decoder::new(&self.raw_buffer[..])?.read_to_end(&mut self.buffer)?;
+                    &self.buffer
+                }
+                Compression::Zstd => {
+                    self.buffer.clear();
+                    zstd::stream::read::Decoder::new(&self.raw_buffer[..])?
+                        .read_to_end(&mut self.buffer)?;
+                    &self.buffer
+                }
+            };
+            // packets are size-prefixed flatbuffers
+            if buffer.get(8..12) != Some(EVENTS_IDENTIFIER) {
+                return Err(Error::PacketIdentifier(stream_id));
+            }
+            let table = Table::root(buffer, 4)?;
+            let mut events = Vec::new();
+            if let Some((start, length)) = table.vector(0)? {
+                let end = start + length * EVENT_SIZE;
+                if end > buffer.len() {
+                    return Err(Error::Flatbuffer("the events vector is out of bounds"));
+                }
+                events.reserve_exact(length);
+                for bytes in buffer[start..end].chunks_exact(EVENT_SIZE) {
+                    events.push(crate::core::Event {
+                        t: i64::from_le_bytes(bytes[0..8].try_into().unwrap()) as u64,
+                        x: i16::from_le_bytes(bytes[8..10].try_into().unwrap()) as u16,
+                        y: i16::from_le_bytes(bytes[10..12].try_into().unwrap()) as u16,
+                        polarity: if bytes[12] == 0 {
+                            neuromorphic_types::DvsPolarity::Off
+                        } else {
+                            neuromorphic_types::DvsPolarity::On
+                        },
+                    });
+                }
+            }
+            return Ok(Some(Packet { stream_id, events }));
+        }
+    }
+}
+
+impl Iterator for Decoder {
+    type Item = Result<Packet, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet().transpose()
+    }
+}
+
+// Finds the event streams (type identifier "EVTS") and their resolution.
+fn parse_description(description: &str) -> Result<Vec<Stream>, Error> {
+    if description.is_empty() {
+        return Ok(Vec::new());
+    }
+    let document = roxmltree::Document::parse(description)?;
+    let mut streams = Vec::new();
+    for node in document.descendants() {
+        if !node.has_tag_name("node") {
+            continue;
+        }
+        let is_event_stream = node.children().any(|child| {
+            child.has_tag_name("attr")
+                && child.attribute("key") == Some("typeIdentifier")
+                && child.text() == Some("EVTS")
+        });
+        if !is_event_stream {
+            continue;
+        }
+        let name = node.attribute("name").unwrap_or("");
+        let id = name
+            .parse::<u32>()
+            .map_err(|_| Error::Stream(name.to_owned()))?;
+        let mut width = None;
+        let mut height = None;
+        for info in node
+            .children()
+            .filter(|child| child.has_tag_name("node") && child.attribute("name") == Some("info"))
+        {
+            for attribute in info.children().filter(|child| child.has_tag_name("attr")) {
+                let value = attribute.text().and_then(|text| text.parse::<u16>().ok());
+                match attribute.attribute("key") {
+                    Some("sizeX") => width = value,
+                    Some("sizeY") => height = value,
+                    _ => {}
+                }
+            }
+        }
+        match (width, height) {
+            (Some(width), Some(height)) => streams.push(Stream { id, width, height }),
+            _ => return Err(Error::Stream(name.to_owned())),
+        }
+    }
+    Ok(streams)
+}
+
+// Minimal flatbuffers table reader, see https://flatbuffers.dev/internals/.
+struct Table<'a> {
+    buffer: &'a [u8],
+    position: usize,
+    vtable: usize,
+    vtable_length: usize,
+}
+
+impl<'a> Table<'a> {
+    fn root(buffer: &'a [u8], offset: usize) -> Result<Self, Error> {
+        let position = offset + read_u32(buffer, offset)? as usize;
+        let vtable = (position as i64 - read_u32(buffer, position)? as i32 as i64)
+            .try_into()
+            .map_err(|_| Error::Flatbuffer("the vtable offset is out of bounds"))?;
+        let vtable_length = read_u16(buffer, vtable)? as usize;
+        Ok(Self {
+            buffer,
+            position,
+            vtable,
+            vtable_length,
+        })
+    }
+
+    fn field(&self, index: usize) -> Result<Option<usize>, Error> {
+        let entry = 4 + 2 * index;
+        if entry + 2 > self.vtable_length {
+            return Ok(None);
+        }
+        match read_u16(self.buffer, self.vtable + entry)? {
+            0 => Ok(None),
+            offset => Ok(Some(self.position + offset as usize)),
+        }
+    }
+
+    fn i32(&self, index: usize, default: i32) -> Result<i32, Error> {
+        match self.field(index)? {
+            Some(position) => Ok(read_u32(self.buffer, position)? as i32),
+            None => Ok(default),
+        }
+    }
+
+    fn i64(&self, index: usize, default: i64) -> Result<i64, Error> {
+        match self.field(index)? {
+            Some(position) => Ok(i64::from_le_bytes(
+                self.buffer
+                    .get(position..position + 8)
+                    .ok_or(Error::Flatbuffer("a field is out of bounds"))?
+                    .try_into()
+                    .unwrap(),
+            )),
+            None => Ok(default),
+        }
+    }
+
+    // Returns the position of the first element and the number of elements.
+    fn vector(&self, index: usize) -> Result<Option<(usize, usize)>, Error> {
+        match self.field(index)? {
+            Some(position) => {
+                let start = position + read_u32(self.buffer, position)? as usize;
+                Ok(Some((start + 4, read_u32(self.buffer, start)? as usize)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn string(&self, index: usize) -> Result<Option<&'a str>, Error> {
+        match self.vector(index)? {
+            Some((start, length)) => Ok(Some(
+                std::str::from_utf8(
+                    self.buffer
+                        .get(start..start + length)
+                        .ok_or(Error::Flatbuffer("a string is out of bounds"))?,
+                )
+                .map_err(|_| Error::Flatbuffer("a string is not valid UTF-8"))?,
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+fn read_u16(buffer: &[u8], position: usize) -> Result<u16, Error> {
+    Ok(u16::from_le_bytes(
+        buffer
+            .get(position..position + 2)
+            .ok_or(Error::Flatbuffer("a field is out of bounds"))?
+            .try_into()
+            .unwrap(),
+    ))
+}
+
+fn read_u32(buffer: &[u8], position: usize) -> Result<u32, Error> {
+    Ok(u32::from_le_bytes(
+        buffer
+            .get(position..position + 4)
+            .ok_or(Error::Flatbuffer("a field is out of bounds"))?
+            .try_into()
+            .unwrap(),
+    ))
+}
@@ -0,0 +1,69 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Normal,
+    Low,
+    High,
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            State::Normal => "normal",
+            State::Low => "low",
+            State::High => "high",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    pub t: u64, // µs, timestamp of the sample that triggered the transition
+    pub state: State,
+}
+
+// RPM bounds (None disables a bound) checked against the smoothed RPM of every sample. The
+// alarm is raised as soon as the RPM crosses a bound, and cleared only once the RPM is back
+// within the bounds by at least hysteresis (RPM), so that a noisy RPM close to a bound does not
+// toggle the alarm at every sample. Samples without a detected frequency and windows without
+// events (a still rotor) count as 0 RPM, samples emitted during the warmup (NaN RPM) leave the
+// state as is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alarm {
+    pub min_rpm: Option<f32>,
+    pub max_rpm: Option<f32>,
+    pub hysteresis: f32,
+}
+
+impl Alarm {
+    pub fn validate(&self) -> Result<(), crate::core::Error> {
+        for threshold in [self.min_rpm, self.max_rpm].into_iter().flatten() {
+            if !threshold.is_finite() {
+                return Err(crate::core::Error::AlarmThreshold(threshold));
+            }
+        }
+        if let (Some(min_rpm), Some(max_rpm)) = (self.min_rpm, self.max_rpm) {
+            if min_rpm >= max_rpm {
+                return Err(crate::core::Error::AlarmRange { min_rpm, max_rpm });
+            }
+        }
+        if self.hysteresis < 0.0 || !self.hysteresis.is_finite() {
+            return Err(crate::core::Error::AlarmHysteresis(self.hysteresis));
+        }
+        Ok(())
+    }
+
+    pub fn next_state(&self, state: State, rpm: f32) -> State {
+        if rpm.is_nan() {
+            return state;
+        }
+        let below = |margin: f32| self.min_rpm.is_some_and(|min_rpm| rpm < min_rpm + margin);
+        let above = |margin: f32| self.max_rpm.is_some_and(|max_rpm| rpm > max_rpm - margin);
+        match state {
+            State::Normal | State::High if below(0.0) => State::Low,
+            State::Normal | State::Low if above(0.0) => State::High,
+            State::Low if below(self.hysteresis) => State::Low,
+            State::High if above(self.hysteresis) => State::High,
+            _ => State::Normal,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+// Degradations applied when process (or flush) calls exceed Configuration::max_latency_ms. Each
+// level includes the previous ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Degradation {
+    #[default]
+    None,
+    FewerTimelines, // half of most_active_timelines (at least one)
+    SkippedWindows, // every other sample window is merged into the next one
+    CoarseGrid,     // blocks twice as large, without anti-aliasing
+}
+
+impl Degradation {
+    pub const ALL: [Degradation; 4] = [
+        Degradation::None,
+        Degradation::FewerTimelines,
+        Degradation::SkippedWindows,
+        Degradation::CoarseGrid,
+    ];
+
+    // The level goes up by one after a call that exceeded the budget, and down by one after a
+    // call that took less than half of it, so that the estimator does not oscillate between two
+    // levels whose latencies straddle the budget.
+    pub fn next(self, latency: std::time::Duration, max_latency: std::time::Duration) -> Self {
+        let index = self as usize;
+        if latency > max_latency {
+            Self::ALL[(index + 1).min(Self::ALL.len() - 1)]
+        } else if latency * 2 < max_latency {
+            Self::ALL[index.saturating_sub(1)]
+        } else {
+            self
+        }
+    }
+
+    // Degradations applied at this level, from the least to the most severe.
+    pub fn applied(self) -> &'static [Degradation] {
+        &Self::ALL[1..=self as usize]
+    }
+}
+
+impl std::fmt::Display for Degradation {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Degradation::None => "none",
+            Degradation::FewerTimelines => "fewer_timelines",
+            Degradation::SkippedWindows => "skipped_windows",
+            Degradation::CoarseGrid => "coarse_grid",
+        })
+    }
+}
@@ -0,0 +1,174 @@
+// C interface, see include/fidget_spinner.h (which documents the safety contracts).
+#![allow(clippy::missing_safety_doc)]
+
+// Same layout as rpm_event.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Event {
+    pub t: u64, // µs
+    pub x: u16,
+    pub y: u16,
+    pub on: u8,
+}
+
+// Same layout as rpm_sample.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct Sample {
+    pub t: u64, // µs, end of the sample window
+    pub rpm: f32,
+    pub confidence: f32,
+    pub smoothed_rpm: f32,
+    pub phase: f32, // rad, NaN if no frequency or flip was detected
+}
+
+pub struct Rpm {
+    estimator: crate::core::RpmEstimator,
+    latest: Option<crate::core::Sample>,
+    error: std::ffi::CString,
+}
+
+impl Rpm {
+    fn new(estimator: crate::core::RpmEstimator) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            estimator,
+            latest: None,
+            error: std::ffi::CString::default(),
+        }))
+    }
+
+    fn set_error(&mut self, error: impl std::fmt::Display) {
+        self.error = std::ffi::CString::new(error.to_string().replace('\0', ""))
+            .expect("nul bytes were removed");
+    }
+}
+
+// Returns NULL if the dimensions are invalid.
+#[no_mangle]
+pub extern "C" fn rpm_create(width: u16, height: u16) -> *mut Rpm {
+    match crate::core::RpmEstimator::new(crate::core::Configuration {
+        width,
+        height,
+        ..Default::default()
+    }) {
+        Ok(estimator) => Rpm::new(estimator),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+// Restores an estimator saved with RpmCalculator.save_state, which gives access to the full
+// configuration. Returns NULL if the state is invalid.
+#[no_mangle]
+pub unsafe extern "C" fn rpm_create_from_state(state: *const u8, length: usize) -> *mut Rpm {
+    if state.is_null() {
+        return std::ptr::null_mut();
+    }
+    match crate::core::RpmEstimator::from_state(std::slice::from_raw_parts(state, length)) {
+        Ok(estimator) => Rpm::new(estimator),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rpm_destroy(rpm: *mut Rpm) {
+    if !rpm.is_null() {
+        drop(Box::from_raw(rpm));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rpm_reset(rpm: *mut Rpm) {
+    if let Some(rpm) = rpm.as_mut() {
+        rpm.estimator.reset();
+        rpm.latest = None;
+    }
+}
+
+// Returns 0 on success and -1 on error (see rpm_error).
+#[no_mangle]
+pub unsafe extern "C" fn rpm_process_events(
+    rpm: *mut Rpm,
+    events: *const Event,
+    length: usize,
+    amplitude_threshold: f32,
+    autocorrelation_threshold: f32,
+) -> i32 {
+    let Some(rpm) = rpm.as_mut() else {
+        return -1;
+    };
+    let events = if length == 0 {
+        &[]
+    } else if events.is_null() {
+        rpm.set_error("events is NULL");
+        return -1;
+    } else {
+        std::slice::from_raw_parts(events, length)
+    };
+    let parameters = crate::core::Parameters {
+        amplitude_threshold,
+        autocorrelation_threshold,
+        frequency_multiplier: 1.0,
+    };
+    // panics must not unwind into the caller
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        rpm.estimator.process_events(
+            events.iter().map(|event| crate::core::Event {
+                t: event.t,
+                x: event.x,
+                y: event.y,
+                polarity: if event.on == 0 {
+                    neuromorphic_types::DvsPolarity::Off
+                } else {
+                    neuromorphic_types::DvsPolarity::On
+                },
+            }),
+            &parameters,
+        )
+    }));
+    match result {
+        Ok(Ok(())) => {
+            if let Some(sample) = rpm.estimator.samples().last() {
+                rpm.latest = Some(*sample);
+            }
+            0
+        }
+        Ok(Err(error)) => {
+            rpm.set_error(error);
+            -1
+        }
+        Err(_) => {
+            rpm.set_error("the estimator panicked");
+            -1
+        }
+    }
+}
+
+// Writes the most recent sample and returns 1, or returns 0 if no sample has been computed yet.
+#[no_mangle]
+pub unsafe extern "C" fn rpm_latest(rpm: *const Rpm, sample: *mut Sample) -> i32 {
+    let (Some(rpm), Some(sample)) = (rpm.as_ref(), sample.as_mut()) else {
+        return 0;
+    };
+    match rpm.latest {
+        Some(latest) => {
+            *sample = Sample {
+                t: latest.t,
+                rpm: latest.rpm,
+                confidence: latest.confidence,
+                smoothed_rpm: latest.smoothed_rpm,
+                phase: latest.phase.unwrap_or(f32::NAN),
+            };
+            1
+        }
+        None => 0,
+    }
+}
+
+// Message of the last error, valid until the next call with the same handle.
+#[no_mangle]
+pub unsafe extern "C" fn rpm_error(rpm: *const Rpm) -> *const std::ffi::c_char {
+    match rpm.as_ref() {
+        Some(rpm) => rpm.error.as_ptr(),
+        None => c"rpm is NULL".as_ptr(),
+    }
+}
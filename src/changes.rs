@@ -0,0 +1,121 @@
+pub const DIRECTION_CONFIRMATIONS: usize = 2; // consecutive samples
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Acceleration,
+    Deceleration,
+    Stop,
+    Start,
+    Reversal,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Kind::Acceleration => "acceleration",
+            Kind::Deceleration => "deceleration",
+            Kind::Stop => "stop",
+            Kind::Start => "start",
+            Kind::Reversal => "reversal",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub t: u64, // µs, timestamp of the sample that revealed the change
+    pub kind: Kind,
+}
+
+// Detects changes on the trace of samples. A stop is a sample without a detected frequency after
+// a sample with one, and a start the opposite (the first sample reports neither). Acceleration
+// and deceleration are reported once when the rate of change of the (smoothed) frequency
+// between consecutive running samples exceeds the threshold, and again only after it fell below.
+// A reversal is a direction different from the last one (across stops), seen in
+// DIRECTION_CONFIRMATIONS consecutive samples.
+#[derive(Debug, Clone, Default)]
+pub struct Detector {
+    running: Option<bool>,
+    previous: Option<(u64, f32)>, // µs and Hz, last running sample
+    trend: Option<Kind>,
+    direction: Option<crate::tracking::Direction>,
+    candidate: Option<(crate::tracking::Direction, usize)>,
+}
+
+impl Detector {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    // threshold is in Hz/s. frequency is 0 if no peak was detected.
+    pub fn update(
+        &mut self,
+        t: u64,
+        frequency: f32,
+        smoothed_frequency: f32,
+        direction: Option<crate::tracking::Direction>,
+        threshold: f64,
+        changes: &mut Vec<Change>,
+    ) {
+        let running = frequency > 0.0;
+        match self.running {
+            Some(true) if !running => changes.push(Change {
+                t,
+                kind: Kind::Stop,
+            }),
+            Some(false) if running => changes.push(Change {
+                t,
+                kind: Kind::Start,
+            }),
+            _ => {}
+        }
+        self.running = Some(running);
+        if !running {
+            self.previous = None;
+            self.trend = None;
+            self.candidate = None;
+            return;
+        }
+        if let Some((previous_t, previous_frequency)) = self.previous {
+            if t > previous_t {
+                let rate = (smoothed_frequency - previous_frequency) as f64
+                    / ((t - previous_t) as f64 / 1e6); // Hz/s
+                let trend = if rate >= threshold {
+                    Some(Kind::Acceleration)
+                } else if rate <= -threshold {
+                    Some(Kind::Deceleration)
+                } else {
+                    None
+                };
+                if let Some(kind) = trend.filter(|kind| self.trend != Some(*kind)) {
+                    changes.push(Change { t, kind });
+                }
+                self.trend = trend;
+            }
+        }
+        self.previous = Some((t, smoothed_frequency));
+        match direction {
+            Some(direction) if self.direction.is_none_or(|previous| previous == direction) => {
+                self.direction = Some(direction);
+                self.candidate = None;
+            }
+            Some(direction) => {
+                let confirmations = match self.candidate {
+                    Some((candidate, confirmations)) if candidate == direction => confirmations + 1,
+                    _ => 1,
+                };
+                if confirmations >= DIRECTION_CONFIRMATIONS {
+                    changes.push(Change {
+                        t,
+                        kind: Kind::Reversal,
+                    });
+                    self.direction = Some(direction);
+                    self.candidate = None;
+                } else {
+                    self.candidate = Some((direction, confirmations));
+                }
+            }
+            None => {}
+        }
+    }
+}
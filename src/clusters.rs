@@ -0,0 +1,32 @@
+pub const CLUSTER_DISTANCE: u16 = 2; // downsampled pixels
+
+// Groups pixels whose Chebyshev distance is smaller than or equal to CLUSTER_DISTANCE
+// (single-linkage), writes the cluster index of each position in labels, and returns the
+// number of clusters.
+pub fn label(positions: &[(u16, u16)], labels: &mut Vec<usize>) -> usize {
+    labels.clear();
+    labels.resize(positions.len(), usize::MAX);
+    let mut labels_count = 0;
+    let mut stack = Vec::new();
+    for seed in 0..positions.len() {
+        if labels[seed] != usize::MAX {
+            continue;
+        }
+        labels[seed] = labels_count;
+        stack.push(seed);
+        while let Some(index) = stack.pop() {
+            let (x, y) = positions[index];
+            for (other_index, (other_x, other_y)) in positions.iter().enumerate() {
+                if labels[other_index] == usize::MAX
+                    && x.abs_diff(*other_x) <= CLUSTER_DISTANCE
+                    && y.abs_diff(*other_y) <= CLUSTER_DISTANCE
+                {
+                    labels[other_index] = labels_count;
+                    stack.push(other_index);
+                }
+            }
+        }
+        labels_count += 1;
+    }
+    labels_count
+}
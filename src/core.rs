@@ -0,0 +1,4457 @@
+use rayon::prelude::*;
+
+pub const DEFAULT_WIDTH: u16 = 1280;
+pub const DEFAULT_HEIGHT: u16 = 720;
+pub const DEFAULT_SPATIAL_DOWNSAMPLING: u16 = 4;
+pub const DEFAULT_SIGN_CHECK_RADIUS: u16 = 1; // blocks
+pub const DEFAULT_DEBOUNCE_EVENTS: u16 = 2;
+pub const DEFAULT_ACTIVITY_TAU: u64 = 10000; // µs
+pub const DEFAULT_RANKING_WINDOW: u64 = 100000; // µs
+pub const DEFAULT_TIMELINE_LENGTH: usize = 256; // flips per timeline
+pub const DEFAULT_FLIPS_CAPACITY: usize = 4096; // recorded flips per block
+pub const DEFAULT_SAMPLING_FREQUENCY: f64 = 10.0; // Hz
+pub const DEFAULT_MOST_ACTIVE_TIMELINES: usize = 32;
+pub const DEFAULT_FFT_FREQUENCY: f64 = 512.0; // Hz
+pub const DEFAULT_FFT_SAMPLES: usize = 1024; // samples
+pub const MINIMUM_FREQUENCY: f64 = 5.0; // Hz
+pub const DEFAULT_LOW_FREQUENCY_RATE: f64 = 100.0; // Hz
+pub const REFINE_STEPS: usize = 16; // frequencies per refinement iteration
+pub const REFINE_ITERATIONS: usize = 4;
+pub const DEFAULT_AMPLITUDE_THRESHOLD: f32 = 10.0;
+pub const DEFAULT_AUTOCORRELATION_THRESHOLD: f32 = 0.4;
+pub const DEFAULT_HARMONICS: usize = 5;
+pub const DEFAULT_MAXIMUM_BLADE_COUNT: usize = 8;
+
+pub type Event = neuromorphic_types::DvsEvent<u64, u16, u16>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("width and height must be larger than zero (got {width}x{height})")]
+    Resolution { width: u16, height: u16 },
+
+    #[error("event {index} is out of bounds (x={x}, y={y}, width={width}, height={height})")]
+    OutOfBounds {
+        index: usize,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+
+    #[error("fft_samples must be an even number larger than or equal to 4 (got {0})")]
+    FftSamples(usize),
+
+    #[error("fft_frequency must be a strictly positive number (got {0})")]
+    FftFrequency(f64),
+
+    #[error(
+        "the FFT resolution ({fft_frequency} Hz / {fft_samples} samples) is too coarse to skip frequencies below {minimum_frequency} Hz"
+    )]
+    FftResolution {
+        fft_samples: usize,
+        fft_frequency: f64,
+        minimum_frequency: f64,
+    },
+
+    #[error(
+        "the region of interest must satisfy x0 < x1 <= {width} and y0 < y1 <= {height} (got x0={x0}, y0={y0}, x1={x1}, y1={y1})"
+    )]
+    Roi {
+        x0: u16,
+        y0: u16,
+        x1: u16,
+        y1: u16,
+        width: u16,
+        height: u16,
+    },
+
+    #[error("the mask must have {expected} elements (got {actual})")]
+    MaskLength { expected: usize, actual: usize },
+
+    #[error("sampling_frequency must be a strictly positive number (got {0})")]
+    SamplingFrequency(f64),
+
+    #[error("hop_duration must be a strictly positive number (got {0})")]
+    HopDuration(f64),
+
+    #[error("events_per_sample must be larger than zero")]
+    EventsPerSample,
+
+    #[error("unknown timestamp policy \"{0}\" (expected \"skip\", \"clamp\", or \"error\")")]
+    TimestampPolicy(String),
+
+    #[error(
+        "unknown method \"{0}\" (expected \"autocorrelation\", \"harmonic_product\", or \"lombscargle\")"
+    )]
+    Method(String),
+
+    #[error(
+        "unknown window function \"{0}\" (expected \"rectangular\", \"hann\", \"hamming\", or \"blackman_harris\")"
+    )]
+    WindowFunction(String),
+
+    #[error(
+        "window_duration must be a strictly positive number smaller than or equal to fft_samples / fft_frequency ({maximum} s, got {window_duration})"
+    )]
+    WindowDuration { window_duration: f64, maximum: f64 },
+
+    #[error("hot_pixel_ratio must be a number larger than 1 (got {0})")]
+    HotPixelRatio(f64),
+
+    #[error("spatial_downsampling must be larger than zero")]
+    SpatialDownsampling,
+
+    #[error("refractory_period must be larger than zero")]
+    RefractoryPeriod,
+
+    #[error("wrap_period must be larger than zero")]
+    WrapPeriod,
+
+    #[error("noise_filter_window must be larger than zero")]
+    NoiseFilterWindow,
+
+    #[error("noise_filter_neighbours must be in the range [1, 8] (got {0})")]
+    NoiseFilterNeighbours(usize),
+
+    #[error("hot_pixel_window must be larger than zero")]
+    HotPixelWindow,
+
+    #[error("the pixel ({x}, {y}) is out of bounds (width={width}, height={height})")]
+    Pixel {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+
+    #[error("harmonics must be larger than zero")]
+    Harmonics,
+
+    #[error("maximum_blade_count must be larger than zero")]
+    MaximumBladeCount,
+
+    #[error("most_active_timelines must be larger than zero")]
+    MostActiveTimelines,
+
+    #[error("timeline_length must be larger than zero")]
+    TimelineLength,
+
+    #[error("activity_tau must be larger than zero")]
+    ActivityTau,
+
+    #[error("unknown ranking \"{0}\" (expected \"activity\" or \"count\")")]
+    Ranking(String),
+
+    #[error("ranking_window must be larger than zero")]
+    RankingWindow,
+
+    #[error("unknown mode \"{0}\" (expected \"rotation\" or \"vibration\")")]
+    Mode(String),
+
+    #[error("unknown sign detection \"{0}\" (expected \"neighbourhood\" or \"transition\")")]
+    SignDetection(String),
+
+    #[error("debounce_events must be larger than zero")]
+    DebounceEvents,
+
+    #[error("max_latency_ms must be a strictly positive number (got {0})")]
+    MaxLatency(f64),
+
+    #[error("unknown subsampling \"{0}\" (expected \"random\" or \"stratified\")")]
+    Subsampling(String),
+
+    #[error("subsampling_threshold must be a strictly positive number (got {0})")]
+    SubsamplingThreshold(f64),
+
+    #[error("blades must be at least 2 (got {0})")]
+    Blades(usize),
+
+    #[error("flips_capacity must be larger than zero")]
+    FlipsCapacity,
+
+    #[error("unknown low frequency mode \"{0}\" (expected \"off\", \"on\", or \"adaptive\")")]
+    LowFrequency(String),
+
+    #[error(
+        "low_frequency_rate must be a strictly positive number smaller than fft_frequency ({fft_frequency} Hz, got {low_frequency_rate})"
+    )]
+    LowFrequencyRate {
+        low_frequency_rate: f64,
+        fft_frequency: f64,
+    },
+
+    #[error("change_threshold must be a strictly positive number (got {0})")]
+    ChangeThreshold(f64),
+
+    #[error("spectral_smoothing must be in the range ]0, 1] (got {0})")]
+    SpectralSmoothing(f64),
+
+    #[error(
+        "sign_check_tolerance must be smaller than the number of blocks in the neighbourhood ({blocks} for sign_check_radius {sign_check_radius}, got {sign_check_tolerance})"
+    )]
+    SignCheckTolerance {
+        sign_check_tolerance: usize,
+        sign_check_radius: u16,
+        blocks: usize,
+    },
+
+    #[error("unknown time unit \"{0}\" (expected \"us\", \"ns\", \"ms\", or a scale)")]
+    TimeUnit(String),
+
+    #[error("the time unit scale must be larger than 0 (got {0})")]
+    TimeScale(f64),
+
+    #[error(
+        "warmup_timelines must be smaller than or equal to the number of downsampled pixels ({maximum}, got {warmup_timelines})"
+    )]
+    WarmupTimelines {
+        warmup_timelines: usize,
+        maximum: usize,
+    },
+
+    #[error("activity_fraction must be in the range ]0, 1] (got {0})")]
+    ActivityFraction(f64),
+
+    #[error("flicker_rejection must be in the range ]0, 1] (got {0})")]
+    FlickerRejection(f64),
+
+    #[error("a region named \"{0}\" already exists")]
+    DuplicateRegion(String),
+
+    #[error("there is no region named \"{0}\"")]
+    UnknownRegion(String),
+
+    #[error("zero_padding must be larger than zero")]
+    ZeroPadding,
+
+    #[error("unknown smoothing \"{0}\" (expected \"none\", \"exponential\", or \"kalman\")")]
+    Smoothing(String),
+
+    #[error("smoothing_alpha must be in the range ]0, 1] (got {0})")]
+    SmoothingAlpha(f64),
+
+    #[error(
+        "process_noise and measurement_noise must be strictly positive numbers (got {process_noise} and {measurement_noise})"
+    )]
+    SmoothingNoise {
+        process_noise: f64,
+        measurement_noise: f64,
+    },
+
+    #[error(
+        "event {index} has a timestamp ({t} µs) earlier than the previous event ({previous_t} µs)"
+    )]
+    NonMonotonic {
+        index: usize,
+        t: u64,
+        previous_t: u64,
+    },
+
+    #[error("t_end ({t_end} µs) is earlier than the last event ({previous_t} µs)")]
+    FlushTimestamp { t_end: u64, previous_t: u64 },
+
+    #[error("the scale must be a strictly positive number (got {0})")]
+    Scale(f64),
+
+    #[error("alarm thresholds must be finite (got {0})")]
+    AlarmThreshold(f32),
+
+    #[error("min_rpm must be smaller than max_rpm (got {min_rpm} and {max_rpm})")]
+    AlarmRange { min_rpm: f32, max_rpm: f32 },
+
+    #[error("the alarm hysteresis must be a positive number (got {0})")]
+    AlarmHysteresis(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampPolicy {
+    Skip,
+    Clamp,
+    Error,
+}
+
+impl std::str::FromStr for TimestampPolicy {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "skip" => Ok(TimestampPolicy::Skip),
+            "clamp" => Ok(TimestampPolicy::Clamp),
+            "error" => Ok(TimestampPolicy::Error),
+            _ => Err(Error::TimestampPolicy(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for TimestampPolicy {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            TimestampPolicy::Skip => "skip",
+            TimestampPolicy::Clamp => "clamp",
+            TimestampPolicy::Error => "error",
+        })
+    }
+}
+
+// LombScargle replaces the FFT of the resampled timelines with a Lomb-Scargle periodogram of
+// the flip timestamps and detects the peak with the autocorrelation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Autocorrelation,
+    HarmonicProduct,
+    LombScargle,
+}
+
+impl std::str::FromStr for Method {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "autocorrelation" => Ok(Method::Autocorrelation),
+            "harmonic_product" => Ok(Method::HarmonicProduct),
+            "lombscargle" => Ok(Method::LombScargle),
+            _ => Err(Error::Method(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Method::Autocorrelation => "autocorrelation",
+            Method::HarmonicProduct => "harmonic_product",
+            Method::LombScargle => "lombscargle",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFunction {
+    Rectangular,
+    Hann,
+    Hamming,
+    BlackmanHarris,
+}
+
+impl std::str::FromStr for WindowFunction {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "rectangular" => Ok(WindowFunction::Rectangular),
+            "hann" => Ok(WindowFunction::Hann),
+            "hamming" => Ok(WindowFunction::Hamming),
+            "blackman_harris" => Ok(WindowFunction::BlackmanHarris),
+            _ => Err(Error::WindowFunction(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for WindowFunction {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            WindowFunction::Rectangular => "rectangular",
+            WindowFunction::Hann => "hann",
+            WindowFunction::Hamming => "hamming",
+            WindowFunction::BlackmanHarris => "blackman_harris",
+        })
+    }
+}
+
+// Activity ranks timelines by their exponentially decayed event count (time constant
+// activity_tau), Count ranks them by the number of flips in the last ranking_window µs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ranking {
+    Activity,
+    Count,
+}
+
+impl std::str::FromStr for Ranking {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "activity" => Ok(Ranking::Activity),
+            "count" => Ok(Ranking::Count),
+            _ => Err(Error::Ranking(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for Ranking {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Ranking::Activity => "activity",
+            Ranking::Count => "count",
+        })
+    }
+}
+
+// Slow targets (ceiling fans for instance) need windows of several seconds. The low frequency
+// analysis resamples the most active timelines at low_frequency_rate instead of fft_frequency,
+// hence its window is fft_frequency / low_frequency_rate times longer and it detects
+// frequencies down to MINIMUM_FREQUENCY * low_frequency_rate / fft_frequency. On uses it for
+// every sample, and Adaptive only when it detects a frequency below MINIMUM_FREQUENCY (which the
+// standard analysis ignores). Spectra (see RpmEstimator::spectrum) are always those of the
+// standard analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LowFrequency {
+    Off,
+    On,
+    Adaptive,
+}
+
+impl std::str::FromStr for LowFrequency {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "off" => Ok(LowFrequency::Off),
+            "on" => Ok(LowFrequency::On),
+            "adaptive" => Ok(LowFrequency::Adaptive),
+            _ => Err(Error::LowFrequency(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for LowFrequency {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            LowFrequency::Off => "off",
+            LowFrequency::On => "on",
+            LowFrequency::Adaptive => "adaptive",
+        })
+    }
+}
+
+// Rotation records a flip when the sign of a block changes (edges sweeping past, see
+// SignDetection), Vibration records a flip whenever the polarity of a block's last event changes
+// (edges oscillating in place, for instance on vibrating machinery).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Rotation,
+    Vibration,
+}
+
+impl std::str::FromStr for Mode {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "rotation" => Ok(Mode::Rotation),
+            "vibration" => Ok(Mode::Vibration),
+            _ => Err(Error::Mode(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Mode::Rotation => "rotation",
+            Mode::Vibration => "vibration",
+        })
+    }
+}
+
+// Sign of a block in Rotation mode. Neighbourhood is the common polarity of the block's
+// neighbourhood (see Configuration::sign_check_radius), which rejects isolated noise events but
+// rarely agrees under heavy noise. Transition is the polarity of the block's own events once
+// debounce_events consecutive events share it, which keeps working under noise but also follows
+// texture within a blade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignDetection {
+    Neighbourhood,
+    Transition,
+}
+
+impl std::str::FromStr for SignDetection {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "neighbourhood" => Ok(SignDetection::Neighbourhood),
+            "transition" => Ok(SignDetection::Transition),
+            _ => Err(Error::SignDetection(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for SignDetection {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            SignDetection::Neighbourhood => "neighbourhood",
+            SignDetection::Transition => "transition",
+        })
+    }
+}
+
+// Unit of the input timestamps, which are converted to µs on ingest. Scale is the duration of
+// a timestamp tick in µs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TimeUnit {
+    Microseconds,
+    Nanoseconds,
+    Milliseconds,
+    Scale(f64),
+}
+
+impl std::str::FromStr for TimeUnit {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "us" => Ok(TimeUnit::Microseconds),
+            "ns" => Ok(TimeUnit::Nanoseconds),
+            "ms" => Ok(TimeUnit::Milliseconds),
+            _ => string
+                .parse()
+                .map(TimeUnit::Scale)
+                .map_err(|_| Error::TimeUnit(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for TimeUnit {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimeUnit::Microseconds => formatter.write_str("us"),
+            TimeUnit::Nanoseconds => formatter.write_str("ns"),
+            TimeUnit::Milliseconds => formatter.write_str("ms"),
+            TimeUnit::Scale(scale) => write!(formatter, "{scale}"),
+        }
+    }
+}
+
+impl TimeUnit {
+    pub fn microseconds(&self, t: u64) -> u64 {
+        match self {
+            TimeUnit::Microseconds => t,
+            TimeUnit::Nanoseconds => t / 1000,
+            TimeUnit::Milliseconds => t.saturating_mul(1000),
+            TimeUnit::Scale(scale) => (t as f64 * scale) as u64,
+        }
+    }
+}
+
+impl WindowFunction {
+    // Symmetric window with the given number of samples.
+    pub fn coefficients(self, length: usize) -> Vec<f32> {
+        let cosine_terms: &[f64] = match self {
+            WindowFunction::Rectangular => &[1.0],
+            WindowFunction::Hann => &[0.5, -0.5],
+            WindowFunction::Hamming => &[0.54, -0.46],
+            WindowFunction::BlackmanHarris => &[0.35875, -0.48829, 0.14128, -0.01168],
+        };
+        if length < 2 {
+            return vec![1.0; length];
+        }
+        (0..length)
+            .map(|index| {
+                let phase = 2.0 * std::f64::consts::PI * index as f64 / (length - 1) as f64;
+                cosine_terms
+                    .iter()
+                    .enumerate()
+                    .map(|(order, term)| term * (order as f64 * phase).cos())
+                    .sum::<f64>() as f32
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Configuration {
+    pub width: u16,
+    pub height: u16,
+    pub fft_samples: usize,
+    pub fft_frequency: f64,
+    pub timestamp_policy: TimestampPolicy,
+    pub sampling_frequency: f64,
+    pub events_per_sample: Option<usize>,
+    pub method: Method,
+    pub harmonics: usize,
+    pub window_duration: Option<f64>, // s, defaults to fft_samples / fft_frequency
+    pub hot_pixel_ratio: Option<f64>, // None disables hot pixel detection
+    pub hot_pixel_window: u64,        // µs
+    pub refractory_period: Option<u64>, // µs
+    pub noise_filter_window: Option<u64>, // µs, None disables the background activity filter
+    pub noise_filter_neighbours: usize,
+    pub spatial_downsampling: u16, // 1 disables downsampling
+    pub anti_aliasing: bool,       // events also update the blocks within half a block
+    pub most_active_timelines: usize,
+    pub window_function: WindowFunction, // applied to the last window_duration seconds
+    pub zero_padding: usize,             // the FFT length is fft_samples * zero_padding
+    pub peak_interpolation: bool,        // parabolic interpolation around the detected peak
+    pub smoothing: crate::smoothing::Smoothing,
+    pub smoothing_alpha: f64,   // exponential smoothing only
+    pub process_noise: f64,     // Hz² s⁻³, Kalman smoothing only
+    pub measurement_noise: f64, // Hz², Kalman smoothing only
+    pub activity_tau: u64,      // µs
+    pub ranking: Ranking,
+    pub ranking_window: u64,    // µs, count ranking only
+    pub timeline_length: usize, // flips, older flips are overwritten
+    pub mode: Mode,
+    pub warmup_events: u64, // accepted events since the last reset before samples are valid
+    pub warmup_timelines: usize, // non-empty timelines before samples are valid
+    // None uses the most_active_timelines most active timelines, otherwise timelines whose
+    // activity (or count) is larger than activity_fraction times the largest are used, up to
+    // most_active_timelines
+    pub activity_fraction: Option<f64>,
+    pub strobe_length: usize, // accepted events kept for strobe images, 0 disables them
+    // None disables flicker rejection, otherwise lighting flicker is detected when at least
+    // flicker_rejection times the selected timelines peak at harmonics of the same flicker
+    // frequency (see crate::flicker). These timelines are left out of the sum and are no longer
+    // selected until the next reset.
+    pub flicker_rejection: Option<f64>,
+    // input timestamps are converted to µs on ingest, durations in the configuration are in µs
+    pub time_unit: TimeUnit,
+    // input ticks, None disables unwrapping, otherwise timestamps that jump backwards by more
+    // than half the wrap period start a new epoch (for instance 1 << 32 for 32-bit clocks)
+    pub wrap_period: Option<u64>,
+    // after each sample, the ROI (see RpmEstimator::set_roi) is moved without resizing it so
+    // that it stays centred on the tracked spinner (see Sample::track)
+    pub roi_tracking: bool,
+    // 0 disables the radial profile, otherwise timelines are binned by their distance to the
+    // rotor's centre in the rotation plane and each bin is estimated independently (see
+    // RpmEstimator::radial_profiles)
+    pub radial_bins: usize,
+    pub low_frequency: LowFrequency,
+    pub low_frequency_rate: f64, // Hz, ignored if low_frequency is Off
+    // refines the spectrum's peak (whose accuracy is the frequency resolution) to the frequency
+    // whose harmonics maximize the phase coherence of the selected timelines' flips
+    pub refine: bool,
+    // Hz/s, None disables change detection, otherwise rates of change of the (smoothed)
+    // frequency larger than the threshold are reported as accelerations or decelerations (see
+    // RpmEstimator::changes)
+    pub change_threshold: Option<f64>,
+    // None disables spectral averaging, otherwise each spectrum is blended with the running
+    // average of the previous ones, average = spectral_smoothing × spectrum + (1 -
+    // spectral_smoothing) × average, before peak detection (smaller values are steadier but
+    // slower to follow changes)
+    pub spectral_smoothing: Option<f64>,
+    // blocks, a flip is registered when the blocks within sign_check_radius of the event's block
+    // (clipped at the borders) switch to a common polarity, except for at most
+    // sign_check_tolerance blocks that may disagree or have not received events yet (Rotation
+    // mode only)
+    pub sign_check_radius: u16,
+    pub sign_check_tolerance: usize,
+    pub sign_detection: SignDetection, // Rotation mode only
+    pub debounce_events: u16,          // Transition sign detection only
+    // ms, None disables the budget, otherwise process (and flush) calls that take longer degrade
+    // the estimator one level at a time until they fit (see crate::budget::Degradation)
+    pub max_latency_ms: Option<f64>,
+    // events/s, None disables subsampling, otherwise the input events are subsampled (before
+    // filtering) whenever their rate exceeds the threshold (see crate::subsampling::Subsampler)
+    pub subsampling_threshold: Option<f64>,
+    pub subsampling: crate::subsampling::Subsampling,
+    // number of blades of the rotor, None disables the imbalance index (see
+    // Sample::imbalance_index)
+    pub blades: Option<usize>,
+    // recorded flips (see RpmEstimator::set_record_flips) per block, the oldest flips of a block
+    // are dropped when it is full
+    pub flips_capacity: usize,
+}
+
+impl Configuration {
+    pub fn builder() -> ConfigurationBuilder {
+        ConfigurationBuilder::default()
+    }
+
+    // RpmEstimator::new calls validate, calling it directly reports errors without allocating
+    // the estimator.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::Resolution {
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if self.fft_samples < 4 || !self.fft_samples.is_multiple_of(2) {
+            return Err(Error::FftSamples(self.fft_samples));
+        }
+        if self.fft_frequency <= 0.0 || !self.fft_frequency.is_finite() {
+            return Err(Error::FftFrequency(self.fft_frequency));
+        }
+        if self.zero_padding == 0 {
+            return Err(Error::ZeroPadding);
+        }
+        if self.smoothing_alpha <= 0.0
+            || self.smoothing_alpha > 1.0
+            || self.smoothing_alpha.is_nan()
+        {
+            return Err(Error::SmoothingAlpha(self.smoothing_alpha));
+        }
+        if self.process_noise <= 0.0
+            || !self.process_noise.is_finite()
+            || self.measurement_noise <= 0.0
+            || !self.measurement_noise.is_finite()
+        {
+            return Err(Error::SmoothingNoise {
+                process_noise: self.process_noise,
+                measurement_noise: self.measurement_noise,
+            });
+        }
+        let fft_length = self.fft_samples * self.zero_padding;
+        if ((MINIMUM_FREQUENCY * fft_length as f64) / self.fft_frequency).ceil() as usize
+            >= fft_length / 2
+        {
+            return Err(Error::FftResolution {
+                fft_samples: self.fft_samples,
+                fft_frequency: self.fft_frequency,
+                minimum_frequency: MINIMUM_FREQUENCY,
+            });
+        }
+        if self.sampling_frequency <= 0.0 || !self.sampling_frequency.is_finite() {
+            return Err(Error::SamplingFrequency(self.sampling_frequency));
+        }
+        if self.events_per_sample == Some(0) {
+            return Err(Error::EventsPerSample);
+        }
+        if self.harmonics == 0 {
+            return Err(Error::Harmonics);
+        }
+        if self.most_active_timelines == 0 {
+            return Err(Error::MostActiveTimelines);
+        }
+        if self.activity_tau == 0 {
+            return Err(Error::ActivityTau);
+        }
+        if self.ranking_window == 0 {
+            return Err(Error::RankingWindow);
+        }
+        if self.timeline_length == 0 {
+            return Err(Error::TimelineLength);
+        }
+        if let Some(activity_fraction) = self.activity_fraction {
+            if activity_fraction <= 0.0 || activity_fraction > 1.0 || activity_fraction.is_nan() {
+                return Err(Error::ActivityFraction(activity_fraction));
+            }
+        }
+        if let TimeUnit::Scale(scale) = self.time_unit {
+            if scale <= 0.0 || !scale.is_finite() {
+                return Err(Error::TimeScale(scale));
+            }
+        }
+        if let Some(flicker_rejection) = self.flicker_rejection {
+            if flicker_rejection <= 0.0 || flicker_rejection > 1.0 || flicker_rejection.is_nan() {
+                return Err(Error::FlickerRejection(flicker_rejection));
+            }
+        }
+        if let Some(hot_pixel_ratio) = self.hot_pixel_ratio {
+            if hot_pixel_ratio <= 1.0 || !hot_pixel_ratio.is_finite() {
+                return Err(Error::HotPixelRatio(hot_pixel_ratio));
+            }
+        }
+        if self.hot_pixel_window == 0 {
+            return Err(Error::HotPixelWindow);
+        }
+        if self.spatial_downsampling == 0 {
+            return Err(Error::SpatialDownsampling);
+        }
+        if self.refractory_period == Some(0) {
+            return Err(Error::RefractoryPeriod);
+        }
+        if self.wrap_period == Some(0) {
+            return Err(Error::WrapPeriod);
+        }
+        if self.noise_filter_window == Some(0) {
+            return Err(Error::NoiseFilterWindow);
+        }
+        if self.noise_filter_neighbours == 0 || self.noise_filter_neighbours > 8 {
+            return Err(Error::NoiseFilterNeighbours(self.noise_filter_neighbours));
+        }
+        if let Some(window_duration) = self.window_duration {
+            let maximum = self.fft_samples as f64 / self.fft_frequency;
+            if window_duration <= 0.0 || !window_duration.is_finite() || window_duration > maximum {
+                return Err(Error::WindowDuration {
+                    window_duration,
+                    maximum,
+                });
+            }
+        }
+        let downsampled_length = self.width.div_ceil(self.spatial_downsampling) as usize
+            * self.height.div_ceil(self.spatial_downsampling) as usize;
+        if self.warmup_timelines > downsampled_length {
+            return Err(Error::WarmupTimelines {
+                warmup_timelines: self.warmup_timelines,
+                maximum: downsampled_length,
+            });
+        }
+        if self.low_frequency != LowFrequency::Off
+            && (self.low_frequency_rate <= 0.0
+                || !self.low_frequency_rate.is_finite()
+                || self.low_frequency_rate >= self.fft_frequency)
+        {
+            return Err(Error::LowFrequencyRate {
+                low_frequency_rate: self.low_frequency_rate,
+                fft_frequency: self.fft_frequency,
+            });
+        }
+        if let Some(change_threshold) = self.change_threshold {
+            if change_threshold <= 0.0 || !change_threshold.is_finite() {
+                return Err(Error::ChangeThreshold(change_threshold));
+            }
+        }
+        if let Some(spectral_smoothing) = self.spectral_smoothing {
+            if spectral_smoothing <= 0.0 || spectral_smoothing > 1.0 || spectral_smoothing.is_nan()
+            {
+                return Err(Error::SpectralSmoothing(spectral_smoothing));
+            }
+        }
+        if self.debounce_events == 0 {
+            return Err(Error::DebounceEvents);
+        }
+        if let Some(max_latency_ms) = self.max_latency_ms {
+            if max_latency_ms <= 0.0 || !max_latency_ms.is_finite() {
+                return Err(Error::MaxLatency(max_latency_ms));
+            }
+        }
+        if let Some(subsampling_threshold) = self.subsampling_threshold {
+            if subsampling_threshold <= 0.0 || !subsampling_threshold.is_finite() {
+                return Err(Error::SubsamplingThreshold(subsampling_threshold));
+            }
+        }
+        if let Some(blades) = self.blades {
+            if blades < 2 {
+                return Err(Error::Blades(blades));
+            }
+        }
+        if self.flips_capacity == 0 {
+            return Err(Error::FlipsCapacity);
+        }
+        let blocks = (self.sign_check_radius as usize * 2 + 1).pow(2);
+        if self.sign_check_tolerance >= blocks {
+            return Err(Error::SignCheckTolerance {
+                sign_check_tolerance: self.sign_check_tolerance,
+                sign_check_radius: self.sign_check_radius,
+                blocks,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            fft_samples: DEFAULT_FFT_SAMPLES,
+            fft_frequency: DEFAULT_FFT_FREQUENCY,
+            timestamp_policy: TimestampPolicy::Skip,
+            sampling_frequency: DEFAULT_SAMPLING_FREQUENCY,
+            events_per_sample: None,
+            method: Method::Autocorrelation,
+            harmonics: DEFAULT_HARMONICS,
+            window_duration: None,
+            hot_pixel_ratio: None,
+            hot_pixel_window: crate::hot_pixels::DEFAULT_HOT_PIXEL_WINDOW,
+            refractory_period: None,
+            noise_filter_window: None,
+            noise_filter_neighbours: crate::noise::DEFAULT_NOISE_FILTER_NEIGHBOURS,
+            spatial_downsampling: DEFAULT_SPATIAL_DOWNSAMPLING,
+            anti_aliasing: false,
+            most_active_timelines: DEFAULT_MOST_ACTIVE_TIMELINES,
+            window_function: WindowFunction::Rectangular,
+            zero_padding: 1,
+            peak_interpolation: false,
+            smoothing: crate::smoothing::Smoothing::None,
+            smoothing_alpha: crate::smoothing::DEFAULT_SMOOTHING_ALPHA,
+            process_noise: crate::smoothing::DEFAULT_PROCESS_NOISE,
+            measurement_noise: crate::smoothing::DEFAULT_MEASUREMENT_NOISE,
+            activity_tau: DEFAULT_ACTIVITY_TAU,
+            ranking: Ranking::Activity,
+            ranking_window: DEFAULT_RANKING_WINDOW,
+            timeline_length: DEFAULT_TIMELINE_LENGTH,
+            mode: Mode::Rotation,
+            warmup_events: 0,
+            warmup_timelines: 0,
+            activity_fraction: None,
+            strobe_length: 0,
+            flicker_rejection: None,
+            time_unit: TimeUnit::Microseconds,
+            wrap_period: None,
+            roi_tracking: false,
+            radial_bins: 0,
+            low_frequency: LowFrequency::Off,
+            low_frequency_rate: DEFAULT_LOW_FREQUENCY_RATE,
+            refine: false,
+            change_threshold: None,
+            spectral_smoothing: None,
+            sign_check_radius: DEFAULT_SIGN_CHECK_RADIUS,
+            sign_check_tolerance: 0,
+            sign_detection: SignDetection::Neighbourhood,
+            debounce_events: DEFAULT_DEBOUNCE_EVENTS,
+            max_latency_ms: None,
+            subsampling_threshold: None,
+            subsampling: crate::subsampling::Subsampling::Stratified,
+            blades: None,
+            flips_capacity: DEFAULT_FLIPS_CAPACITY,
+        }
+    }
+}
+
+// Chained setters on top of the default configuration. build validates the configuration.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfigurationBuilder {
+    configuration: Configuration,
+    hop_duration: Option<f64>, // s, validated by build
+}
+
+impl ConfigurationBuilder {
+    pub fn width(mut self, width: u16) -> Self {
+        self.configuration.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: u16) -> Self {
+        self.configuration.height = height;
+        self
+    }
+
+    pub fn fft_samples(mut self, fft_samples: usize) -> Self {
+        self.configuration.fft_samples = fft_samples;
+        self
+    }
+
+    pub fn fft_frequency(mut self, fft_frequency: f64) -> Self {
+        self.configuration.fft_frequency = fft_frequency;
+        self
+    }
+
+    pub fn timestamp_policy(mut self, timestamp_policy: TimestampPolicy) -> Self {
+        self.configuration.timestamp_policy = timestamp_policy;
+        self
+    }
+
+    pub fn sampling_frequency(mut self, sampling_frequency: f64) -> Self {
+        self.configuration.sampling_frequency = sampling_frequency;
+        self.hop_duration = None;
+        self
+    }
+
+    pub fn events_per_sample(mut self, events_per_sample: Option<usize>) -> Self {
+        self.configuration.events_per_sample = events_per_sample;
+        self
+    }
+
+    pub fn method(mut self, method: Method) -> Self {
+        self.configuration.method = method;
+        self
+    }
+
+    pub fn harmonics(mut self, harmonics: usize) -> Self {
+        self.configuration.harmonics = harmonics;
+        self
+    }
+
+    pub fn window_duration(mut self, window_duration: Option<f64>) -> Self {
+        self.configuration.window_duration = window_duration;
+        self
+    }
+
+    pub fn hot_pixel_ratio(mut self, hot_pixel_ratio: Option<f64>) -> Self {
+        self.configuration.hot_pixel_ratio = hot_pixel_ratio;
+        self
+    }
+
+    pub fn hot_pixel_window(mut self, hot_pixel_window: u64) -> Self {
+        self.configuration.hot_pixel_window = hot_pixel_window;
+        self
+    }
+
+    pub fn refractory_period(mut self, refractory_period: Option<u64>) -> Self {
+        self.configuration.refractory_period = refractory_period;
+        self
+    }
+
+    pub fn noise_filter_window(mut self, noise_filter_window: Option<u64>) -> Self {
+        self.configuration.noise_filter_window = noise_filter_window;
+        self
+    }
+
+    pub fn noise_filter_neighbours(mut self, noise_filter_neighbours: usize) -> Self {
+        self.configuration.noise_filter_neighbours = noise_filter_neighbours;
+        self
+    }
+
+    pub fn spatial_downsampling(mut self, spatial_downsampling: u16) -> Self {
+        self.configuration.spatial_downsampling = spatial_downsampling;
+        self
+    }
+
+    pub fn anti_aliasing(mut self, anti_aliasing: bool) -> Self {
+        self.configuration.anti_aliasing = anti_aliasing;
+        self
+    }
+
+    pub fn most_active_timelines(mut self, most_active_timelines: usize) -> Self {
+        self.configuration.most_active_timelines = most_active_timelines;
+        self
+    }
+
+    pub fn window_function(mut self, window_function: WindowFunction) -> Self {
+        self.configuration.window_function = window_function;
+        self
+    }
+
+    pub fn zero_padding(mut self, zero_padding: usize) -> Self {
+        self.configuration.zero_padding = zero_padding;
+        self
+    }
+
+    pub fn peak_interpolation(mut self, peak_interpolation: bool) -> Self {
+        self.configuration.peak_interpolation = peak_interpolation;
+        self
+    }
+
+    pub fn smoothing(mut self, smoothing: crate::smoothing::Smoothing) -> Self {
+        self.configuration.smoothing = smoothing;
+        self
+    }
+
+    pub fn smoothing_alpha(mut self, smoothing_alpha: f64) -> Self {
+        self.configuration.smoothing_alpha = smoothing_alpha;
+        self
+    }
+
+    pub fn process_noise(mut self, process_noise: f64) -> Self {
+        self.configuration.process_noise = process_noise;
+        self
+    }
+
+    pub fn measurement_noise(mut self, measurement_noise: f64) -> Self {
+        self.configuration.measurement_noise = measurement_noise;
+        self
+    }
+
+    pub fn activity_tau(mut self, activity_tau: u64) -> Self {
+        self.configuration.activity_tau = activity_tau;
+        self
+    }
+
+    pub fn ranking(mut self, ranking: Ranking) -> Self {
+        self.configuration.ranking = ranking;
+        self
+    }
+
+    pub fn ranking_window(mut self, ranking_window: u64) -> Self {
+        self.configuration.ranking_window = ranking_window;
+        self
+    }
+
+    pub fn timeline_length(mut self, timeline_length: usize) -> Self {
+        self.configuration.timeline_length = timeline_length;
+        self
+    }
+
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.configuration.mode = mode;
+        self
+    }
+
+    pub fn warmup_events(mut self, warmup_events: u64) -> Self {
+        self.configuration.warmup_events = warmup_events;
+        self
+    }
+
+    pub fn warmup_timelines(mut self, warmup_timelines: usize) -> Self {
+        self.configuration.warmup_timelines = warmup_timelines;
+        self
+    }
+
+    pub fn activity_fraction(mut self, activity_fraction: Option<f64>) -> Self {
+        self.configuration.activity_fraction = activity_fraction;
+        self
+    }
+
+    pub fn strobe_length(mut self, strobe_length: usize) -> Self {
+        self.configuration.strobe_length = strobe_length;
+        self
+    }
+
+    pub fn flicker_rejection(mut self, flicker_rejection: Option<f64>) -> Self {
+        self.configuration.flicker_rejection = flicker_rejection;
+        self
+    }
+
+    pub fn time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.configuration.time_unit = time_unit;
+        self
+    }
+
+    pub fn wrap_period(mut self, wrap_period: Option<u64>) -> Self {
+        self.configuration.wrap_period = wrap_period;
+        self
+    }
+
+    pub fn roi_tracking(mut self, roi_tracking: bool) -> Self {
+        self.configuration.roi_tracking = roi_tracking;
+        self
+    }
+
+    pub fn radial_bins(mut self, radial_bins: usize) -> Self {
+        self.configuration.radial_bins = radial_bins;
+        self
+    }
+
+    pub fn low_frequency(mut self, low_frequency: LowFrequency) -> Self {
+        self.configuration.low_frequency = low_frequency;
+        self
+    }
+
+    pub fn low_frequency_rate(mut self, low_frequency_rate: f64) -> Self {
+        self.configuration.low_frequency_rate = low_frequency_rate;
+        self
+    }
+
+    pub fn refine(mut self, refine: bool) -> Self {
+        self.configuration.refine = refine;
+        self
+    }
+
+    pub fn change_threshold(mut self, change_threshold: Option<f64>) -> Self {
+        self.configuration.change_threshold = change_threshold;
+        self
+    }
+
+    pub fn spectral_smoothing(mut self, spectral_smoothing: Option<f64>) -> Self {
+        self.configuration.spectral_smoothing = spectral_smoothing;
+        self
+    }
+
+    pub fn sign_check_radius(mut self, sign_check_radius: u16) -> Self {
+        self.configuration.sign_check_radius = sign_check_radius;
+        self
+    }
+
+    pub fn sign_check_tolerance(mut self, sign_check_tolerance: usize) -> Self {
+        self.configuration.sign_check_tolerance = sign_check_tolerance;
+        self
+    }
+
+    pub fn sign_detection(mut self, sign_detection: SignDetection) -> Self {
+        self.configuration.sign_detection = sign_detection;
+        self
+    }
+
+    pub fn debounce_events(mut self, debounce_events: u16) -> Self {
+        self.configuration.debounce_events = debounce_events;
+        self
+    }
+
+    pub fn max_latency_ms(mut self, max_latency_ms: Option<f64>) -> Self {
+        self.configuration.max_latency_ms = max_latency_ms;
+        self
+    }
+
+    pub fn subsampling_threshold(mut self, subsampling_threshold: Option<f64>) -> Self {
+        self.configuration.subsampling_threshold = subsampling_threshold;
+        self
+    }
+
+    pub fn subsampling(mut self, subsampling: crate::subsampling::Subsampling) -> Self {
+        self.configuration.subsampling = subsampling;
+        self
+    }
+
+    pub fn blades(mut self, blades: Option<usize>) -> Self {
+        self.configuration.blades = blades;
+        self
+    }
+
+    pub fn flips_capacity(mut self, flips_capacity: usize) -> Self {
+        self.configuration.flips_capacity = flips_capacity;
+        self
+    }
+
+    // s, sets sampling_frequency to 1 / hop_duration
+    pub fn hop_duration(mut self, hop_duration: f64) -> Self {
+        self.configuration.sampling_frequency = 1.0 / hop_duration;
+        self.hop_duration = Some(hop_duration);
+        self
+    }
+
+    pub fn build(self) -> Result<Configuration, Error> {
+        if let Some(hop_duration) = self.hop_duration {
+            if hop_duration <= 0.0 || !hop_duration.is_finite() {
+                return Err(Error::HopDuration(hop_duration));
+            }
+        }
+        self.configuration.validate()?;
+        Ok(self.configuration)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Roi {
+    pub x0: u16,
+    pub y0: u16,
+    pub x1: u16,
+    pub y1: u16,
+}
+
+// Converts the rotor's size to meters for tip speeds (see Sample::tip_speed).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    MetersPerPixel(f64), // the radius is the fitted semi-major axis (see Sample::geometry)
+    RotorRadius(f64),    // m
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parameters {
+    pub amplitude_threshold: f32,
+    pub autocorrelation_threshold: f32,
+    pub frequency_multiplier: f32,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            amplitude_threshold: DEFAULT_AMPLITUDE_THRESHOLD,
+            autocorrelation_threshold: DEFAULT_AUTOCORRELATION_THRESHOLD,
+            frequency_multiplier: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutocorrelationDetections {
+    pub peak_start: f32,
+    pub peak_end: f32,
+    pub peak_frequency: f32,
+    pub peak_amplitude: f32,
+}
+
+impl AutocorrelationDetections {
+    pub fn to_array(self) -> [f32; 4] {
+        [
+            self.peak_start,
+            self.peak_end,
+            self.peak_frequency,
+            self.peak_amplitude,
+        ]
+    }
+}
+
+impl Default for AutocorrelationDetections {
+    fn default() -> Self {
+        Self {
+            peak_start: -1.0,
+            peak_end: -1.0,
+            peak_frequency: -1.0,
+            peak_amplitude: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub t: u64,   // µs, end of the sample window
+    pub rpm: f32, // NaN during the warmup
+    pub confidence: f32,
+    pub shaft_frequency: f32, // Hz
+    pub blade_count: usize,   // 0 during the warmup
+    pub timelines: usize,     // active timelines whose spectra were summed
+    pub smoothed_rpm: f32,    // equal to rpm if smoothing is disabled
+    pub phase: Option<f32>,   // rad, in [0, 2π[, None if no frequency or flip was detected
+    pub window: WindowStatistics,
+    pub track: Option<crate::tracking::Track>, // None during the warmup or without activity
+    pub geometry: Option<crate::tracking::Geometry>, // None during the warmup or without outline
+    pub tip_speed: f32, // m/s, NaN without scale (see RpmEstimator::set_scale) or radius
+    pub low_frequency: bool, // the estimate comes from the low frequency analysis
+    // None during the warmup, without frequency or track, or if the timelines' phases disagree
+    pub direction: Option<crate::tracking::Direction>,
+    pub degradation: crate::budget::Degradation, // see Configuration::max_latency_ms
+    // in [0, 1], larger values hint at an unbalanced rotor or a bent blade (see imbalance_index),
+    // NaN without Configuration::blades, during the warmup, without frequency, or for low
+    // frequency estimates
+    pub imbalance_index: f32,
+}
+
+// Accepted events since the previous sample. A drifting ON/OFF balance hints at bias drift, and
+// a growing spread at a defocused (or moving) target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowStatistics {
+    pub events: usize,
+    pub on_ratio: f32,   // ON events / events, NaN if there were no events
+    pub event_rate: f32, // events/s, NaN if the window has no duration
+    pub spread: f32,     // pixels, RMS distance of the events to their centroid, NaN if no events
+    // flips/s registered by each sign detector (see SignDetection, only the configured one
+    // feeds the timelines), NaN in Vibration mode or if the window has no duration
+    pub neighbourhood_flip_rate: f32,
+    pub transition_flip_rate: f32,
+}
+
+// Sums over the accepted events since the previous sample (see WindowStatistics).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct WindowSums {
+    start_t: Option<u64>, // µs, end of the previous sample, or first event
+    on_events: usize,
+    x: f64,
+    y: f64,
+    squared_x: f64,
+    squared_y: f64,
+    neighbourhood_flips: usize,
+    transition_flips: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BladeCount {
+    pub blade_count: usize,
+    pub confidence: f32, // in [0, 1]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cluster {
+    pub rpm: f32,
+    pub frequency: f32, // Hz, dominant frequency before frequency_multiplier
+    pub confidence: f32,
+    pub x0: u16,
+    pub y0: u16,
+    pub x1: u16,
+    pub y1: u16,
+    pub timelines: usize,
+}
+
+// Counters accumulated since the last reset, except processing_duration, which is the
+// duration of the last process call.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Statistics {
+    pub events: u64,
+    pub out_of_order_events: u64, // skipped or clamped, depending on the timestamp policy
+    pub subsampled_events: u64,   // dropped by the subsampler
+    pub noise_filtered_events: u64,
+    pub masked_events: u64, // outside the ROI or masked
+    pub hot_pixel_events: u64,
+    pub accepted_events: u64,
+    pub active_pixels: usize, // downsampled pixels with at least one polarity flip
+    pub timeline_overflows: u64,
+    pub flicker_timelines: usize, // timelines excluded by flicker rejection
+    pub neighbourhood_flips: u64, // Rotation mode, see WindowStatistics::neighbourhood_flip_rate
+    pub transition_flips: u64,    // Rotation mode, see WindowStatistics::transition_flip_rate
+    pub dropped_flips: u64,       // recorded flips dropped because their block was full
+    pub processing_duration: std::time::Duration,
+}
+
+// Sign flip of a timeline (see RpmEstimator::take_flips). x and y are the sensor coordinates of
+// the top-left pixel of the timeline's block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C, packed)]
+pub struct Flip {
+    pub t: u64, // µs
+    pub x: u16,
+    pub y: u16,
+}
+
+// Named part of the sensor monitored independently (see RpmEstimator::add_region).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+    pub roi: Roi,
+}
+
+// Estimate of a region, its most active timelines are selected among the region's timelines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegionSample {
+    pub rpm: f32,       // NaN during the warmup
+    pub frequency: f32, // Hz, dominant frequency before frequency_multiplier
+    pub confidence: f32,
+    pub timelines: usize, // active timelines whose spectra were summed
+}
+
+// Estimate of the timelines whose distance to the rotor's centre is in the bin. Rigid rotation
+// yields the same frequency in every bin, unlike fluid flows or slipping belts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadialBin {
+    pub radius: f32,    // pixels, centre of the bin in the rotation plane
+    pub rpm: f32,       // NaN if the bin has no timelines
+    pub frequency: f32, // Hz, dominant frequency before frequency_multiplier
+    pub confidence: f32,
+    pub timelines: usize, // active timelines whose spectra were summed
+}
+
+// Spectrum of one of the most active timelines at the last sample. (x, y) is the top-left
+// sensor pixel of the timeline's block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimelineSpectrum<'a> {
+    pub x: u16,
+    pub y: u16,
+    pub activity: f64,
+    pub spectrum: &'a [f32],
+}
+
+// Flip timestamps of every downsampled pixel, stored as a structure of arrays. Ring buffers are
+// allocated in a shared arena the first time their pixel flips, since most pixels never do.
+#[derive(Clone)]
+struct Timelines {
+    length: usize,
+    activities: Vec<f64>,
+    activities_ts: Vec<u64>,
+    slots: Vec<u32>, // per pixel, index of the ring buffer in the arena (u32::MAX if none)
+    timestamps: Vec<u64>, // arena, `length` timestamps per ring buffer
+    timestamps_indices: Vec<u32>, // per ring buffer, index of the next write
+}
+
+const NO_SLOT: u32 = u32::MAX;
+
+impl Timelines {
+    fn new(count: usize, length: usize) -> Self {
+        Self {
+            length,
+            activities: vec![0.0; count],
+            activities_ts: vec![0; count],
+            slots: vec![NO_SLOT; count],
+            timestamps: Vec::new(),
+            timestamps_indices: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    // The arena's capacity is kept.
+    fn clear(&mut self) {
+        self.activities.fill(0.0);
+        self.activities_ts.fill(0);
+        self.slots.fill(NO_SLOT);
+        self.timestamps.clear();
+        self.timestamps_indices.clear();
+    }
+
+    fn is_empty(&self, index: usize) -> bool {
+        self.slots[index] == NO_SLOT
+    }
+
+    // Number of non-empty timelines.
+    fn active(&self) -> usize {
+        self.timestamps_indices.len()
+    }
+
+    fn ring(&self, index: usize) -> Option<(&[u64], usize)> {
+        let slot = self.slots[index];
+        (slot != NO_SLOT).then(|| {
+            let slot = slot as usize;
+            (
+                &self.timestamps[slot * self.length..(slot + 1) * self.length],
+                self.timestamps_indices[slot] as usize,
+            )
+        })
+    }
+
+    fn last(&self, index: usize) -> Option<u64> {
+        let (timestamps, timestamps_index) = self.ring(index)?;
+        let timestamp = timestamps[(timestamps_index + self.length - 1) % self.length];
+        (timestamp != u64::MAX).then_some(timestamp)
+    }
+
+    // activity_mu is -1 / activity_tau (µs⁻¹). Returns the overwritten timestamp if the
+    // timeline was full.
+    fn push(&mut self, index: usize, t: u64, activity_mu: f64) -> Option<u64> {
+        if self.slots[index] == NO_SLOT {
+            self.slots[index] = self.timestamps_indices.len() as u32;
+            self.timestamps
+                .resize(self.timestamps.len() + self.length, u64::MAX);
+            self.timestamps_indices.push(0);
+        }
+        let slot = self.slots[index] as usize;
+        let timestamps_index = self.timestamps_indices[slot] as usize;
+        let cell = &mut self.timestamps[slot * self.length + timestamps_index];
+        let overwritten = *cell;
+        *cell = t;
+        self.timestamps_indices[slot] = ((timestamps_index + 1) % self.length) as u32;
+        self.activities[index] = self.activity_at(index, t, activity_mu) + 1.0;
+        self.activities_ts[index] = t;
+        (overwritten != u64::MAX).then_some(overwritten)
+    }
+
+    fn activity_at(&self, index: usize, t: u64, activity_mu: f64) -> f64 {
+        self.activities[index]
+            * (t.saturating_sub(self.activities_ts[index]) as f64 * activity_mu).exp()
+    }
+
+    // Oldest first.
+    fn timestamps(&self, index: usize) -> impl Iterator<Item = u64> + '_ {
+        let (timestamps, timestamps_index): (&[u64], usize) = self.ring(index).unwrap_or((&[], 0));
+        timestamps[timestamps_index..]
+            .iter()
+            .chain(timestamps[..timestamps_index].iter())
+            .copied()
+            .filter(|timestamp| *timestamp != u64::MAX)
+    }
+
+    // Number of flips in [start_t, t], capped at the timeline length.
+    fn count_since(&self, index: usize, start_t: u64) -> usize {
+        self.ring(index).map_or(0, |(timestamps, _)| {
+            timestamps
+                .iter()
+                .filter(|timestamp| **timestamp != u64::MAX && **timestamp >= start_t)
+                .count()
+        })
+    }
+
+    // Replaces the timeline with the given timestamps (oldest first).
+    fn restore(
+        &mut self,
+        index: usize,
+        activity: f64,
+        activity_t: u64,
+        timestamps: &[u64],
+    ) -> Result<(), crate::state::Error> {
+        if timestamps.len() > self.length {
+            return Err(crate::state::Error::Invalid("too many timestamps"));
+        }
+        for t in timestamps {
+            self.push(index, *t, 0.0);
+        }
+        // push updates the activity, which is overwritten with the saved one
+        self.activities[index] = activity;
+        self.activities_ts[index] = activity_t;
+        Ok(())
+    }
+
+    // Timestamps older than the window are left out (zero-padded), the others are weighted by
+    // the window's coefficients (the last coefficient corresponds to t).
+    fn fill(
+        &self,
+        index: usize,
+        fft_samples: &mut [f32],
+        fft_frequency: f64,
+        window: &[f32],
+        t: u64,
+    ) {
+        fft_samples.fill(0.0);
+        for timestamp in self.timestamps(index) {
+            let fft_reverse_index =
+                ((t - timestamp) as f64 * (fft_frequency / 1e6)).round() as usize;
+            if fft_reverse_index < window.len() {
+                fft_samples[fft_samples.len() - 1 - fft_reverse_index] =
+                    window[window.len() - 1 - fft_reverse_index];
+            }
+        }
+    }
+
+    // Lomb-Scargle periodogram of the flips in the window, evaluated at the FFT frequencies.
+    // Flips are impulses at their exact timestamps (instead of the nearest FFT sample), weighted
+    // by the window function. Amplitudes are scaled to match the FFT of the resampled timeline
+    // (the sum of the weights for in-phase flips).
+    #[allow(clippy::too_many_arguments)]
+    fn periodogram(
+        &self,
+        index: usize,
+        sums: &mut [[f64; 4]],
+        spectrum: &mut [f32],
+        fft_frequency: f64,
+        window: &[f32],
+        t: u64,
+        skip_low_frequency_samples: usize,
+    ) {
+        let fft_length = (spectrum.len() - 1) * 2;
+        let angular_step = std::f64::consts::TAU * fft_frequency / fft_length as f64; // rad/s
+        sums.fill([0.0; 4]);
+        let mut weights_sum = 0.0;
+        for timestamp in self.timestamps(index) {
+            let delta = (t - timestamp) as f64 / 1e6; // s
+            let fft_reverse_index = (delta * fft_frequency).round() as usize;
+            if fft_reverse_index >= window.len() {
+                continue;
+            }
+            let weight = window[window.len() - 1 - fft_reverse_index] as f64;
+            weights_sum += weight;
+            let step = realfft::num_complex::Complex64::from_polar(1.0, angular_step * delta);
+            let mut phasor = realfft::num_complex::Complex64::from_polar(
+                1.0,
+                angular_step * delta * skip_low_frequency_samples as f64,
+            );
+            for sum in sums.iter_mut().skip(skip_low_frequency_samples) {
+                let phasor_squared = phasor * phasor;
+                sum[0] += weight * phasor.re;
+                sum[1] += weight * phasor.im;
+                sum[2] += weight * phasor_squared.re;
+                sum[3] += weight * phasor_squared.im;
+                phasor *= step;
+            }
+        }
+        spectrum.fill(0.0);
+        let minimum_norm = weights_sum * 1e-9;
+        for (amplitude, [cos_sum, sin_sum, cos2_sum, sin2_sum]) in spectrum
+            .iter_mut()
+            .zip(sums.iter())
+            .skip(skip_low_frequency_samples)
+        {
+            // the offset tau makes the sine and cosine terms orthogonal
+            let (sin_tau, cos_tau) = (sin2_sum.atan2(*cos2_sum) / 2.0).sin_cos();
+            let cos_projection = cos_sum * cos_tau + sin_sum * sin_tau;
+            let sin_projection = sin_sum * cos_tau - cos_sum * sin_tau;
+            let resultant = cos2_sum.hypot(*sin2_sum);
+            let cos_norm = (weights_sum + resultant) / 2.0;
+            let sin_norm = (weights_sum - resultant) / 2.0;
+            let mut power = 0.0;
+            if cos_norm > minimum_norm {
+                power += cos_projection.powi(2) / cos_norm;
+            }
+            if sin_norm > minimum_norm {
+                power += sin_projection.powi(2) / sin_norm;
+            }
+            *amplitude = (weights_sum * power).sqrt() as f32;
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sign {
+    None,
+    Negative,
+    Positive,
+}
+
+#[derive(Clone)]
+pub struct RpmEstimator {
+    configuration: Configuration,
+    roi: Roi,
+    mask: Option<Vec<bool>>,
+    scale: Option<Scale>,
+    alarm: Option<crate::alarm::Alarm>,
+    alarm_state: crate::alarm::State,
+    alarm_transitions: Vec<crate::alarm::Transition>,
+    hot_pixels: crate::hot_pixels::Detector,
+    subsampler: crate::subsampling::Subsampler,
+    noise_filter: crate::noise::Filter,
+    smoother: crate::smoothing::Smoother,
+    downsampled_width: u16,
+    downsampled_height: u16,
+    skip_low_frequency_samples: usize,
+    window: Vec<f32>,
+    signed_timestamps: Vec<f64>,
+    surface: Vec<i64>, // sensor pixels, see surface()
+    strobe_events: std::collections::VecDeque<Event>, // see strobe_events()
+    timelines: Timelines,
+    statistics: Statistics,
+    signs: Vec<Sign>,
+    alternative_signs: Vec<Sign>, // signs of the sign detector that is not configured
+    polarity_runs: Vec<u16>,      // consecutive events with the polarity of the last one
+    previous_t: u64,
+    sample_index: usize,
+    next_sample_t: u64,
+    events_since_sample: usize, // accepted
+    last_accepted_t: Option<u64>,
+    window_sums: WindowSums,
+    warmup_events: u64,  // remaining
+    previous_raw_t: u64, // input ticks, before unwrapping
+    epoch_offset: u64,   // input ticks, added by unwrapping
+    samples: Vec<Sample>,
+    record_spectra: bool,
+    spectra: Vec<f32>,
+    record_flips: bool,
+    flips: Vec<std::collections::VecDeque<u64>>, // per block, since the last take call
+    profiler: crate::profiling::Profiler,
+    degradation: crate::budget::Degradation,
+    autocorrelation_detections: AutocorrelationDetections,
+    timelines_activities_and_indices: Vec<(f64, usize)>,
+    timelines_count: usize,     // used by the last sample
+    timelines_scores: Vec<f64>, // ranking scores indexed by timeline
+    timelines_spectra: Vec<f32>,
+    flicker_harmonics: Vec<Option<usize>>, // see crate::flicker::detect
+    flicker_frequency: Option<f64>,        // Hz, detected since the last reset
+    flicker_mask: Vec<bool>,               // timelines excluded by flicker rejection
+    fft_sum: Vec<f32>,
+    spectral_average: Vec<f32>, // empty until the first spectrum, see spectral_smoothing
+    autocorrelation: Vec<f32>,
+    clusters: Vec<Vec<Cluster>>,
+    clusters_positions: Vec<(u16, u16)>,
+    clusters_labels: Vec<usize>,
+    cluster_spectrum: Vec<f32>,
+    cluster_autocorrelation: Vec<f32>,
+    regions: Vec<Region>,
+    regions_samples: Vec<Vec<RegionSample>>,
+    region_activities_and_indices: Vec<(f64, usize)>,
+    region_spectra: Vec<f32>,
+    radial_profiles: Vec<Vec<RadialBin>>,
+    radial_activities_and_indices: Vec<Vec<(f64, usize)>>, // one list per bin
+    detector: crate::changes::Detector,
+    changes: Vec<crate::changes::Change>,
+    fft: FftBuffers,
+    #[cfg(feature = "gpu")]
+    gpu: Option<crate::gpu::Dft>,
+    #[cfg(feature = "gpu")]
+    gpu_samples: Vec<f32>,
+}
+
+impl RpmEstimator {
+    pub fn new(configuration: Configuration) -> Result<Self, Error> {
+        configuration.validate()?;
+        // samples beyond fft_samples are zeros (older than the window)
+        let fft_length = configuration.fft_samples * configuration.zero_padding;
+        let skip_low_frequency_samples =
+            ((MINIMUM_FREQUENCY * fft_length as f64) / configuration.fft_frequency).ceil() as usize;
+        let window_samples = match configuration.window_duration {
+            Some(window_duration) => {
+                ((window_duration * configuration.fft_frequency).round() as usize).max(1)
+            }
+            None => configuration.fft_samples,
+        };
+        // partial blocks on the right and bottom edges get their own downsampled pixel
+        let downsampled_width = configuration
+            .width
+            .div_ceil(configuration.spatial_downsampling);
+        let downsampled_height = configuration
+            .height
+            .div_ceil(configuration.spatial_downsampling);
+        let downsampled_length = downsampled_width as usize * downsampled_height as usize;
+        // the inputs are real, hence spectra only store positive frequencies (including Nyquist)
+        let spectrum_length = fft_length / 2 + 1;
+        Ok(Self {
+            configuration,
+            roi: Roi {
+                x0: 0,
+                y0: 0,
+                x1: configuration.width,
+                y1: configuration.height,
+            },
+            mask: None,
+            scale: None,
+            alarm: None,
+            alarm_state: crate::alarm::State::Normal,
+            alarm_transitions: Vec::new(),
+            hot_pixels: crate::hot_pixels::Detector::new(
+                configuration.width,
+                configuration.height,
+                configuration.hot_pixel_ratio,
+                configuration.hot_pixel_window,
+            ),
+            subsampler: crate::subsampling::Subsampler::new(
+                configuration.width,
+                configuration.height,
+                configuration.subsampling,
+                configuration.subsampling_threshold,
+            ),
+            noise_filter: crate::noise::Filter::new(
+                configuration.width,
+                configuration.height,
+                configuration.refractory_period,
+                configuration.noise_filter_window,
+                configuration.noise_filter_neighbours,
+            ),
+            smoother: crate::smoothing::Smoother::new(
+                configuration.smoothing,
+                configuration.smoothing_alpha,
+                configuration.process_noise,
+                configuration.measurement_noise,
+            ),
+            downsampled_width,
+            downsampled_height,
+            skip_low_frequency_samples,
+            window: configuration.window_function.coefficients(window_samples),
+            signed_timestamps: vec![0.0; downsampled_length],
+            surface: vec![0; configuration.width as usize * configuration.height as usize],
+            strobe_events: std::collections::VecDeque::with_capacity(configuration.strobe_length),
+            timelines: Timelines::new(downsampled_length, configuration.timeline_length),
+            statistics: Statistics::default(),
+            signs: vec![Sign::None; downsampled_length],
+            alternative_signs: vec![Sign::None; downsampled_length],
+            polarity_runs: vec![0; downsampled_length],
+            previous_t: 0,
+            sample_index: 0,
+            next_sample_t: sample_t(configuration.sampling_frequency, 0),
+            events_since_sample: 0,
+            last_accepted_t: None,
+            window_sums: WindowSums::default(),
+            warmup_events: configuration.warmup_events,
+            previous_raw_t: 0,
+            epoch_offset: 0,
+            samples: Vec::new(),
+            record_spectra: false,
+            spectra: Vec::new(),
+            record_flips: false,
+            flips: vec![std::collections::VecDeque::new(); downsampled_length],
+            profiler: crate::profiling::Profiler::default(),
+            degradation: crate::budget::Degradation::None,
+            autocorrelation_detections: AutocorrelationDetections::default(),
+            timelines_activities_and_indices: vec![(0.0, 0); downsampled_length],
+            timelines_count: 0,
+            timelines_scores: vec![0.0; downsampled_length],
+            flicker_harmonics: Vec::new(),
+            flicker_frequency: None,
+            flicker_mask: vec![false; downsampled_length],
+            timelines_spectra: vec![
+                0.0;
+                configuration.most_active_timelines.min(downsampled_length)
+                    * spectrum_length
+            ],
+            fft_sum: vec![0.0; spectrum_length],
+            spectral_average: Vec::with_capacity(spectrum_length),
+            autocorrelation: vec![0.0; spectrum_length],
+            clusters: Vec::new(),
+            clusters_positions: Vec::with_capacity(configuration.most_active_timelines),
+            clusters_labels: Vec::with_capacity(configuration.most_active_timelines),
+            cluster_spectrum: vec![0.0; spectrum_length],
+            cluster_autocorrelation: vec![0.0; spectrum_length],
+            regions: Vec::new(),
+            regions_samples: Vec::new(),
+            region_activities_and_indices: Vec::new(),
+            region_spectra: vec![
+                0.0;
+                configuration.most_active_timelines.min(downsampled_length)
+                    * spectrum_length
+            ],
+            radial_profiles: Vec::new(),
+            radial_activities_and_indices: vec![Vec::new(); configuration.radial_bins],
+            detector: crate::changes::Detector::default(),
+            changes: Vec::new(),
+            fft: FftBuffers::new(fft_length),
+            #[cfg(feature = "gpu")]
+            gpu: crate::gpu::Dft::new(
+                fft_length,
+                configuration.most_active_timelines.min(downsampled_length),
+            ),
+            #[cfg(feature = "gpu")]
+            gpu_samples: Vec::new(),
+        })
+    }
+
+    pub fn configuration(&self) -> Configuration {
+        self.configuration
+    }
+
+    pub fn reset(&mut self) {
+        self.signed_timestamps.fill(0.0);
+        self.surface.fill(0);
+        self.strobe_events.clear();
+        self.timelines.clear();
+        self.statistics = Statistics::default();
+        self.profiler.reset();
+        self.degradation = crate::budget::Degradation::None;
+        self.signs.fill(Sign::None);
+        self.alternative_signs.fill(Sign::None);
+        self.polarity_runs.fill(0);
+        self.hot_pixels.reset();
+        self.subsampler.reset();
+        self.noise_filter.reset();
+        self.smoother.reset();
+        self.detector.reset();
+        self.alarm_state = crate::alarm::State::Normal;
+        self.previous_t = 0;
+        self.sample_index = 0;
+        self.next_sample_t = sample_t(self.configuration.sampling_frequency, 0);
+        self.events_since_sample = 0;
+        self.last_accepted_t = None;
+        self.window_sums = WindowSums::default();
+        self.warmup_events = self.configuration.warmup_events;
+        self.previous_raw_t = 0;
+        self.epoch_offset = 0;
+        self.samples.clear();
+        self.spectra.clear();
+        self.flips
+            .iter_mut()
+            .for_each(std::collections::VecDeque::clear);
+        self.autocorrelation_detections = AutocorrelationDetections::default();
+        self.timelines_activities_and_indices.fill((0.0, 0));
+        self.timelines_count = 0;
+        self.flicker_frequency = None;
+        self.flicker_mask.fill(false);
+        self.timelines_spectra.fill(0.0);
+        self.fft_sum.fill(0.0);
+        self.spectral_average.clear();
+        self.autocorrelation.fill(0.0);
+        self.clusters.clear();
+        self.regions_samples.clear();
+        self.radial_profiles.clear();
+        self.changes.clear();
+        self.alarm_transitions.clear();
+    }
+
+    // Serializes the configuration, the ROI, the mask, the scale, the alarm, hot pixel overrides,
+    // and the estimator state (timelines, signs, and sample clock). Hot pixel counts, subsampler
+    // and noise filter state, smoother state, spectral average, alarm state, degradation level,
+    // and the last samples are not saved and start from scratch after loading.
+    pub fn to_state(&self) -> Vec<u8> {
+        let mut encoder = crate::state::Encoder::new();
+        let configuration = &self.configuration;
+        encoder.u16(configuration.width);
+        encoder.u16(configuration.height);
+        encoder.usize(configuration.fft_samples);
+        encoder.f64(configuration.fft_frequency);
+        encoder.u8(match configuration.timestamp_policy {
+            TimestampPolicy::Skip => 0,
+            TimestampPolicy::Clamp => 1,
+            TimestampPolicy::Error => 2,
+        });
+        encoder.f64(configuration.sampling_frequency);
+        encoder.option_u64(configuration.events_per_sample.map(|value| value as u64));
+        encoder.u8(match configuration.method {
+            Method::Autocorrelation => 0,
+            Method::HarmonicProduct => 1,
+            Method::LombScargle => 2,
+        });
+        encoder.usize(configuration.harmonics);
+        encoder.option_f64(configuration.window_duration);
+        encoder.option_f64(configuration.hot_pixel_ratio);
+        encoder.u64(configuration.hot_pixel_window);
+        encoder.option_u64(configuration.refractory_period);
+        encoder.option_u64(configuration.noise_filter_window);
+        encoder.usize(configuration.noise_filter_neighbours);
+        encoder.u16(configuration.spatial_downsampling);
+        encoder.bool(configuration.anti_aliasing);
+        encoder.usize(configuration.most_active_timelines);
+        encoder.u8(match configuration.window_function {
+            WindowFunction::Rectangular => 0,
+            WindowFunction::Hann => 1,
+            WindowFunction::Hamming => 2,
+            WindowFunction::BlackmanHarris => 3,
+        });
+        encoder.usize(configuration.zero_padding);
+        encoder.bool(configuration.peak_interpolation);
+        encoder.u8(match configuration.smoothing {
+            crate::smoothing::Smoothing::None => 0,
+            crate::smoothing::Smoothing::Exponential => 1,
+            crate::smoothing::Smoothing::Kalman => 2,
+        });
+        encoder.f64(configuration.smoothing_alpha);
+        encoder.f64(configuration.process_noise);
+        encoder.f64(configuration.measurement_noise);
+        encoder.u64(configuration.activity_tau);
+        encoder.u8(match configuration.ranking {
+            Ranking::Activity => 0,
+            Ranking::Count => 1,
+        });
+        encoder.u64(configuration.ranking_window);
+        encoder.usize(configuration.timeline_length);
+        encoder.u8(match configuration.mode {
+            Mode::Rotation => 0,
+            Mode::Vibration => 1,
+        });
+        encoder.u64(configuration.warmup_events);
+        encoder.usize(configuration.warmup_timelines);
+        encoder.option_f64(configuration.activity_fraction);
+        encoder.usize(configuration.strobe_length);
+        encoder.option_f64(configuration.flicker_rejection);
+        match configuration.time_unit {
+            TimeUnit::Microseconds => encoder.u8(0),
+            TimeUnit::Nanoseconds => encoder.u8(1),
+            TimeUnit::Milliseconds => encoder.u8(2),
+            TimeUnit::Scale(scale) => {
+                encoder.u8(3);
+                encoder.f64(scale);
+            }
+        }
+        encoder.option_u64(configuration.wrap_period);
+        encoder.bool(configuration.roi_tracking);
+        encoder.usize(configuration.radial_bins);
+        encoder.u8(match configuration.low_frequency {
+            LowFrequency::Off => 0,
+            LowFrequency::On => 1,
+            LowFrequency::Adaptive => 2,
+        });
+        encoder.f64(configuration.low_frequency_rate);
+        encoder.bool(configuration.refine);
+        encoder.option_f64(configuration.change_threshold);
+        encoder.option_f64(configuration.spectral_smoothing);
+        encoder.u16(configuration.sign_check_radius);
+        encoder.usize(configuration.sign_check_tolerance);
+        encoder.u8(match configuration.sign_detection {
+            SignDetection::Neighbourhood => 0,
+            SignDetection::Transition => 1,
+        });
+        encoder.u16(configuration.debounce_events);
+        encoder.option_f64(configuration.max_latency_ms);
+        encoder.option_f64(configuration.subsampling_threshold);
+        encoder.u8(match configuration.subsampling {
+            crate::subsampling::Subsampling::Random => 0,
+            crate::subsampling::Subsampling::Stratified => 1,
+        });
+        encoder.option_u64(configuration.blades.map(|value| value as u64));
+        encoder.usize(configuration.flips_capacity);
+        encoder.u16(self.roi.x0);
+        encoder.u16(self.roi.y0);
+        encoder.u16(self.roi.x1);
+        encoder.u16(self.roi.y1);
+        encoder.bool(self.mask.is_some());
+        if let Some(mask) = self.mask.as_ref() {
+            for value in mask.iter() {
+                encoder.bool(*value);
+            }
+        }
+        match self.scale {
+            None => encoder.u8(0),
+            Some(Scale::MetersPerPixel(meters_per_pixel)) => {
+                encoder.u8(1);
+                encoder.f64(meters_per_pixel);
+            }
+            Some(Scale::RotorRadius(radius)) => {
+                encoder.u8(2);
+                encoder.f64(radius);
+            }
+        }
+        encoder.bool(self.alarm.is_some());
+        if let Some(alarm) = self.alarm {
+            encoder.option_f64(alarm.min_rpm.map(f64::from));
+            encoder.option_f64(alarm.max_rpm.map(f64::from));
+            encoder.f64(alarm.hysteresis as f64);
+        }
+        for hot_override in self.hot_pixels.overrides() {
+            encoder.u8(match hot_override {
+                None => 0,
+                Some(false) => 1,
+                Some(true) => 2,
+            });
+        }
+        encoder.usize(self.regions.len());
+        for region in self.regions.iter() {
+            encoder.string(&region.name);
+            encoder.u16(region.roi.x0);
+            encoder.u16(region.roi.y0);
+            encoder.u16(region.roi.x1);
+            encoder.u16(region.roi.y1);
+        }
+        encoder.u64(self.previous_t);
+        encoder.usize(self.sample_index);
+        encoder.u64(self.next_sample_t);
+        encoder.usize(self.events_since_sample);
+        encoder.option_u64(self.last_accepted_t);
+        encoder.option_u64(self.window_sums.start_t);
+        encoder.usize(self.window_sums.on_events);
+        encoder.f64(self.window_sums.x);
+        encoder.f64(self.window_sums.y);
+        encoder.f64(self.window_sums.squared_x);
+        encoder.f64(self.window_sums.squared_y);
+        encoder.usize(self.window_sums.neighbourhood_flips);
+        encoder.usize(self.window_sums.transition_flips);
+        encoder.u64(self.warmup_events);
+        encoder.u64(self.previous_raw_t);
+        encoder.u64(self.epoch_offset);
+        encoder.option_f64(self.flicker_frequency);
+        encoder.usize(self.flicker_mask.iter().filter(|masked| **masked).count());
+        for (index, masked) in self.flicker_mask.iter().enumerate() {
+            if *masked {
+                encoder.usize(index);
+            }
+        }
+        for index in 0..self.signs.len() {
+            encoder.f64(self.signed_timestamps[index]);
+            for sign in [self.signs[index], self.alternative_signs[index]] {
+                encoder.u8(match sign {
+                    Sign::None => 0,
+                    Sign::Negative => 1,
+                    Sign::Positive => 2,
+                });
+            }
+            encoder.u16(self.polarity_runs[index]);
+        }
+        // most timelines are empty, only the others are saved (oldest timestamp first)
+        let timelines_count = (0..self.timelines.len())
+            .filter(|index| !self.timelines.is_empty(*index))
+            .count();
+        encoder.usize(timelines_count);
+        for index in 0..self.timelines.len() {
+            if !self.timelines.is_empty(index) {
+                encoder.usize(index);
+                encoder.f64(self.timelines.activities[index]);
+                encoder.u64(self.timelines.activities_ts[index]);
+                encoder.usize(self.timelines.timestamps(index).count());
+                for timestamp in self.timelines.timestamps(index) {
+                    encoder.u64(timestamp);
+                }
+            }
+        }
+        encoder.into_bytes()
+    }
+
+    // Rebuilds an estimator (including FFT plans) from the output of to_state.
+    pub fn from_state(bytes: &[u8]) -> Result<Self, crate::state::Error> {
+        let mut decoder = crate::state::Decoder::new(bytes)?;
+        let configuration = Configuration {
+            width: decoder.u16()?,
+            height: decoder.u16()?,
+            fft_samples: decoder.usize()?,
+            fft_frequency: decoder.f64()?,
+            timestamp_policy: match decoder.u8()? {
+                0 => TimestampPolicy::Skip,
+                1 => TimestampPolicy::Clamp,
+                2 => TimestampPolicy::Error,
+                _ => return Err(crate::state::Error::Invalid("unknown timestamp policy")),
+            },
+            sampling_frequency: decoder.f64()?,
+            events_per_sample: decoder.option_u64()?.map(|value| value as usize),
+            method: match decoder.u8()? {
+                0 => Method::Autocorrelation,
+                1 => Method::HarmonicProduct,
+                2 => Method::LombScargle,
+                _ => return Err(crate::state::Error::Invalid("unknown method")),
+            },
+            harmonics: decoder.usize()?,
+            window_duration: decoder.option_f64()?,
+            hot_pixel_ratio: decoder.option_f64()?,
+            hot_pixel_window: decoder.u64()?,
+            refractory_period: decoder.option_u64()?,
+            noise_filter_window: decoder.option_u64()?,
+            noise_filter_neighbours: decoder.usize()?,
+            spatial_downsampling: decoder.u16()?,
+            anti_aliasing: decoder.bool()?,
+            most_active_timelines: decoder.usize()?,
+            window_function: match decoder.u8()? {
+                0 => WindowFunction::Rectangular,
+                1 => WindowFunction::Hann,
+                2 => WindowFunction::Hamming,
+                3 => WindowFunction::BlackmanHarris,
+                _ => return Err(crate::state::Error::Invalid("unknown window function")),
+            },
+            zero_padding: decoder.usize()?,
+            peak_interpolation: decoder.bool()?,
+            smoothing: match decoder.u8()? {
+                0 => crate::smoothing::Smoothing::None,
+                1 => crate::smoothing::Smoothing::Exponential,
+                2 => crate::smoothing::Smoothing::Kalman,
+                _ => return Err(crate::state::Error::Invalid("unknown smoothing")),
+            },
+            smoothing_alpha: decoder.f64()?,
+            process_noise: decoder.f64()?,
+            measurement_noise: decoder.f64()?,
+            activity_tau: decoder.u64()?,
+            ranking: match decoder.u8()? {
+                0 => Ranking::Activity,
+                1 => Ranking::Count,
+                _ => return Err(crate::state::Error::Invalid("unknown ranking")),
+            },
+            ranking_window: decoder.u64()?,
+            timeline_length: decoder.usize()?,
+            mode: match decoder.u8()? {
+                0 => Mode::Rotation,
+                1 => Mode::Vibration,
+                _ => return Err(crate::state::Error::Invalid("unknown mode")),
+            },
+            warmup_events: decoder.u64()?,
+            warmup_timelines: decoder.usize()?,
+            activity_fraction: decoder.option_f64()?,
+            strobe_length: decoder.usize()?,
+            flicker_rejection: decoder.option_f64()?,
+            time_unit: match decoder.u8()? {
+                0 => TimeUnit::Microseconds,
+                1 => TimeUnit::Nanoseconds,
+                2 => TimeUnit::Milliseconds,
+                3 => TimeUnit::Scale(decoder.f64()?),
+                _ => return Err(crate::state::Error::Invalid("unknown time unit")),
+            },
+            wrap_period: decoder.option_u64()?,
+            roi_tracking: decoder.bool()?,
+            radial_bins: decoder.usize()?,
+            low_frequency: match decoder.u8()? {
+                0 => LowFrequency::Off,
+                1 => LowFrequency::On,
+                2 => LowFrequency::Adaptive,
+                _ => return Err(crate::state::Error::Invalid("unknown low frequency mode")),
+            },
+            low_frequency_rate: decoder.f64()?,
+            refine: decoder.bool()?,
+            change_threshold: decoder.option_f64()?,
+            spectral_smoothing: decoder.option_f64()?,
+            sign_check_radius: decoder.u16()?,
+            sign_check_tolerance: decoder.usize()?,
+            sign_detection: match decoder.u8()? {
+                0 => SignDetection::Neighbourhood,
+                1 => SignDetection::Transition,
+                _ => return Err(crate::state::Error::Invalid("unknown sign detection")),
+            },
+            debounce_events: decoder.u16()?,
+            max_latency_ms: decoder.option_f64()?,
+            subsampling_threshold: decoder.option_f64()?,
+            subsampling: match decoder.u8()? {
+                0 => crate::subsampling::Subsampling::Random,
+                1 => crate::subsampling::Subsampling::Stratified,
+                _ => return Err(crate::state::Error::Invalid("unknown subsampling")),
+            },
+            blades: decoder.option_u64()?.map(|value| value as usize),
+            flips_capacity: decoder.usize()?,
+        };
+        let mut estimator = Self::new(configuration)?;
+        let roi = Roi {
+            x0: decoder.u16()?,
+            y0: decoder.u16()?,
+            x1: decoder.u16()?,
+            y1: decoder.u16()?,
+        };
+        estimator.set_roi(Some(roi))?;
+        let length = configuration.width as usize * configuration.height as usize;
+        if decoder.bool()? {
+            let mask = (0..length)
+                .map(|_| decoder.bool())
+                .collect::<Result<Vec<bool>, _>>()?;
+            estimator.set_mask(Some(mask))?;
+        }
+        estimator.set_scale(match decoder.u8()? {
+            0 => None,
+            1 => Some(Scale::MetersPerPixel(decoder.f64()?)),
+            2 => Some(Scale::RotorRadius(decoder.f64()?)),
+            _ => return Err(crate::state::Error::Invalid("unknown scale")),
+        })?;
+        if decoder.bool()? {
+            estimator.set_alarm(Some(crate::alarm::Alarm {
+                min_rpm: decoder.option_f64()?.map(|min_rpm| min_rpm as f32),
+                max_rpm: decoder.option_f64()?.map(|max_rpm| max_rpm as f32),
+                hysteresis: decoder.f64()? as f32,
+            }))?;
+        }
+        for index in 0..length {
+            let hot = match decoder.u8()? {
+                0 => None,
+                1 => Some(false),
+                2 => Some(true),
+                _ => return Err(crate::state::Error::Invalid("unknown hot pixel override")),
+            };
+            estimator.hot_pixels.set_override(
+                (index % configuration.width as usize) as u16,
+                (index / configuration.width as usize) as u16,
+                hot,
+            );
+        }
+        for _ in 0..decoder.usize()? {
+            let name = decoder.string()?;
+            let roi = Roi {
+                x0: decoder.u16()?,
+                y0: decoder.u16()?,
+                x1: decoder.u16()?,
+                y1: decoder.u16()?,
+            };
+            estimator.add_region(&name, roi)?;
+        }
+        estimator.previous_t = decoder.u64()?;
+        estimator.sample_index = decoder.usize()?;
+        estimator.next_sample_t = decoder.u64()?;
+        estimator.events_since_sample = decoder.usize()?;
+        estimator.last_accepted_t = decoder.option_u64()?;
+        estimator.window_sums = WindowSums {
+            start_t: decoder.option_u64()?,
+            on_events: decoder.usize()?,
+            x: decoder.f64()?,
+            y: decoder.f64()?,
+            squared_x: decoder.f64()?,
+            squared_y: decoder.f64()?,
+            neighbourhood_flips: decoder.usize()?,
+            transition_flips: decoder.usize()?,
+        };
+        estimator.warmup_events = decoder.u64()?;
+        estimator.previous_raw_t = decoder.u64()?;
+        estimator.epoch_offset = decoder.u64()?;
+        estimator.flicker_frequency = decoder.option_f64()?;
+        for _ in 0..decoder.usize()? {
+            *estimator.flicker_mask.get_mut(decoder.usize()?).ok_or(
+                crate::state::Error::Invalid("flicker timeline out of range"),
+            )? = true;
+        }
+        for index in 0..estimator.signs.len() {
+            estimator.signed_timestamps[index] = decoder.f64()?;
+            for sign in [
+                &mut estimator.signs[index],
+                &mut estimator.alternative_signs[index],
+            ] {
+                *sign = match decoder.u8()? {
+                    0 => Sign::None,
+                    1 => Sign::Negative,
+                    2 => Sign::Positive,
+                    _ => return Err(crate::state::Error::Invalid("unknown sign")),
+                };
+            }
+            estimator.polarity_runs[index] = decoder.u16()?;
+        }
+        let timelines_count = decoder.usize()?;
+        for _ in 0..timelines_count {
+            let index = decoder.usize()?;
+            if index >= estimator.timelines.len() {
+                return Err(crate::state::Error::Invalid("timeline index out of bounds"));
+            }
+            let activity = decoder.f64()?;
+            let activity_t = decoder.u64()?;
+            let timestamps_count = decoder.usize()?;
+            if timestamps_count > configuration.timeline_length {
+                return Err(crate::state::Error::Invalid("too many timestamps"));
+            }
+            let timestamps = (0..timestamps_count)
+                .map(|_| decoder.u64())
+                .collect::<Result<Vec<u64>, _>>()?;
+            estimator
+                .timelines
+                .restore(index, activity, activity_t, &timestamps)?;
+        }
+        decoder.finish()?;
+        Ok(estimator)
+    }
+
+    pub fn roi(&self) -> Roi {
+        self.roi
+    }
+
+    pub fn set_roi(&mut self, roi: Option<Roi>) -> Result<(), Error> {
+        match roi {
+            Some(roi) => {
+                if roi.x0 >= roi.x1
+                    || roi.x1 > self.configuration.width
+                    || roi.y0 >= roi.y1
+                    || roi.y1 > self.configuration.height
+                {
+                    return Err(Error::Roi {
+                        x0: roi.x0,
+                        y0: roi.y0,
+                        x1: roi.x1,
+                        y1: roi.y1,
+                        width: self.configuration.width,
+                        height: self.configuration.height,
+                    });
+                }
+                self.roi = roi;
+            }
+            None => {
+                self.roi = Roi {
+                    x0: 0,
+                    y0: 0,
+                    x1: self.configuration.width,
+                    y1: self.configuration.height,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    pub fn mask(&self) -> Option<&[bool]> {
+        self.mask.as_deref()
+    }
+
+    // The mask is indexed with x + y * width, and events on false pixels are ignored.
+    pub fn set_mask(&mut self, mask: Option<Vec<bool>>) -> Result<(), Error> {
+        if let Some(mask) = mask.as_ref() {
+            let expected = self.configuration.width as usize * self.configuration.height as usize;
+            if mask.len() != expected {
+                return Err(Error::MaskLength {
+                    expected,
+                    actual: mask.len(),
+                });
+            }
+        }
+        self.mask = mask;
+        Ok(())
+    }
+
+    pub fn scale(&self) -> Option<Scale> {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: Option<Scale>) -> Result<(), Error> {
+        if let Some(Scale::MetersPerPixel(value) | Scale::RotorRadius(value)) = scale {
+            if value <= 0.0 || !value.is_finite() {
+                return Err(Error::Scale(value));
+            }
+        }
+        self.scale = scale;
+        Ok(())
+    }
+
+    pub fn alarm(&self) -> Option<crate::alarm::Alarm> {
+        self.alarm
+    }
+
+    // Replacing or removing the alarm clears its state.
+    pub fn set_alarm(&mut self, alarm: Option<crate::alarm::Alarm>) -> Result<(), Error> {
+        if let Some(alarm) = alarm.as_ref() {
+            alarm.validate()?;
+        }
+        self.alarm = alarm;
+        self.alarm_state = crate::alarm::State::Normal;
+        Ok(())
+    }
+
+    pub fn alarm_state(&self) -> crate::alarm::State {
+        self.alarm_state
+    }
+
+    // Alarm state changes during the last process call (see set_alarm).
+    pub fn alarm_transitions(&self) -> &[crate::alarm::Transition] {
+        &self.alarm_transitions
+    }
+
+    // Hot pixels are either detected (if hot_pixel_ratio is set) or manually overridden.
+    pub fn hot_pixels(&self) -> Vec<(u16, u16)> {
+        self.hot_pixels.hot_pixels()
+    }
+
+    // Some(true) blacklists the pixel, Some(false) whitelists it, and None restores detection.
+    pub fn set_hot_pixel(&mut self, x: u16, y: u16, hot: Option<bool>) -> Result<(), Error> {
+        if x >= self.configuration.width || y >= self.configuration.height {
+            return Err(Error::Pixel {
+                x,
+                y,
+                width: self.configuration.width,
+                height: self.configuration.height,
+            });
+        }
+        self.hot_pixels.set_override(x, y, hot);
+        Ok(())
+    }
+
+    pub fn process(&mut self, events: &[Event], parameters: &Parameters) -> Result<(), Error> {
+        self.process_events(events.iter().copied(), parameters)
+    }
+
+    pub fn process_events<Events: IntoIterator<Item = Event>>(
+        &mut self,
+        events: Events,
+        parameters: &Parameters,
+    ) -> Result<(), Error> {
+        let start = std::time::Instant::now();
+        let timer = self.profiler.start(crate::profiling::Stage::Process);
+        let result = self.process_events_unmeasured(events, parameters);
+        self.statistics.processing_duration = start.elapsed();
+        self.update_degradation();
+        self.profiler.stop(timer);
+        result
+    }
+
+    // Split from process_events so that the duration, the degradation, and the profiler are also
+    // updated when the call returns an error.
+    fn process_events_unmeasured<Events: IntoIterator<Item = Event>>(
+        &mut self,
+        events: Events,
+        parameters: &Parameters,
+    ) -> Result<(), Error> {
+        self.samples.clear();
+        self.spectra.clear();
+        self.clusters.clear();
+        self.regions_samples.clear();
+        self.radial_profiles.clear();
+        self.changes.clear();
+        self.alarm_transitions.clear();
+        self.autocorrelation_detections = AutocorrelationDetections::default();
+        for (index, mut event) in events.into_iter().enumerate() {
+            self.statistics.events += 1;
+            if event.x >= self.configuration.width || event.y >= self.configuration.height {
+                return Err(Error::OutOfBounds {
+                    index,
+                    x: event.x,
+                    y: event.y,
+                    width: self.configuration.width,
+                    height: self.configuration.height,
+                });
+            }
+            if let Some(wrap_period) = self.configuration.wrap_period {
+                event.t = self.unwrap_timestamp(event.t, wrap_period);
+            }
+            event.t = self.configuration.time_unit.microseconds(event.t);
+            if event.t < self.previous_t {
+                self.statistics.out_of_order_events += 1;
+                match self.configuration.timestamp_policy {
+                    TimestampPolicy::Skip => continue,
+                    TimestampPolicy::Clamp => {
+                        event.t = self.previous_t;
+                    }
+                    TimestampPolicy::Error => {
+                        return Err(Error::NonMonotonic {
+                            index,
+                            t: event.t,
+                            previous_t: self.previous_t,
+                        });
+                    }
+                }
+            }
+            self.previous_t = event.t;
+            self.window_sums.start_t.get_or_insert(event.t);
+            if self.configuration.events_per_sample.is_none() {
+                while event.t > self.next_sample_t {
+                    self.evaluate_sample(event.t, parameters);
+                }
+            }
+            if self.subsampler.is_enabled() && !self.subsampler.keep(event.x, event.y, event.t) {
+                self.statistics.subsampled_events += 1;
+                continue;
+            }
+            if self.noise_filter.is_enabled()
+                && !self.noise_filter.filter(event.x, event.y, event.t)
+            {
+                self.statistics.noise_filtered_events += 1;
+                continue;
+            }
+            if event.x < self.roi.x0
+                || event.x >= self.roi.x1
+                || event.y < self.roi.y0
+                || event.y >= self.roi.y1
+            {
+                self.statistics.masked_events += 1;
+                continue;
+            }
+            if let Some(mask) = self.mask.as_ref() {
+                if !mask[event.x as usize + event.y as usize * self.configuration.width as usize] {
+                    self.statistics.masked_events += 1;
+                    continue;
+                }
+            }
+            if self.hot_pixels.update(event.x, event.y, event.t) {
+                self.statistics.hot_pixel_events += 1;
+                continue;
+            }
+            self.statistics.accepted_events += 1;
+            self.warmup_events = self.warmup_events.saturating_sub(1);
+            self.surface[event.x as usize + event.y as usize * self.configuration.width as usize] =
+                match event.polarity {
+                    neuromorphic_types::DvsPolarity::Off => -(event.t as i64 + 1),
+                    neuromorphic_types::DvsPolarity::On => event.t as i64 + 1,
+                };
+            if self.configuration.strobe_length > 0 {
+                if self.strobe_events.len() == self.configuration.strobe_length {
+                    self.strobe_events.pop_front();
+                }
+                self.strobe_events.push_back(event);
+            }
+            self.push_event(event);
+            self.events_since_sample += 1;
+            self.last_accepted_t = Some(event.t);
+            if matches!(event.polarity, neuromorphic_types::DvsPolarity::On) {
+                self.window_sums.on_events += 1;
+            }
+            self.window_sums.x += event.x as f64;
+            self.window_sums.y += event.y as f64;
+            self.window_sums.squared_x += (event.x as f64).powi(2);
+            self.window_sums.squared_y += (event.y as f64).powi(2);
+            if let Some(events_per_sample) = self.configuration.events_per_sample {
+                if self.events_since_sample >= events_per_sample * self.window_stride() {
+                    self.evaluate_sample(event.t, parameters);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Emits the samples that a process call with an event at t_end would emit, followed by a
+    // sample for the last, partial window (if it contains accepted events), so that the end of a
+    // recording is not lost. If t_end is None, the partial window ends at the last event.
+    // Otherwise, activities decay and the FFT window slides until t_end. The sampling grid is not
+    // advanced, hence this function should only be called at the end of a stream. t_end is in
+    // the input time unit (see Configuration::time_unit).
+    pub fn flush(&mut self, t_end: Option<u64>, parameters: &Parameters) -> Result<(), Error> {
+        let start = std::time::Instant::now();
+        let timer = self.profiler.start(crate::profiling::Stage::Process);
+        let result = self.flush_unmeasured(t_end, parameters);
+        self.statistics.processing_duration = start.elapsed();
+        self.update_degradation();
+        self.profiler.stop(timer);
+        result
+    }
+
+    // See process_events_unmeasured.
+    fn flush_unmeasured(
+        &mut self,
+        t_end: Option<u64>,
+        parameters: &Parameters,
+    ) -> Result<(), Error> {
+        self.samples.clear();
+        self.spectra.clear();
+        self.clusters.clear();
+        self.regions_samples.clear();
+        self.radial_profiles.clear();
+        self.changes.clear();
+        self.alarm_transitions.clear();
+        self.autocorrelation_detections = AutocorrelationDetections::default();
+        let t_end = t_end.map(|t_end| {
+            self.configuration
+                .time_unit
+                .microseconds(t_end.saturating_add(self.epoch_offset))
+        });
+        let t_end = match t_end {
+            Some(t_end) if t_end < self.previous_t => {
+                return Err(Error::FlushTimestamp {
+                    t_end,
+                    previous_t: self.previous_t,
+                });
+            }
+            Some(t_end) => t_end,
+            None => self.previous_t,
+        };
+        match self.configuration.events_per_sample {
+            Some(_) => {
+                if self.events_since_sample > 0 {
+                    self.push_sample(t_end, t_end, parameters);
+                    self.events_since_sample = 0;
+                }
+            }
+            None => {
+                while t_end > self.next_sample_t {
+                    self.evaluate_sample(t_end, parameters);
+                }
+                if self.events_since_sample > 0 {
+                    self.push_sample(t_end, t_end, parameters);
+                    self.events_since_sample = 0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    // Changes detected during the last process call (see Configuration::change_threshold).
+    pub fn changes(&self) -> &[crate::changes::Change] {
+        &self.changes
+    }
+
+    pub fn spectrum(&self) -> &[f32] {
+        &self.fft_sum[0..self.fft_sum.len() - 1]
+    }
+
+    // Hz, spacing of the spectrum bins (zero padding included).
+    pub fn frequency_resolution(&self) -> f64 {
+        self.configuration.fft_frequency
+            / (self.configuration.fft_samples * self.configuration.zero_padding) as f64
+    }
+
+    // Hz, lighting flicker frequency detected since the last reset (see
+    // Configuration::flicker_rejection).
+    pub fn flicker_frequency(&self) -> Option<f64> {
+        self.flicker_frequency
+    }
+
+    // Hz, frequency of each spectrum bin.
+    pub fn frequencies(&self) -> Vec<f64> {
+        let frequency_resolution = self.frequency_resolution();
+        (0..self.spectrum().len())
+            .map(|index| index as f64 * frequency_resolution)
+            .collect()
+    }
+
+    // Hz, Nyquist frequency of the resampled timelines.
+    pub fn maximum_frequency(&self) -> f64 {
+        self.configuration.fft_frequency / 2.0
+    }
+
+    // Spectra of the samples of the last process call, concatenated (spectrum().len() values
+    // per sample). Spectra are only recorded if record_spectra is enabled.
+    pub fn spectra(&self) -> &[f32] {
+        &self.spectra
+    }
+
+    pub fn record_spectra(&self) -> bool {
+        self.record_spectra
+    }
+
+    pub fn set_record_spectra(&mut self, record_spectra: bool) {
+        self.record_spectra = record_spectra;
+    }
+
+    pub fn profiling(&self) -> bool {
+        self.profiler.enabled()
+    }
+
+    // Per-stage timings are recorded from the moment profiling is enabled (see
+    // crate::profiling::Stage), until the next reset.
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profiler.set_enabled(profiling);
+    }
+
+    pub fn timing(&self, stage: crate::profiling::Stage) -> crate::profiling::Timing {
+        self.profiler.timing(stage)
+    }
+
+    // Level applied to the next call (see Configuration::max_latency_ms).
+    pub fn degradation(&self) -> crate::budget::Degradation {
+        self.degradation
+    }
+
+    pub fn record_flips(&self) -> bool {
+        self.record_flips
+    }
+
+    // Flips are accumulated until they are taken (see take_flips), disabling the recording
+    // drops the pending flips.
+    pub fn set_record_flips(&mut self, record_flips: bool) {
+        self.record_flips = record_flips;
+        if !record_flips {
+            self.flips
+                .iter_mut()
+                .for_each(std::collections::VecDeque::clear);
+        }
+    }
+
+    // Returns the flips recorded since the previous call, in timestamp order.
+    pub fn take_flips(&mut self) -> Vec<Flip> {
+        let spatial_downsampling = self.configuration.spatial_downsampling;
+        let downsampled_width = self.downsampled_width as usize;
+        let mut flips = Vec::with_capacity(self.flips.iter().map(|block| block.len()).sum());
+        for (index, block) in self.flips.iter_mut().enumerate() {
+            let x = (index % downsampled_width) as u16 * spatial_downsampling;
+            let y = (index / downsampled_width) as u16 * spatial_downsampling;
+            flips.extend(block.drain(..).map(|t| Flip { t, x, y }));
+        }
+        // stable, simultaneous flips stay in block order
+        flips.sort_by_key(|flip| flip.t);
+        flips
+    }
+
+    // Same as take_flips for the block that contains the pixel (x, y), the flips of other blocks
+    // are kept.
+    pub fn take_block_flips(&mut self, x: u16, y: u16) -> Result<Vec<Flip>, Error> {
+        if x >= self.configuration.width || y >= self.configuration.height {
+            return Err(Error::Pixel {
+                x,
+                y,
+                width: self.configuration.width,
+                height: self.configuration.height,
+            });
+        }
+        let spatial_downsampling = self.configuration.spatial_downsampling;
+        let (x, y) = (x / spatial_downsampling, y / spatial_downsampling);
+        let index = x as usize + y as usize * self.downsampled_width as usize;
+        let (x, y) = (x * spatial_downsampling, y * spatial_downsampling);
+        Ok(self.flips[index]
+            .drain(..)
+            .map(|t| Flip { t, x, y })
+            .collect())
+    }
+
+    pub fn autocorrelation(&self) -> &[f32] {
+        &self.autocorrelation[0..self.autocorrelation.len() - 1]
+    }
+
+    pub fn autocorrelation_detections(&self) -> AutocorrelationDetections {
+        self.autocorrelation_detections
+    }
+
+    pub fn clusters(&self) -> &[Vec<Cluster>] {
+        &self.clusters
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    // The region's bounds are sensor pixels, timelines whose block overlaps the region belong to
+    // it. Regions may overlap.
+    pub fn add_region(&mut self, name: &str, roi: Roi) -> Result<(), Error> {
+        if roi.x0 >= roi.x1
+            || roi.x1 > self.configuration.width
+            || roi.y0 >= roi.y1
+            || roi.y1 > self.configuration.height
+        {
+            return Err(Error::Roi {
+                x0: roi.x0,
+                y0: roi.y0,
+                x1: roi.x1,
+                y1: roi.y1,
+                width: self.configuration.width,
+                height: self.configuration.height,
+            });
+        }
+        if self.regions.iter().any(|region| region.name == name) {
+            return Err(Error::DuplicateRegion(name.to_owned()));
+        }
+        self.regions.push(Region {
+            name: name.to_owned(),
+            roi,
+        });
+        Ok(())
+    }
+
+    pub fn remove_region(&mut self, name: &str) -> Result<(), Error> {
+        let index = self
+            .regions
+            .iter()
+            .position(|region| region.name == name)
+            .ok_or_else(|| Error::UnknownRegion(name.to_owned()))?;
+        self.regions.remove(index);
+        Ok(())
+    }
+
+    // Estimates of the samples of the last process call, one per region (in insertion order)
+    // for each sample.
+    pub fn regions_samples(&self) -> &[Vec<RegionSample>] {
+        &self.regions_samples
+    }
+
+    // Radial profiles of the samples of the last process call (see Configuration::radial_bins),
+    // from the centre outwards. A profile is empty if radial_bins is 0 or if the rotor was not
+    // located (during the warmup for instance).
+    pub fn radial_profiles(&self) -> &[Vec<RadialBin>] {
+        &self.radial_profiles
+    }
+
+    // Number of flips dropped since the last reset because their timeline was full while they
+    // were still in the FFT window. A large value means that timeline_length is too small.
+    pub fn timeline_overflows(&self) -> u64 {
+        self.statistics.timeline_overflows
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        Statistics {
+            active_pixels: self.timelines.active(),
+            flicker_timelines: self.flicker_mask.iter().filter(|masked| **masked).count(),
+            ..self.statistics
+        }
+    }
+
+    // Spectra summed by the last sample, by decreasing activity (timelines without activity are
+    // left out).
+    pub fn timeline_spectra(&self) -> Vec<TimelineSpectrum<'_>> {
+        let spectrum_length = self.fft_sum.len();
+        let spatial_downsampling = self.configuration.spatial_downsampling;
+        self.timelines_spectra
+            .chunks_exact(spectrum_length)
+            .zip(self.timelines_activities_and_indices.iter())
+            .take(self.timelines_count)
+            .take_while(|(_, (activity, _))| *activity > 0.0)
+            .map(|(spectrum, (activity, index))| TimelineSpectrum {
+                x: (*index % self.downsampled_width as usize) as u16 * spatial_downsampling,
+                y: (*index / self.downsampled_width as usize) as u16 * spatial_downsampling,
+                activity: *activity,
+                spectrum: &spectrum[0..spectrum_length - 1],
+            })
+            .collect()
+    }
+
+    // (width, height) of the timelines grid.
+    pub fn downsampled_dimensions(&self) -> (u16, u16) {
+        (self.downsampled_width, self.downsampled_height)
+    }
+
+    // Timestamp of the last event (µs).
+    pub fn last_t(&self) -> u64 {
+        self.previous_t
+    }
+
+    // Timestamp plus one of the last accepted event of each sensor pixel (row-major), negated
+    // for OFF events, 0 if the pixel has not received events. The surface is not saved in the
+    // state.
+    pub fn surface(&self) -> &[i64] {
+        &self.surface
+    }
+
+    // The last strobe_length accepted events, oldest first. They are not saved in the state.
+    pub fn strobe_events(&self) -> &std::collections::VecDeque<Event> {
+        &self.strobe_events
+    }
+
+    // Decayed activity of each timeline at the last event, row-major.
+    pub fn activity_map(&self) -> Vec<f32> {
+        let activity_mu = self.activity_mu();
+        (0..self.timelines.len())
+            .map(|index| {
+                self.timelines
+                    .activity_at(index, self.previous_t, activity_mu) as f32
+            })
+            .collect()
+    }
+
+    // Estimates the number of blades from the spectrum and shaft frequency of the last sample
+    // (None if there is no sample or if no frequency was detected).
+    pub fn estimate_blade_count(
+        &self,
+        maximum_blade_count: usize,
+    ) -> Result<Option<BladeCount>, Error> {
+        if maximum_blade_count == 0 {
+            return Err(Error::MaximumBladeCount);
+        }
+        Ok(self.samples.last().and_then(|sample| {
+            blade_comb(
+                &self.fft_sum,
+                sample.shaft_frequency,
+                self.configuration.fft_frequency,
+                maximum_blade_count,
+                self.skip_low_frequency_samples,
+            )
+        }))
+    }
+
+    fn activity_mu(&self) -> f64 {
+        -1.0 / self.configuration.activity_tau as f64
+    }
+
+    // Adds the epoch offset to a raw timestamp. A backward jump by more than half the wrap period
+    // starts a new epoch, and a forward jump by more than half the wrap period (an event
+    // delivered late, just before the wrap) is assigned to the previous epoch.
+    fn unwrap_timestamp(&mut self, t: u64, wrap_period: u64) -> u64 {
+        let half_period = wrap_period / 2;
+        if t.saturating_add(half_period) < self.previous_raw_t {
+            self.epoch_offset = self.epoch_offset.saturating_add(wrap_period);
+        } else if t > self.previous_raw_t.saturating_add(half_period)
+            && self.epoch_offset >= wrap_period
+        {
+            return t + self.epoch_offset - wrap_period;
+        }
+        self.previous_raw_t = t;
+        t.saturating_add(self.epoch_offset)
+    }
+
+    // Activity at t, or number of flips in the ranking window, depending on the ranking.
+    fn ranking_score(&self, index: usize, t: u64) -> f64 {
+        if self.flicker_mask[index] {
+            return 0.0;
+        }
+        match self.configuration.ranking {
+            Ranking::Activity => self.timelines.activity_at(index, t, self.activity_mu()),
+            Ranking::Count => self
+                .timelines
+                .count_since(index, t.saturating_sub(self.configuration.ranking_window))
+                as f64,
+        }
+    }
+
+    fn update_degradation(&mut self) {
+        if let Some(max_latency_ms) = self.configuration.max_latency_ms {
+            self.degradation = self.degradation.next(
+                self.statistics.processing_duration,
+                std::time::Duration::from_secs_f64(max_latency_ms / 1e3),
+            );
+        }
+    }
+
+    // Sample windows per emitted sample, 2 under the SkippedWindows degradation.
+    fn window_stride(&self) -> usize {
+        if self.degradation >= crate::budget::Degradation::SkippedWindows {
+            2
+        } else {
+            1
+        }
+    }
+
+    // Number of timelines selected by the ranking, halved under the FewerTimelines degradation.
+    fn most_active_timelines(&self) -> usize {
+        if self.degradation >= crate::budget::Degradation::FewerTimelines {
+            self.configuration.most_active_timelines.div_ceil(2)
+        } else {
+            self.configuration.most_active_timelines
+        }
+    }
+
+    fn evaluate_sample(&mut self, t: u64, parameters: &Parameters) {
+        let end_t = match self.configuration.events_per_sample {
+            Some(_) => t,
+            None => self.next_sample_t,
+        };
+        // the events (and window statistics) of a skipped window go to the next sample
+        if self.configuration.events_per_sample.is_none()
+            && self.events_since_sample > 0
+            && !(self.sample_index + 1).is_multiple_of(self.window_stride())
+        {
+            self.sample_index += 1;
+            self.next_sample_t = sample_t(self.configuration.sampling_frequency, self.sample_index);
+            return;
+        }
+        // the FFT window still holds flips until the gap in the stream becomes longer than
+        // fft_samples / fft_frequency, later windows skip ranking and transforms
+        let fft_window =
+            (self.configuration.fft_samples as f64 * 1e6 / self.configuration.fft_frequency) as u64; // µs
+        if self.events_since_sample > 0
+            || self
+                .last_accepted_t
+                .is_some_and(|last_accepted_t| end_t.saturating_sub(last_accepted_t) <= fft_window)
+        {
+            self.push_sample(t, end_t, parameters);
+        } else {
+            self.push_empty_sample(end_t);
+            self.spectral_average.clear();
+            // a still rotor does not generate events
+            if let Some(change_threshold) = self.configuration.change_threshold {
+                self.detector
+                    .update(end_t, 0.0, 0.0, None, change_threshold, &mut self.changes);
+            }
+            self.update_alarm(end_t, 0.0);
+        }
+        self.events_since_sample = 0;
+        self.sample_index += 1;
+        self.next_sample_t = sample_t(self.configuration.sampling_frequency, self.sample_index);
+    }
+
+    // Computes the spectrum of the window that ends at t and pushes a sample with timestamp
+    // end_t (the sampling grid is not advanced).
+    fn push_sample(&mut self, t: u64, end_t: u64, parameters: &Parameters) {
+        if self.warmup_events > 0 || self.timelines.active() < self.configuration.warmup_timelines {
+            self.push_empty_sample(end_t);
+            return;
+        }
+        let fft_frequency = self.configuration.fft_frequency;
+        let spectrum_length = self.fft_sum.len();
+        let skip_low_frequency_samples = self.skip_low_frequency_samples;
+        let most_active_timelines = self.most_active_timelines();
+        let timer = self.profiler.start(crate::profiling::Stage::Ranking);
+        for index in 0..self.timelines.len() {
+            self.timelines_activities_and_indices[index] = (self.ranking_score(index, t), index);
+        }
+        let timelines_count = select_most_active(
+            &mut self.timelines_activities_and_indices,
+            most_active_timelines,
+            self.configuration.activity_fraction,
+        );
+        self.timelines_count = timelines_count;
+        // the selection reorders but keeps every timeline
+        let track = crate::tracking::estimate(
+            &self.timelines_activities_and_indices,
+            self.downsampled_width,
+            self.configuration.spatial_downsampling,
+        );
+        for (activity, index) in self.timelines_activities_and_indices.iter() {
+            self.timelines_scores[*index] = *activity;
+        }
+        let geometry = crate::tracking::fit_ellipse(
+            &self.timelines_scores,
+            self.downsampled_width,
+            self.configuration.spatial_downsampling,
+        );
+        self.profiler.stop(timer);
+        let timer = self.profiler.start(crate::profiling::Stage::Transform);
+        if self.configuration.method == Method::LombScargle
+            || !self.transform_on_gpu(t, timelines_count)
+        {
+            transform_timelines(
+                &self.timelines,
+                &self.timelines_activities_and_indices[0..timelines_count],
+                &mut self.timelines_spectra,
+                self.configuration.method,
+                &self.fft.forward,
+                &self.window,
+                fft_frequency,
+                t,
+                skip_low_frequency_samples,
+            );
+        }
+        self.profiler.stop(timer);
+        let timer = self.profiler.start(crate::profiling::Stage::Peak);
+        let flicker = match self.configuration.flicker_rejection {
+            Some(flicker_rejection) => {
+                // excluded timelines have no activity but may still be selected
+                for (timeline_spectrum, (_, index)) in self
+                    .timelines_spectra
+                    .chunks_mut(spectrum_length)
+                    .zip(self.timelines_activities_and_indices[0..timelines_count].iter())
+                {
+                    if self.flicker_mask[*index] {
+                        timeline_spectrum.fill(0.0);
+                    }
+                }
+                crate::flicker::detect(
+                    &self.timelines_spectra[0..timelines_count * spectrum_length],
+                    spectrum_length,
+                    self.frequency_resolution(),
+                    skip_low_frequency_samples,
+                    flicker_rejection,
+                    &mut self.flicker_harmonics,
+                )
+            }
+            None => None,
+        };
+        if let Some(flicker) = flicker {
+            self.flicker_frequency = Some(crate::flicker::FLICKER_FREQUENCIES[flicker]);
+        }
+        let mut flicker_timelines = 0;
+        self.fft_sum.fill(0.0);
+        for (index, timeline_spectrum) in self
+            .timelines_spectra
+            .chunks(spectrum_length)
+            .take(timelines_count)
+            .enumerate()
+        {
+            if flicker.is_some() && self.flicker_harmonics[index] == flicker {
+                self.flicker_mask[self.timelines_activities_and_indices[index].1] = true;
+                flicker_timelines += 1;
+                continue;
+            }
+            for (sum, amplitude) in self.fft_sum.iter_mut().zip(timeline_spectrum.iter()) {
+                *sum += *amplitude;
+            }
+        }
+        let mut maximum_amplitude = normalize(
+            &mut self.fft_sum,
+            match self.configuration.activity_fraction {
+                Some(_) => timelines_count,
+                None => most_active_timelines,
+            },
+            skip_low_frequency_samples,
+        );
+        if let Some(spectral_smoothing) = self.configuration.spectral_smoothing {
+            if self.spectral_average.is_empty() {
+                self.spectral_average.extend_from_slice(&self.fft_sum);
+            } else {
+                let spectral_smoothing = spectral_smoothing as f32;
+                maximum_amplitude = 0.0;
+                for (index, (average, amplitude)) in self
+                    .spectral_average
+                    .iter_mut()
+                    .zip(self.fft_sum.iter_mut())
+                    .enumerate()
+                {
+                    *average += spectral_smoothing * (*amplitude - *average);
+                    *amplitude = *average;
+                    if index >= skip_low_frequency_samples {
+                        maximum_amplitude = maximum_amplitude.max(*amplitude);
+                    }
+                }
+            }
+        }
+        let (frequency, confidence) = match self.configuration.method {
+            Method::Autocorrelation | Method::LombScargle => autocorrelation_peak(
+                &mut self.fft,
+                &self.fft_sum,
+                maximum_amplitude,
+                parameters,
+                fft_frequency,
+                self.configuration.peak_interpolation,
+                &mut self.autocorrelation,
+                &mut self.autocorrelation_detections,
+            ),
+            Method::HarmonicProduct => harmonic_product_peak(
+                &self.fft_sum,
+                maximum_amplitude,
+                parameters,
+                fft_frequency,
+                self.configuration.harmonics,
+                skip_low_frequency_samples,
+                self.configuration.peak_interpolation,
+            ),
+        };
+        let mut blade_count = dominant_harmonic(
+            &self.fft_sum,
+            frequency,
+            fft_frequency,
+            self.configuration.harmonics,
+        );
+        let mut low_frequency = false;
+        let (frequency, confidence) = if self.configuration.low_frequency == LowFrequency::Off {
+            (frequency, confidence)
+        } else {
+            let (estimate, low_frequency_blade_count) =
+                self.evaluate_low_frequency(t, timelines_count, parameters);
+            if self.configuration.low_frequency == LowFrequency::On
+                || (estimate.frequency < MINIMUM_FREQUENCY as f32 && estimate.confidence > 0.0)
+            {
+                blade_count = low_frequency_blade_count;
+                low_frequency = true;
+                (estimate.frequency, estimate.confidence)
+            } else {
+                (frequency, confidence)
+            }
+        };
+        let rate = if low_frequency {
+            self.configuration.low_frequency_rate
+        } else {
+            fft_frequency
+        }; // Hz
+        let window_start_t = t.saturating_sub((self.window.len() as f64 * 1e6 / rate) as u64);
+        let frequency = if self.configuration.refine && confidence > 0.0 {
+            refine_frequency(
+                &self.timelines,
+                self.timelines_activities_and_indices[0..timelines_count]
+                    .iter()
+                    .filter(|(activity, index)| *activity > 0.0 && !self.flicker_mask[*index])
+                    .map(|(_, index)| *index),
+                frequency,
+                rate / (self.configuration.fft_samples * self.configuration.zero_padding) as f64,
+                self.configuration.harmonics,
+                window_start_t,
+                t,
+            )
+        } else {
+            frequency
+        };
+        let smoothed_frequency = self.smoother.update(end_t, frequency);
+        let window = self.take_window_statistics(end_t);
+        let direction = track.and_then(|track| {
+            self.direction(
+                window_start_t,
+                t,
+                frequency,
+                track.x,
+                track.y,
+                timelines_count,
+            )
+        });
+        let rpm = frequency * 60.0 * parameters.frequency_multiplier;
+        let radius = match self.scale {
+            Some(Scale::MetersPerPixel(meters_per_pixel)) => geometry
+                .map(|geometry| geometry.semi_major as f64 * meters_per_pixel)
+                .unwrap_or(f64::NAN),
+            Some(Scale::RotorRadius(radius)) => radius,
+            None => f64::NAN,
+        }; // m
+        self.samples.push(Sample {
+            t: end_t,
+            rpm,
+            confidence,
+            shaft_frequency: frequency,
+            blade_count,
+            timelines: self.timelines_activities_and_indices[0..timelines_count]
+                .iter()
+                .take_while(|(activity, _)| *activity > 0.0)
+                .count()
+                .saturating_sub(flicker_timelines),
+            smoothed_rpm: smoothed_frequency * 60.0 * parameters.frequency_multiplier,
+            phase: self.phase(end_t, frequency),
+            window,
+            track,
+            geometry,
+            tip_speed: (std::f64::consts::TAU * radius * rpm as f64 / 60.0) as f32,
+            low_frequency,
+            direction,
+            degradation: self.degradation,
+            imbalance_index: match self.configuration.blades {
+                Some(blades) if !low_frequency => imbalance_index(
+                    &self.fft_sum,
+                    frequency,
+                    blades,
+                    fft_frequency,
+                    self.configuration.harmonics,
+                    skip_low_frequency_samples,
+                ),
+                _ => f32::NAN,
+            },
+        });
+        if let Some(change_threshold) = self.configuration.change_threshold {
+            self.detector.update(
+                end_t,
+                frequency,
+                smoothed_frequency,
+                direction,
+                change_threshold,
+                &mut self.changes,
+            );
+        }
+        // the smoother predicts a frequency for samples without a detected peak
+        self.update_alarm(
+            end_t,
+            if frequency > 0.0 {
+                smoothed_frequency * 60.0 * parameters.frequency_multiplier
+            } else {
+                0.0
+            },
+        );
+        if let Some(track) = track.filter(|_| self.configuration.roi_tracking) {
+            // drifts smaller than a block are ignored to avoid jitter
+            self.roi = crate::tracking::recenter(
+                self.roi,
+                track,
+                self.configuration.width,
+                self.configuration.height,
+                self.configuration.spatial_downsampling as f32,
+            );
+        }
+        if self.record_spectra {
+            self.spectra
+                .extend_from_slice(&self.fft_sum[0..self.fft_sum.len() - 1]);
+        }
+        self.evaluate_clusters(parameters);
+        self.evaluate_regions(t, parameters);
+        self.evaluate_radial_profile(t, track, geometry, parameters);
+        self.profiler.stop(timer);
+    }
+
+    // Computes the spectra of the most active timelines on the GPU. Returns false if the
+    // extension was built without the gpu feature or if no device is available, the spectra are
+    // then computed on the CPU.
+    #[cfg(feature = "gpu")]
+    fn transform_on_gpu(&mut self, t: u64, timelines_count: usize) -> bool {
+        let Some(gpu) = self.gpu.as_ref() else {
+            return false;
+        };
+        let fft_frequency = self.configuration.fft_frequency;
+        let fft_length = self.fft.samples.len();
+        let spectrum_length = self.fft_sum.len();
+        let timelines = &self.timelines;
+        let window = &self.window;
+        self.gpu_samples.resize(timelines_count * fft_length, 0.0);
+        self.gpu_samples
+            .par_chunks_mut(fft_length)
+            .zip(self.timelines_activities_and_indices[0..timelines_count].par_iter())
+            .for_each(|(samples, (_, index))| {
+                timelines.fill(*index, samples, fft_frequency, window, t);
+            });
+        let timelines_spectra = &mut self.timelines_spectra[0..timelines_count * spectrum_length];
+        if !gpu.transform(&self.gpu_samples, timelines_spectra) {
+            return false;
+        }
+        for timeline_spectrum in timelines_spectra.chunks_mut(spectrum_length) {
+            timeline_spectrum[0..self.skip_low_frequency_samples].fill(0.0);
+        }
+        true
+    }
+
+    #[cfg(not(feature = "gpu"))]
+    fn transform_on_gpu(&mut self, _t: u64, _timelines_count: usize) -> bool {
+        false
+    }
+
+    // Must be called once per sample, before events_since_sample is reset. The next window
+    // starts at end_t.
+    fn take_window_statistics(&mut self, end_t: u64) -> WindowStatistics {
+        let sums = std::mem::replace(
+            &mut self.window_sums,
+            WindowSums {
+                start_t: Some(end_t),
+                ..Default::default()
+            },
+        );
+        let events = self.events_since_sample as f64;
+        let duration = end_t.saturating_sub(sums.start_t.unwrap_or(end_t)) as f64 / 1e6; // s
+        let (mean_x, mean_y) = (sums.x / events, sums.y / events);
+        let flip_rate = |flips: usize| {
+            if duration > 0.0 && self.configuration.mode == Mode::Rotation {
+                (flips as f64 / duration) as f32
+            } else {
+                f32::NAN
+            }
+        };
+        WindowStatistics {
+            events: self.events_since_sample,
+            on_ratio: (sums.on_events as f64 / events) as f32,
+            event_rate: if duration > 0.0 {
+                (events / duration) as f32
+            } else {
+                f32::NAN
+            },
+            neighbourhood_flip_rate: flip_rate(sums.neighbourhood_flips),
+            transition_flip_rate: flip_rate(sums.transition_flips),
+            spread: if self.events_since_sample > 0 {
+                // rounding errors may yield tiny negative variances
+                (sums.squared_x / events - mean_x.powi(2) + sums.squared_y / events
+                    - mean_y.powi(2))
+                .max(0.0)
+                .sqrt() as f32
+            } else {
+                f32::NAN
+            },
+        }
+    }
+
+    // Samples emitted before the warmup conditions are met, or for windows that end more than
+    // fft_samples / fft_frequency after the last accepted event, have a NaN RPM and do not update
+    // the smoother.
+    fn push_empty_sample(&mut self, end_t: u64) {
+        let window = self.take_window_statistics(end_t);
+        self.samples.push(Sample {
+            t: end_t,
+            rpm: f32::NAN,
+            confidence: 0.0,
+            shaft_frequency: f32::NAN,
+            blade_count: 0,
+            timelines: 0,
+            smoothed_rpm: f32::NAN,
+            phase: None,
+            window,
+            track: None,
+            geometry: None,
+            tip_speed: f32::NAN,
+            low_frequency: false,
+            direction: None,
+            degradation: self.degradation,
+            imbalance_index: f32::NAN,
+        });
+        if self.record_spectra {
+            self.spectra
+                .extend(std::iter::repeat_n(0.0, self.fft_sum.len() - 1));
+        }
+        self.clusters.push(Vec::new());
+        self.regions_samples.push(
+            self.regions
+                .iter()
+                .map(|_| RegionSample {
+                    rpm: f32::NAN,
+                    frequency: f32::NAN,
+                    confidence: 0.0,
+                    timelines: 0,
+                })
+                .collect(),
+        );
+        self.radial_profiles.push(Vec::new());
+    }
+
+    // The phase is the fraction of the period (1 / frequency) elapsed since the last flip of
+    // the most active timeline, which serves as the angular reference.
+    fn phase(&self, t: u64, frequency: f32) -> Option<f32> {
+        if frequency <= 0.0 {
+            return None;
+        }
+        let (activity, index) = *self.timelines_activities_and_indices.first()?;
+        if activity <= 0.0 {
+            return None;
+        }
+        let last_flip_t = self.timelines.last(index)?;
+        let turns = (t.saturating_sub(last_flip_t) as f64 / 1e6) * frequency as f64;
+        Some((turns.fract() * std::f64::consts::TAU) as f32)
+    }
+
+    fn update_alarm(&mut self, t: u64, rpm: f32) {
+        if let Some(alarm) = self.alarm {
+            let state = alarm.next_state(self.alarm_state, rpm);
+            if state != self.alarm_state {
+                self.alarm_state = state;
+                self.alarm_transitions
+                    .push(crate::alarm::Transition { t, state });
+            }
+        }
+    }
+
+    // Phases are those of the timelines' flips in [start_t, t], averaged as phasors.
+    fn direction(
+        &self,
+        start_t: u64,
+        t: u64,
+        frequency: f32,
+        x: f32,
+        y: f32,
+        timelines_count: usize,
+    ) -> Option<crate::tracking::Direction> {
+        if frequency <= 0.0 {
+            return None;
+        }
+        let angular_frequency = std::f64::consts::TAU * frequency as f64; // rad/s
+        let spatial_downsampling = self.configuration.spatial_downsampling as f64;
+        let phases_and_positions: Vec<(f64, f64, f64)> = self.timelines_activities_and_indices
+            [0..timelines_count]
+            .iter()
+            .filter(|(activity, index)| *activity > 0.0 && !self.flicker_mask[*index])
+            .filter_map(|(_, index)| {
+                let (mut cosines, mut sines) = (0.0, 0.0);
+                for timestamp in self.timelines.timestamps(*index) {
+                    if timestamp >= start_t && timestamp <= t {
+                        let (sine, cosine) =
+                            (angular_frequency * (t - timestamp) as f64 / 1e6).sin_cos();
+                        cosines += cosine;
+                        sines += sine;
+                    }
+                }
+                (cosines != 0.0 || sines != 0.0).then(|| {
+                    (
+                        sines.atan2(cosines) / std::f64::consts::TAU,
+                        ((index % self.downsampled_width as usize) as f64 + 0.5)
+                            * spatial_downsampling,
+                        ((index / self.downsampled_width as usize) as f64 + 0.5)
+                            * spatial_downsampling,
+                    )
+                })
+            })
+            .collect();
+        crate::tracking::direction(&phases_and_positions, x, y, DEFAULT_MAXIMUM_BLADE_COUNT)
+    }
+
+    fn evaluate_clusters(&mut self, parameters: &Parameters) {
+        let spectrum_length = self.cluster_spectrum.len();
+        let spatial_downsampling = self.configuration.spatial_downsampling;
+        self.clusters_positions.clear();
+        for (activity, index) in self
+            .timelines_activities_and_indices
+            .iter()
+            .take(self.timelines_count)
+        {
+            if *activity <= 0.0 {
+                break;
+            }
+            self.clusters_positions.push((
+                (*index % self.downsampled_width as usize) as u16,
+                (*index / self.downsampled_width as usize) as u16,
+            ));
+        }
+        let clusters_count =
+            crate::clusters::label(&self.clusters_positions, &mut self.clusters_labels);
+        let mut clusters = Vec::with_capacity(clusters_count);
+        for label in 0..clusters_count {
+            self.cluster_spectrum.fill(0.0);
+            let mut cluster = Cluster {
+                rpm: 0.0,
+                frequency: 0.0,
+                confidence: 0.0,
+                x0: u16::MAX,
+                y0: u16::MAX,
+                x1: 0,
+                y1: 0,
+                timelines: 0,
+            };
+            for (rank, (x, y)) in self.clusters_positions.iter().enumerate() {
+                if self.clusters_labels[rank] == label {
+                    for (sum, amplitude) in self.cluster_spectrum.iter_mut().zip(
+                        self.timelines_spectra
+                            [rank * spectrum_length..(rank + 1) * spectrum_length]
+                            .iter(),
+                    ) {
+                        *sum += *amplitude;
+                    }
+                    cluster.x0 = cluster.x0.min(x * spatial_downsampling);
+                    cluster.y0 = cluster.y0.min(y * spatial_downsampling);
+                    cluster.x1 = cluster.x1.max(
+                        ((*x as u32 + 1) * spatial_downsampling as u32)
+                            .min(self.configuration.width as u32) as u16,
+                    );
+                    cluster.y1 = cluster.y1.max(
+                        ((*y as u32 + 1) * spatial_downsampling as u32)
+                            .min(self.configuration.height as u32) as u16,
+                    );
+                    cluster.timelines += 1;
+                }
+            }
+            let maximum_amplitude = normalize(
+                &mut self.cluster_spectrum,
+                cluster.timelines,
+                self.skip_low_frequency_samples,
+            );
+            let (frequency, confidence) = detect_peak(
+                &self.configuration,
+                &mut self.fft,
+                &self.cluster_spectrum,
+                maximum_amplitude,
+                parameters,
+                self.skip_low_frequency_samples,
+                &mut self.cluster_autocorrelation,
+            );
+            cluster.rpm = frequency * 60.0 * parameters.frequency_multiplier;
+            cluster.frequency = frequency;
+            cluster.confidence = confidence;
+            clusters.push(cluster);
+        }
+        self.clusters.push(clusters);
+    }
+
+    // Each region selects its own most active timelines, hence their spectra are computed
+    // separately from the global ones.
+    fn evaluate_regions(&mut self, t: u64, parameters: &Parameters) {
+        let spatial_downsampling = self.configuration.spatial_downsampling;
+        let mut regions_samples = Vec::with_capacity(self.regions.len());
+        for region_index in 0..self.regions.len() {
+            let roi = self.regions[region_index].roi;
+            let mut region_activities_and_indices =
+                std::mem::take(&mut self.region_activities_and_indices);
+            region_activities_and_indices.clear();
+            for y in roi.y0 / spatial_downsampling..=(roi.y1 - 1) / spatial_downsampling {
+                for x in roi.x0 / spatial_downsampling..=(roi.x1 - 1) / spatial_downsampling {
+                    let index = x as usize + y as usize * self.downsampled_width as usize;
+                    region_activities_and_indices.push((self.ranking_score(index, t), index));
+                }
+            }
+            regions_samples.push(self.evaluate_subset(
+                t,
+                &mut region_activities_and_indices,
+                parameters,
+                self.configuration.fft_frequency,
+            ));
+            self.region_activities_and_indices = region_activities_and_indices;
+        }
+        self.regions_samples.push(regions_samples);
+    }
+
+    // The rotor's centre and radius are those of the fitted geometry, or of the track (as a
+    // circle) if no ellipse was fitted. The profile is empty if there is neither.
+    fn evaluate_radial_profile(
+        &mut self,
+        t: u64,
+        track: Option<crate::tracking::Track>,
+        geometry: Option<crate::tracking::Geometry>,
+        parameters: &Parameters,
+    ) {
+        let bins = self.configuration.radial_bins;
+        // (x, y, radius, angle, semi_major / semi_minor)
+        let (x, y, radius, angle, ratio) = match (geometry, track) {
+            (Some(geometry), _) => (
+                geometry.x,
+                geometry.y,
+                geometry.semi_major,
+                geometry.angle,
+                geometry.semi_major / geometry.semi_minor,
+            ),
+            (None, Some(track)) => (track.x, track.y, track.radius, 0.0, 1.0),
+            (None, None) => (0.0, 0.0, 0.0, 0.0, 1.0),
+        };
+        if bins == 0 || radius <= 0.0 || !ratio.is_finite() {
+            self.radial_profiles.push(Vec::new());
+            return;
+        }
+        let mut radial_activities_and_indices =
+            std::mem::take(&mut self.radial_activities_and_indices);
+        for bin_activities_and_indices in radial_activities_and_indices.iter_mut() {
+            bin_activities_and_indices.clear();
+        }
+        let spatial_downsampling = self.configuration.spatial_downsampling as f32;
+        let (sin, cos) = angle.sin_cos();
+        for (index, score) in self.timelines_scores.iter().enumerate() {
+            let dx =
+                ((index % self.downsampled_width as usize) as f32 + 0.5) * spatial_downsampling - x;
+            let dy =
+                ((index / self.downsampled_width as usize) as f32 + 0.5) * spatial_downsampling - y;
+            // the minor axis is foreshortened by the tilt
+            let distance = (dx * cos + dy * sin).hypot((dy * cos - dx * sin) * ratio) / radius;
+            let bin = (distance * bins as f32) as usize;
+            if bin < bins {
+                radial_activities_and_indices[bin].push((*score, index));
+            }
+        }
+        let mut profile = Vec::with_capacity(bins);
+        for (bin, bin_activities_and_indices) in
+            radial_activities_and_indices.iter_mut().enumerate()
+        {
+            let bin_radius = (bin as f32 + 0.5) * radius / bins as f32;
+            if bin_activities_and_indices.is_empty() {
+                profile.push(RadialBin {
+                    radius: bin_radius,
+                    rpm: f32::NAN,
+                    frequency: f32::NAN,
+                    confidence: 0.0,
+                    timelines: 0,
+                });
+                continue;
+            }
+            let estimate = self.evaluate_subset(
+                t,
+                bin_activities_and_indices,
+                parameters,
+                self.configuration.fft_frequency,
+            );
+            profile.push(RadialBin {
+                radius: bin_radius,
+                rpm: estimate.rpm,
+                frequency: estimate.frequency,
+                confidence: estimate.confidence,
+                timelines: estimate.timelines,
+            });
+        }
+        self.radial_activities_and_indices = radial_activities_and_indices;
+        self.radial_profiles.push(profile);
+    }
+
+    // Estimates the frequency of the timelines selected by the last sample, resampled at
+    // low_frequency_rate (see LowFrequency). Returns the estimate and its blade count.
+    fn evaluate_low_frequency(
+        &mut self,
+        t: u64,
+        timelines_count: usize,
+        parameters: &Parameters,
+    ) -> (RegionSample, usize) {
+        let low_frequency_rate = self.configuration.low_frequency_rate;
+        let mut activities_and_indices = std::mem::take(&mut self.region_activities_and_indices);
+        activities_and_indices.clear();
+        activities_and_indices
+            .extend_from_slice(&self.timelines_activities_and_indices[0..timelines_count]);
+        let estimate = self.evaluate_subset(
+            t,
+            &mut activities_and_indices,
+            parameters,
+            low_frequency_rate,
+        );
+        self.region_activities_and_indices = activities_and_indices;
+        let blade_count = dominant_harmonic(
+            &self.cluster_spectrum,
+            estimate.frequency,
+            low_frequency_rate,
+            self.configuration.harmonics,
+        );
+        (estimate, blade_count)
+    }
+
+    // Estimates the frequency of the most active timelines among activities_and_indices (which
+    // is reordered), independently of the main estimate. Timelines are resampled at
+    // fft_frequency.
+    fn evaluate_subset(
+        &mut self,
+        t: u64,
+        activities_and_indices: &mut [(f64, usize)],
+        parameters: &Parameters,
+        fft_frequency: f64,
+    ) -> RegionSample {
+        let spectrum_length = self.cluster_spectrum.len();
+        let most_active_timelines = self.most_active_timelines();
+        let timelines_count = select_most_active(
+            activities_and_indices,
+            most_active_timelines,
+            self.configuration.activity_fraction,
+        );
+        transform_timelines(
+            &self.timelines,
+            &activities_and_indices[0..timelines_count],
+            &mut self.region_spectra,
+            self.configuration.method,
+            &self.fft.forward,
+            &self.window,
+            fft_frequency,
+            t,
+            self.skip_low_frequency_samples,
+        );
+        self.cluster_spectrum.fill(0.0);
+        for timeline_spectrum in self
+            .region_spectra
+            .chunks(spectrum_length)
+            .take(timelines_count)
+        {
+            for (sum, amplitude) in self
+                .cluster_spectrum
+                .iter_mut()
+                .zip(timeline_spectrum.iter())
+            {
+                *sum += *amplitude;
+            }
+        }
+        let maximum_amplitude = normalize(
+            &mut self.cluster_spectrum,
+            match self.configuration.activity_fraction {
+                Some(_) => timelines_count,
+                None => most_active_timelines,
+            },
+            self.skip_low_frequency_samples,
+        );
+        let (frequency, confidence) = detect_peak(
+            &Configuration {
+                fft_frequency,
+                ..self.configuration
+            },
+            &mut self.fft,
+            &self.cluster_spectrum,
+            maximum_amplitude,
+            parameters,
+            self.skip_low_frequency_samples,
+            &mut self.cluster_autocorrelation,
+        );
+        RegionSample {
+            rpm: frequency * 60.0 * parameters.frequency_multiplier,
+            frequency,
+            confidence,
+            timelines: activities_and_indices[0..timelines_count]
+                .iter()
+                .take_while(|(activity, _)| *activity > 0.0)
+                .count(),
+        }
+    }
+
+    fn push_event(&mut self, event: Event) {
+        let spatial_downsampling = self.configuration.spatial_downsampling;
+        if self.degradation == crate::budget::Degradation::CoarseGrid {
+            // a coarse block is a square of 2 × 2 blocks, represented by its top-left block
+            self.push_downsampled_event(
+                (event.x / spatial_downsampling) & !1,
+                (event.y / spatial_downsampling) & !1,
+                event.t,
+                event.polarity,
+            );
+        } else if self.configuration.anti_aliasing {
+            let half = spatial_downsampling as u32 / 2;
+            let x0 = (event.x as u32).saturating_sub(half) / spatial_downsampling as u32;
+            let x1 = ((event.x as u32 + half) / spatial_downsampling as u32)
+                .min(self.downsampled_width as u32 - 1);
+            let y0 = (event.y as u32).saturating_sub(half) / spatial_downsampling as u32;
+            let y1 = ((event.y as u32 + half) / spatial_downsampling as u32)
+                .min(self.downsampled_height as u32 - 1);
+            for y in y0..=y1 {
+                for x in x0..=x1 {
+                    self.push_downsampled_event(x as u16, y as u16, event.t, event.polarity);
+                }
+            }
+        } else {
+            self.push_downsampled_event(
+                event.x / spatial_downsampling,
+                event.y / spatial_downsampling,
+                event.t,
+                event.polarity,
+            );
+        }
+    }
+
+    fn push_downsampled_event(
+        &mut self,
+        x: u16,
+        y: u16,
+        t: u64,
+        polarity: neuromorphic_types::DvsPolarity,
+    ) {
+        let downsampled_index = x as usize + (y as usize * self.downsampled_width as usize);
+        let polarity_sign = match polarity {
+            neuromorphic_types::DvsPolarity::Off => Sign::Negative,
+            neuromorphic_types::DvsPolarity::On => Sign::Positive,
+        };
+        let previous_t = self.signed_timestamps[downsampled_index];
+        self.polarity_runs[downsampled_index] = match polarity_sign {
+            Sign::Negative if previous_t < 0.0 => {
+                self.polarity_runs[downsampled_index].saturating_add(1)
+            }
+            Sign::Positive if previous_t > 0.0 => {
+                self.polarity_runs[downsampled_index].saturating_add(1)
+            }
+            _ => 1,
+        };
+        self.signed_timestamps[downsampled_index] = match polarity {
+            neuromorphic_types::DvsPolarity::Off => -(t as f64),
+            neuromorphic_types::DvsPolarity::On => t as f64,
+        };
+        let sign = match self.configuration.mode {
+            Mode::Rotation => {
+                let neighbourhood_sign = self.neighbourhood_sign(x, y);
+                let transition_sign = if self.polarity_runs[downsampled_index]
+                    >= self.configuration.debounce_events
+                {
+                    polarity_sign
+                } else {
+                    Sign::None
+                };
+                let (sign, alternative_sign, alternative) = match self.configuration.sign_detection
+                {
+                    SignDetection::Neighbourhood => (
+                        neighbourhood_sign,
+                        transition_sign,
+                        SignDetection::Transition,
+                    ),
+                    SignDetection::Transition => (
+                        transition_sign,
+                        neighbourhood_sign,
+                        SignDetection::Neighbourhood,
+                    ),
+                };
+                if !matches!(alternative_sign, Sign::None) {
+                    let previous_sign = self.alternative_signs[downsampled_index];
+                    if !matches!(previous_sign, Sign::None) && alternative_sign != previous_sign {
+                        self.count_flip(alternative);
+                    }
+                    self.alternative_signs[downsampled_index] = alternative_sign;
+                }
+                sign
+            }
+            Mode::Vibration => polarity_sign,
+        };
+        if !matches!(sign, Sign::None) {
+            let previous_sign = self.signs[downsampled_index];
+            if !matches!(previous_sign, Sign::None) && sign != previous_sign {
+                if self.configuration.mode == Mode::Rotation {
+                    self.count_flip(self.configuration.sign_detection);
+                }
+                if self.record_flips {
+                    let block = &mut self.flips[downsampled_index];
+                    if block.len() == self.configuration.flips_capacity {
+                        block.pop_front();
+                        self.statistics.dropped_flips += 1;
+                    }
+                    block.push_back(t);
+                }
+                let activity_mu = self.activity_mu();
+                if let Some(overwritten_t) = self.timelines.push(downsampled_index, t, activity_mu)
+                {
+                    // flips that are still in the FFT window are lost
+                    let window_duration =
+                        (self.window.len() as f64 * 1e6 / self.configuration.fft_frequency) as u64;
+                    if overwritten_t + window_duration > t {
+                        self.statistics.timeline_overflows += 1;
+                    }
+                }
+            }
+            self.signs[downsampled_index] = sign;
+        }
+    }
+
+    fn count_flip(&mut self, sign_detection: SignDetection) {
+        match sign_detection {
+            SignDetection::Neighbourhood => {
+                self.window_sums.neighbourhood_flips += 1;
+                self.statistics.neighbourhood_flips += 1;
+            }
+            SignDetection::Transition => {
+                self.window_sums.transition_flips += 1;
+                self.statistics.transition_flips += 1;
+            }
+        }
+    }
+
+    // Majority sign of the blocks around (x, y) (see Configuration::sign_check_radius), None if
+    // more than sign_check_tolerance blocks disagree with it or have not received events yet.
+    fn neighbourhood_sign(&self, x: u16, y: u16) -> Sign {
+        // neighbours are coarse blocks under the CoarseGrid degradation (see push_event)
+        let step = if self.degradation == crate::budget::Degradation::CoarseGrid {
+            2
+        } else {
+            1
+        };
+        let radius = self.configuration.sign_check_radius.saturating_mul(step);
+        let mut negatives = 0;
+        let mut positives = 0;
+        let mut blocks = 0;
+        for window_y in (y.saturating_sub(radius)
+            ..=y.saturating_add(radius).min(self.downsampled_height - 1))
+            .step_by(step as usize)
+        {
+            for window_x in (x.saturating_sub(radius)
+                ..=x.saturating_add(radius).min(self.downsampled_width - 1))
+                .step_by(step as usize)
+            {
+                let window_t = self.signed_timestamps
+                    [window_x as usize + (window_y as usize * self.downsampled_width as usize)];
+                if window_t < 0.0 {
+                    negatives += 1;
+                } else if window_t > 0.0 {
+                    positives += 1;
+                }
+                blocks += 1;
+            }
+        }
+        let (sign, agreements) = match negatives.cmp(&positives) {
+            std::cmp::Ordering::Less => (Sign::Positive, positives),
+            std::cmp::Ordering::Greater => (Sign::Negative, negatives),
+            std::cmp::Ordering::Equal => return Sign::None,
+        };
+        if blocks - agreements > self.configuration.sign_check_tolerance {
+            Sign::None
+        } else {
+            sign
+        }
+    }
+}
+
+// Peak of a cluster or region spectrum (see autocorrelation_peak and harmonic_product_peak).
+fn detect_peak(
+    configuration: &Configuration,
+    fft: &mut FftBuffers,
+    spectrum: &[f32],
+    maximum_amplitude: f32,
+    parameters: &Parameters,
+    skip_low_frequency_samples: usize,
+    autocorrelation: &mut [f32],
+) -> (f32, f32) {
+    match configuration.method {
+        Method::Autocorrelation | Method::LombScargle => autocorrelation_peak(
+            fft,
+            spectrum,
+            maximum_amplitude,
+            parameters,
+            configuration.fft_frequency,
+            configuration.peak_interpolation,
+            autocorrelation,
+            &mut AutocorrelationDetections::default(),
+        ),
+        Method::HarmonicProduct => harmonic_product_peak(
+            spectrum,
+            maximum_amplitude,
+            parameters,
+            configuration.fft_frequency,
+            configuration.harmonics,
+            skip_low_frequency_samples,
+            configuration.peak_interpolation,
+        ),
+    }
+}
+
+// Moves the count most active entries to the front, sorted by decreasing activity, and returns
+// the number of entries to use (see Configuration::activity_fraction).
+pub fn select_most_active(
+    activities_and_indices: &mut [(f64, usize)],
+    count: usize,
+    activity_fraction: Option<f64>,
+) -> usize {
+    let count = count.min(activities_and_indices.len());
+    if count == 0 {
+        return 0;
+    }
+    // only the most active timelines need to be sorted, the partition is O(n)
+    if count < activities_and_indices.len() {
+        activities_and_indices.select_nth_unstable_by(count - 1, compare_activities);
+    }
+    activities_and_indices[0..count].sort_by(compare_activities);
+    match activity_fraction {
+        Some(activity_fraction) => {
+            let threshold = activities_and_indices[0].0 * activity_fraction;
+            activities_and_indices[0..count]
+                .iter()
+                .take_while(|(activity, _)| *activity > 0.0 && *activity >= threshold)
+                .count()
+        }
+        None => count,
+    }
+}
+
+// Computes the spectra of the given timelines on the CPU (one spectrum per spectra chunk).
+#[allow(clippy::too_many_arguments)]
+fn transform_timelines(
+    timelines: &Timelines,
+    activities_and_indices: &[(f64, usize)],
+    spectra: &mut [f32],
+    method: Method,
+    forward: &std::sync::Arc<dyn realfft::RealToComplex<f32>>,
+    window: &[f32],
+    fft_frequency: f64,
+    t: u64,
+    skip_low_frequency_samples: usize,
+) {
+    let spectrum_length = forward.len() / 2 + 1;
+    if method == Method::LombScargle {
+        spectra
+            .par_chunks_mut(spectrum_length)
+            .zip(activities_and_indices.par_iter())
+            .for_each_init(
+                || vec![[0.0; 4]; spectrum_length],
+                |sums, (timeline_spectrum, (_, index))| {
+                    timelines.periodogram(
+                        *index,
+                        sums,
+                        timeline_spectrum,
+                        fft_frequency,
+                        window,
+                        t,
+                        skip_low_frequency_samples,
+                    );
+                },
+            );
+    } else {
+        spectra
+            .par_chunks_mut(spectrum_length)
+            .zip(activities_and_indices.par_iter())
+            .for_each_init(
+                || {
+                    (
+                        forward.make_input_vec(),
+                        forward.make_output_vec(),
+                        forward.make_scratch_vec(),
+                    )
+                },
+                |(samples, spectrum, scratch), (timeline_spectrum, (_, index))| {
+                    timelines.fill(*index, samples, fft_frequency, window, t);
+                    forward
+                        .process_with_scratch(samples, spectrum, scratch)
+                        .expect("the buffers have the planned lengths");
+                    timeline_spectrum.fill(0.0);
+                    for (sample_index, sample) in
+                        spectrum.iter().enumerate().skip(skip_low_frequency_samples)
+                    {
+                        timeline_spectrum[sample_index] = sample.norm_sqr().sqrt();
+                    }
+                },
+            );
+    }
+}
+
+// Sorts by decreasing activity, ties are broken by index.
+fn compare_activities(a: &(f64, usize), b: &(f64, usize)) -> std::cmp::Ordering {
+    if a.0 < b.0 {
+        std::cmp::Ordering::Greater
+    } else if a.0 > b.0 {
+        std::cmp::Ordering::Less
+    } else {
+        a.1.cmp(&b.1)
+    }
+}
+
+// Returns the end of the sample window with the given index, in µs.
+fn sample_t(sampling_frequency: f64, sample_index: usize) -> u64 {
+    ((sample_index + 1) as f64 * (1e6 / sampling_frequency)).round() as u64
+}
+
+#[derive(Clone)]
+struct FftBuffers {
+    samples: Vec<f32>,
+    spectrum: Vec<realfft::num_complex::Complex32>,
+    scratch: Vec<realfft::num_complex::Complex32>,
+    forward: std::sync::Arc<dyn realfft::RealToComplex<f32>>,
+    inverse: std::sync::Arc<dyn realfft::ComplexToReal<f32>>,
+}
+
+impl FftBuffers {
+    fn new(length: usize) -> Self {
+        let mut planner = realfft::RealFftPlanner::new();
+        let forward = planner.plan_fft_forward(length);
+        let inverse = planner.plan_fft_inverse(length);
+        let scratch_length = forward.get_scratch_len().max(inverse.get_scratch_len());
+        Self {
+            samples: forward.make_input_vec(),
+            spectrum: forward.make_output_vec(),
+            scratch: vec![realfft::num_complex::Complex32::default(); scratch_length],
+            forward,
+            inverse,
+        }
+    }
+}
+
+// Divides the spectrum by the number of summed timelines, flattens the skipped low
+// frequencies, and returns the maximum amplitude.
+fn normalize(spectrum: &mut [f32], count: usize, skip_low_frequency_samples: usize) -> f32 {
+    let mut maximum_amplitude: f32 = 0.0;
+    for amplitude in spectrum.iter_mut().skip(skip_low_frequency_samples) {
+        if count > 0 {
+            *amplitude /= count as f32;
+        }
+        maximum_amplitude = maximum_amplitude.max(*amplitude);
+    }
+    let zero_amplitude = spectrum[skip_low_frequency_samples];
+    for amplitude in spectrum.iter_mut().take(skip_low_frequency_samples) {
+        *amplitude = zero_amplitude;
+    }
+    maximum_amplitude
+}
+
+// Returns the frequency of the spectrum's harmonic comb in Hz and the normalized
+// autocorrelation at that frequency, which is used as a confidence score (both are 0 if no
+// peak is found).
+#[allow(clippy::too_many_arguments)]
+fn autocorrelation_peak(
+    fft: &mut FftBuffers,
+    spectrum: &[f32],
+    maximum_amplitude: f32,
+    parameters: &Parameters,
+    fft_frequency: f64,
+    interpolation: bool,
+    autocorrelation: &mut [f32],
+    detections: &mut AutocorrelationDetections,
+) -> (f32, f32) {
+    let fft_samples = fft.samples.len();
+    let variance_times_length = {
+        // the autocorrelation is computed on the full (symmetric) spectrum
+        let mut mean = 0.0;
+        for (sample_index, fft_sample) in fft.samples.iter_mut().enumerate() {
+            *fft_sample = spectrum[sample_index.min(fft_samples - sample_index)];
+            mean += *fft_sample;
+        }
+        mean /= fft_samples as f32;
+        let mut variance_times_length = 0.0;
+        for fft_sample in fft.samples.iter_mut() {
+            let delta = *fft_sample - mean;
+            variance_times_length += delta.powi(2);
+            *fft_sample = delta;
+        }
+        variance_times_length
+    };
+    if maximum_amplitude < parameters.amplitude_threshold || variance_times_length == 0.0 {
+        autocorrelation.fill(0.0);
+        autocorrelation[0] = 1.0;
+        return (0.0, 0.0);
+    }
+    fft.forward
+        .process_with_scratch(&mut fft.samples, &mut fft.spectrum, &mut fft.scratch)
+        .expect("the buffers have the planned lengths");
+    for sample in fft.spectrum.iter_mut() {
+        sample.re = sample.norm_sqr() / fft_samples as f32;
+        sample.im = 0.0;
+    }
+    fft.inverse
+        .process_with_scratch(&mut fft.spectrum, &mut fft.samples, &mut fft.scratch)
+        .expect("the buffers have the planned lengths");
+    for (lag, sample) in autocorrelation.iter_mut().zip(fft.samples.iter()) {
+        *lag = *sample / variance_times_length;
+    }
+    let mut on_peak = false;
+    let mut maximum: Option<(usize, f32)> = None;
+    for (sample, amplitude) in autocorrelation.iter().enumerate().take(fft_samples / 2) {
+        if on_peak {
+            match maximum {
+                Some((_, maximum_amplitude)) => {
+                    if *amplitude < parameters.autocorrelation_threshold {
+                        break;
+                    }
+                    if *amplitude > maximum_amplitude {
+                        maximum = Some((sample, *amplitude));
+                    }
+                    detections.peak_end =
+                        (sample as f32 / fft_samples as f32) * fft_frequency as f32;
+                }
+                None => {
+                    if *amplitude >= parameters.autocorrelation_threshold {
+                        detections.peak_start =
+                            (sample as f32 / fft_samples as f32) * fft_frequency as f32;
+                        maximum = Some((sample, *amplitude));
+                    }
+                }
+            }
+        } else if *amplitude < parameters.autocorrelation_threshold {
+            on_peak = true;
+        }
+    }
+    match maximum {
+        Some((sample, amplitude)) => {
+            let offset = if interpolation {
+                parabolic_offset(
+                    autocorrelation[sample - 1],
+                    amplitude,
+                    autocorrelation[sample + 1],
+                )
+            } else {
+                0.0
+            };
+            detections.peak_frequency =
+                ((sample as f32 + offset) / fft_samples as f32) * fft_frequency as f32;
+            detections.peak_amplitude = amplitude;
+            (detections.peak_frequency, amplitude)
+        }
+        None => (0.0, 0.0),
+    }
+}
+
+// Returns the frequency in [frequency - resolution, frequency + resolution] that maximizes the
+// phase coherence of the timelines' flips in [start_t, t], the sum over timelines and over the
+// first `harmonics` multiples of the squared norm of the flips' mean phasor. The interval is
+// sampled with REFINE_STEPS frequencies, then narrowed around the best one REFINE_ITERATIONS
+// times. Unlike the spectrum, the flips' timestamps are not resampled, hence the accuracy is
+// limited by the window's duration and the flips' jitter only.
+fn refine_frequency(
+    timelines: &Timelines,
+    indices: impl Iterator<Item = usize>,
+    frequency: f32,
+    resolution: f64,
+    harmonics: usize,
+    start_t: u64,
+    t: u64,
+) -> f32 {
+    // s, relative to t to preserve the precision of the phases
+    let flips: Vec<Vec<f64>> = indices
+        .map(|index| {
+            timelines
+                .timestamps(index)
+                .filter(|timestamp| *timestamp >= start_t && *timestamp <= t)
+                .map(|timestamp| (t - timestamp) as f64 / 1e6)
+                .collect()
+        })
+        .filter(|deltas: &Vec<f64>| deltas.len() > 1)
+        .collect();
+    if flips.is_empty() {
+        return frequency;
+    }
+    let coherence = |candidate: f64| -> f64 {
+        flips
+            .par_iter()
+            .map(|deltas| {
+                let mut sum = 0.0;
+                for harmonic in 1..=harmonics.max(1) {
+                    let angular_frequency = std::f64::consts::TAU * candidate * harmonic as f64; // rad/s
+                    let (mut cosines, mut sines) = (0.0, 0.0);
+                    for delta in deltas.iter() {
+                        let (sine, cosine) = (angular_frequency * delta).sin_cos();
+                        cosines += cosine;
+                        sines += sine;
+                    }
+                    sum +=
+                        (cosines * cosines + sines * sines) / (deltas.len() * deltas.len()) as f64;
+                }
+                sum
+            })
+            .sum()
+    };
+    let mut best = frequency as f64;
+    let mut span = resolution; // Hz, half width of the searched interval
+    for _ in 0..=REFINE_ITERATIONS {
+        let step = 2.0 * span / (REFINE_STEPS - 1) as f64;
+        let mut best_coherence = f64::NEG_INFINITY;
+        let centre = best;
+        for step_index in 0..REFINE_STEPS {
+            let candidate = centre - span + step * step_index as f64;
+            if candidate <= 0.0 {
+                continue;
+            }
+            let candidate_coherence = coherence(candidate);
+            if candidate_coherence > best_coherence {
+                best_coherence = candidate_coherence;
+                best = candidate;
+            }
+        }
+        span = step;
+    }
+    best as f32
+}
+
+// Returns the fundamental frequency in Hz that maximizes the product of the spectrum at its
+// first `harmonics` multiples, and the geometric mean of these amplitudes divided by the
+// maximum amplitude, which is used as a confidence score (both are 0 if no peak is found).
+fn harmonic_product_peak(
+    spectrum: &[f32],
+    maximum_amplitude: f32,
+    parameters: &Parameters,
+    fft_frequency: f64,
+    harmonics: usize,
+    skip_low_frequency_samples: usize,
+    interpolation: bool,
+) -> (f32, f32) {
+    if maximum_amplitude < parameters.amplitude_threshold || maximum_amplitude <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let fft_samples = (spectrum.len() - 1) * 2;
+    let last_sample = (fft_samples / 2 - 1) / harmonics;
+    // the product is computed in the log domain to avoid overflows
+    let log_product = |sample: usize| -> f32 {
+        (1..=harmonics)
+            .map(|harmonic| (spectrum[sample * harmonic] + f32::EPSILON).ln())
+            .sum()
+    };
+    let mut maximum: Option<(usize, f32)> = None;
+    for sample in skip_low_frequency_samples.max(1)..=last_sample {
+        let log_product = log_product(sample);
+        if maximum.is_none_or(|(_, maximum_log_product)| log_product > maximum_log_product) {
+            maximum = Some((sample, log_product));
+        }
+    }
+    match maximum {
+        Some((sample, maximum_log_product)) => {
+            let offset = if interpolation && (sample + 1) * harmonics < spectrum.len() {
+                parabolic_offset(
+                    log_product(sample - 1),
+                    maximum_log_product,
+                    log_product(sample + 1),
+                )
+            } else {
+                0.0
+            };
+            (
+                ((sample as f32 + offset) / fft_samples as f32) * fft_frequency as f32,
+                (maximum_log_product / harmonics as f32).exp() / maximum_amplitude,
+            )
+        }
+        None => (0.0, 0.0),
+    }
+}
+
+// Returns the position of the vertex of the parabola that goes through three consecutive
+// samples, relative to the center sample (0 if the center is not a local maximum).
+fn parabolic_offset(left: f32, center: f32, right: f32) -> f32 {
+    let curvature = left - 2.0 * center + right;
+    if curvature < 0.0 {
+        (0.5 * (left - right) / curvature).clamp(-0.5, 0.5)
+    } else {
+        0.0
+    }
+}
+
+// Each candidate blade count B defines a comb at the multiples of B times the shaft frequency.
+// The comb with the largest mean amplitude wins (ties go to the smallest B since the comb of a
+// multiple of B is a subset of the comb of B). The confidence is the contrast between the comb
+// and the other shaft harmonics (or the inter-harmonic floor if B is 1).
+fn blade_comb(
+    spectrum: &[f32],
+    shaft_frequency: f32,
+    fft_frequency: f64,
+    maximum_blade_count: usize,
+    skip_low_frequency_samples: usize,
+) -> Option<BladeCount> {
+    let fft_samples = (spectrum.len() - 1) * 2;
+    let sample = (shaft_frequency as f64 * fft_samples as f64) / fft_frequency;
+    if sample < 1.0 {
+        return None;
+    }
+    // the peak may fall between bins, hence the maximum over neighbours
+    let amplitude = |harmonic: f64| {
+        let center = (sample * harmonic).round() as usize;
+        (center > skip_low_frequency_samples && center + 1 < spectrum.len()).then(|| {
+            spectrum[center - 1]
+                .max(spectrum[center])
+                .max(spectrum[center + 1])
+        })
+    };
+    let amplitudes: Vec<f32> = (1..=maximum_blade_count * 2)
+        .map_while(|harmonic| amplitude(harmonic as f64))
+        .collect();
+    let floors: Vec<f32> = (1..amplitudes.len())
+        .filter_map(|harmonic| amplitude(harmonic as f64 + 0.5))
+        .collect();
+    let floor = floors.iter().sum::<f32>() / floors.len().max(1) as f32;
+    let mut best: Option<(f32, BladeCount)> = None;
+    for blade_count in 1..=maximum_blade_count.min(amplitudes.len()) {
+        let mut on_sum = 0.0;
+        let mut on_count = 0;
+        let mut off_sum = 0.0;
+        let mut off_count = 0;
+        for (index, amplitude) in amplitudes.iter().enumerate() {
+            if (index + 1) % blade_count == 0 {
+                on_sum += amplitude;
+                on_count += 1;
+            } else {
+                off_sum += amplitude;
+                off_count += 1;
+            }
+        }
+        let on = on_sum / on_count as f32;
+        let off = if off_count > 0 {
+            off_sum / off_count as f32
+        } else {
+            floor
+        };
+        let contrast = if on + off > 0.0 {
+            ((on - off) / (on + off)).max(0.0)
+        } else {
+            0.0
+        };
+        if best.is_none_or(|(best_on, _)| on > best_on) {
+            best = Some((
+                on,
+                BladeCount {
+                    blade_count,
+                    confidence: contrast,
+                },
+            ));
+        }
+    }
+    best.map(|(_, blade_count)| blade_count)
+}
+
+// A balanced rotor only emits at the harmonics of its blade-pass frequency, blades × the shaft
+// frequency, which is then the dominant frequency. A heavier or bent blade breaks the symmetry,
+// which adds components at the shaft frequency and its second harmonic, and modulates the
+// blade-pass harmonics (up to `harmonics`) with sidebands at ± the shaft frequency. A strong
+// imbalance makes the shaft frequency dominant instead, hence frequency is assumed to be the
+// shaft frequency if the spectrum peaks at its multiples of blades, and the blade-pass frequency
+// otherwise. The index is the energy of the imbalance components relative to their energy plus
+// that of the blade-pass harmonics.
+fn imbalance_index(
+    spectrum: &[f32],
+    frequency: f32,
+    blades: usize,
+    fft_frequency: f64,
+    harmonics: usize,
+    skip_low_frequency_samples: usize,
+) -> f32 {
+    let fft_samples = (spectrum.len() - 1) * 2;
+    let sample = (frequency as f64 * fft_samples as f64) / fft_frequency;
+    if sample < 1.0 {
+        return f32::NAN;
+    }
+    // the peak may fall between bins (see blade_comb)
+    let energy = |harmonic: f64| {
+        let center = (sample * harmonic).round() as usize;
+        if center > skip_low_frequency_samples && center + 1 < spectrum.len() {
+            (spectrum[center - 1]
+                .max(spectrum[center])
+                .max(spectrum[center + 1]) as f64)
+                .powi(2)
+        } else {
+            0.0
+        }
+    };
+    let (mut on, mut off) = (0.0, 0.0);
+    for harmonic in 1..=harmonics * blades {
+        if harmonic % blades == 0 {
+            on += energy(harmonic as f64);
+        } else {
+            off += energy(harmonic as f64);
+        }
+    }
+    // harmonics of the detected frequency per shaft harmonic
+    let ratio = if on / harmonics as f64 > off / (harmonics * (blades - 1)) as f64 {
+        1.0
+    } else {
+        1.0 / blades as f64
+    };
+    let mut balance = 0.0;
+    let mut imbalance = 0.0;
+    for harmonic in 1..=harmonics * blades + 1 {
+        let remainder = harmonic % blades;
+        if remainder == 0 {
+            balance += energy(harmonic as f64 * ratio);
+        } else if harmonic <= 2 || remainder == 1 || remainder == blades - 1 {
+            imbalance += energy(harmonic as f64 * ratio);
+        }
+    }
+    if imbalance + balance > 0.0 {
+        (imbalance / (imbalance + balance)) as f32
+    } else {
+        f32::NAN
+    }
+}
+
+// Returns the multiple of the shaft frequency (up to `harmonics`) with the largest amplitude,
+// which is the number of blades if the blade-pass frequency dominates the spectrum (0 if the
+// shaft frequency is 0).
+fn dominant_harmonic(
+    spectrum: &[f32],
+    shaft_frequency: f32,
+    fft_frequency: f64,
+    harmonics: usize,
+) -> usize {
+    let fft_samples = (spectrum.len() - 1) * 2;
+    let sample = ((shaft_frequency as f64 * fft_samples as f64) / fft_frequency).round() as usize;
+    if sample == 0 {
+        return 0;
+    }
+    let mut maximum: Option<(usize, f32)> = None;
+    for harmonic in 1..=harmonics {
+        if sample * harmonic >= fft_samples / 2 {
+            break;
+        }
+        let amplitude = spectrum[sample * harmonic];
+        if maximum.is_none_or(|(_, maximum_amplitude)| amplitude > maximum_amplitude) {
+            maximum = Some((harmonic, amplitude));
+        }
+    }
+    maximum.map_or(0, |(harmonic, _)| harmonic)
+}
@@ -0,0 +1,236 @@
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
+
+pub const DEFAULT_CHUNK_LENGTH: usize = 1 << 16; // events
+pub const EVENT_SIZE: usize = 8; // bytes
+pub const CD_EVENT_TYPE: u8 = 0x0c;
+const MAXIMUM_COORDINATE: u16 = 1 << 14;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("the header does not specify the sensor geometry")]
+    Geometry,
+
+    #[error("unsupported event size {0} (expected 8)")]
+    EventSize(u8),
+
+    #[error("chunk_length must be larger than zero")]
+    ChunkLength,
+
+    #[error("width and height must be at most 16384 (got {width} x {height})")]
+    EncoderGeometry { width: u16, height: u16 },
+
+    #[error("the event at x={x}, y={y} is out of bounds (width={width}, height={height})")]
+    OutOfBounds {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+
+    #[error("the payload {0} does not fit in 4 bits")]
+    Payload(u8),
+
+    #[error("events must be sorted by timestamp (got t={t} after t={previous_t})")]
+    Unsorted { previous_t: u64, t: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C, packed)]
+pub struct Event {
+    pub t: u64,
+    pub x: u16,
+    pub y: u16,
+    pub payload: u8,
+}
+
+// Decodes Prophesee DAT recordings (.dat files). Versions 0 and 1 use the ATIS layout
+// (9-bit x, 8-bit y), later versions use 14-bit coordinates and a 4-bit payload.
+pub struct Decoder {
+    file: std::io::BufReader<std::fs::File>,
+    version: u8,
+    event_type: u8,
+    width: u16,
+    height: u16,
+    chunk_length: usize,
+    buffer: Vec<u8>,
+    previous_t: u32,
+    overflows: u32,
+}
+
+impl Decoder {
+    pub fn new<P: AsRef<std::path::Path>>(path: P, chunk_length: usize) -> Result<Self, Error> {
+        if chunk_length == 0 {
+            return Err(Error::ChunkLength);
+        }
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut version = 0;
+        let mut width = None;
+        let mut height = None;
+        let mut line = String::new();
+        while file.fill_buf()?.first() == Some(&b'%') {
+            line.clear();
+            file.read_line(&mut line)?;
+            let mut words = line[1..].split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("Version"), Some(value)) => version = value.parse().unwrap_or(0),
+                (Some("Width"), Some(value)) => width = value.parse().ok(),
+                (Some("Height"), Some(value)) => height = value.parse().ok(),
+                (Some("geometry"), Some(value)) => {
+                    if let Some((geometry_width, geometry_height)) = value.split_once('x') {
+                        width = geometry_width.parse().ok();
+                        height = geometry_height.parse().ok();
+                    }
+                }
+                _ => {}
+            }
+        }
+        let (width, height) = match (width, height) {
+            (Some(width), Some(height)) => (width, height),
+            // older recordings come from 304 x 240 ATIS sensors and omit the geometry
+            _ if version < 2 => (304, 240),
+            _ => return Err(Error::Geometry),
+        };
+        let mut event_type_and_size = [0u8; 2];
+        file.read_exact(&mut event_type_and_size)?;
+        if event_type_and_size[1] as usize != EVENT_SIZE {
+            return Err(Error::EventSize(event_type_and_size[1]));
+        }
+        Ok(Self {
+            file,
+            version,
+            event_type: event_type_and_size[0],
+            width,
+            height,
+            chunk_length,
+            buffer: Vec::with_capacity(chunk_length * EVENT_SIZE),
+            previous_t: 0,
+            overflows: 0,
+        })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn event_type(&self) -> u8 {
+        self.event_type
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Vec<Event>>, Error> {
+        self.buffer.clear();
+        (&mut self.file)
+            .take((self.chunk_length * EVENT_SIZE) as u64)
+            .read_to_end(&mut self.buffer)?;
+        if self.buffer.len() < EVENT_SIZE {
+            return Ok(None);
+        }
+        let mut events = Vec::with_capacity(self.buffer.len() / EVENT_SIZE);
+        for bytes in self.buffer.chunks_exact(EVENT_SIZE) {
+            let t = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+            let data = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+            let t = crate::aedat::unwrap_timestamp(&mut self.previous_t, &mut self.overflows, t);
+            events.push(if self.version < 2 {
+                Event {
+                    t,
+                    x: (data & 0b111111111) as u16,
+                    y: ((data >> 9) & 0b11111111) as u16,
+                    payload: ((data >> 17) & 0b1111) as u8,
+                }
+            } else {
+                Event {
+                    t,
+                    x: (data & 0b11111111111111) as u16,
+                    y: ((data >> 14) & 0b11111111111111) as u16,
+                    payload: (data >> 28) as u8,
+                }
+            });
+        }
+        Ok(Some(events))
+    }
+}
+
+impl Iterator for Decoder {
+    type Item = Result<Vec<Event>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().transpose()
+    }
+}
+
+// Encodes version 2 DAT recordings (.dat files) with 14-bit coordinates. Events must be written
+// in timestamp order. Timestamps are stored modulo 2^32 µs and the decoder only counts backwards
+// jumps larger than 2^31 µs as wrap-arounds, hence consecutive events must be less than 35 minutes
+// apart.
+pub struct Encoder {
+    file: std::io::BufWriter<std::fs::File>,
+    width: u16,
+    height: u16,
+    previous_t: u64,
+}
+
+impl Encoder {
+    pub fn new<P: AsRef<std::path::Path>>(
+        path: P,
+        event_type: u8,
+        width: u16,
+        height: u16,
+    ) -> Result<Self, Error> {
+        if width > MAXIMUM_COORDINATE || height > MAXIMUM_COORDINATE {
+            return Err(Error::EncoderGeometry { width, height });
+        }
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        write!(file, "% Version 2\n% geometry {width}x{height}\n")?;
+        file.write_all(&[event_type, EVENT_SIZE as u8])?;
+        Ok(Self {
+            file,
+            width,
+            height,
+            previous_t: 0,
+        })
+    }
+
+    pub fn write(&mut self, event: Event) -> Result<(), Error> {
+        if event.t < self.previous_t {
+            return Err(Error::Unsorted {
+                previous_t: self.previous_t,
+                t: event.t,
+            });
+        }
+        if event.x >= self.width || event.y >= self.height {
+            return Err(Error::OutOfBounds {
+                x: event.x,
+                y: event.y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        if event.payload > 0b1111 {
+            return Err(Error::Payload(event.payload));
+        }
+        self.previous_t = event.t;
+        self.file.write_all(&(event.t as u32).to_le_bytes())?;
+        self.file.write_all(
+            &((event.x as u32) | ((event.y as u32) << 14) | ((event.payload as u32) << 28))
+                .to_le_bytes(),
+        )?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.file.flush()?;
+        Ok(())
+    }
+}
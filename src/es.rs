@@ -0,0 +1,518 @@
+use std::io::Read;
+use std::io::Write;
+
+pub const MAGIC_NUMBER: &[u8] = b"Event Stream";
+pub const DEFAULT_CHUNK_LENGTH: usize = 1 << 16; // bytes
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("the file does not start with the Event Stream magic number")]
+    MagicNumber,
+
+    #[error("unsupported version {0}.{1}.{2} (expected 2.x.x)")]
+    Version(u8, u8, u8),
+
+    #[error("unknown event type {0}")]
+    EventType(u8),
+
+    #[error("the event at x={x}, y={y} is out of bounds (width={width}, height={height})")]
+    OutOfBounds {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+
+    #[error("chunk_length must be larger than zero")]
+    ChunkLength,
+
+    #[error("events must be sorted by timestamp (got t={t} after t={previous_t})")]
+    Unsorted { previous_t: u64, t: u64 },
+
+    #[error("generic streams cannot be written")]
+    GenericEncoder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventType {
+    Generic,
+    Dvs,
+    Atis,
+    Color,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenericEvent {
+    pub t: u64,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C, packed)]
+pub struct AtisEvent {
+    pub t: u64,
+    pub x: u16,
+    pub y: u16,
+    pub exposure: bool,
+    pub polarity: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C, packed)]
+pub struct ColorEvent {
+    pub t: u64,
+    pub x: u16,
+    pub y: u16,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+pub enum Chunk {
+    Generic(Vec<GenericEvent>),
+    Dvs(Vec<crate::core::Event>),
+    Atis(Vec<AtisEvent>),
+    Color(Vec<ColorEvent>),
+}
+
+#[derive(Clone, Copy)]
+enum State {
+    Idle,
+    Byte(u8),
+    GenericSize(u32),
+}
+
+// Decodes Event Stream 2 recordings (.es files), see
+// https://github.com/neuromorphic-paris/event_stream.
+pub struct Decoder {
+    file: std::io::BufReader<std::fs::File>,
+    version: [u8; 3],
+    event_type: EventType,
+    width: u16,
+    height: u16,
+    chunk_length: usize,
+    buffer: Vec<u8>,
+    state: State,
+    t: u64,
+    x: u16,
+    y: u16,
+    flags: u8,
+    color: [u8; 2],
+    bytes: Vec<u8>,
+    bytes_length: usize,
+}
+
+impl Decoder {
+    pub fn new<P: AsRef<std::path::Path>>(path: P, chunk_length: usize) -> Result<Self, Error> {
+        if chunk_length == 0 {
+            return Err(Error::ChunkLength);
+        }
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut header = [0u8; MAGIC_NUMBER.len() + 4];
+        file.read_exact(&mut header)
+            .map_err(|_| Error::MagicNumber)?;
+        if &header[0..MAGIC_NUMBER.len()] != MAGIC_NUMBER {
+            return Err(Error::MagicNumber);
+        }
+        let version = [
+            header[MAGIC_NUMBER.len()],
+            header[MAGIC_NUMBER.len() + 1],
+            header[MAGIC_NUMBER.len() + 2],
+        ];
+        if version[0] != 2 {
+            return Err(Error::Version(version[0], version[1], version[2]));
+        }
+        let event_type = match header[MAGIC_NUMBER.len() + 3] {
+            0 => EventType::Generic,
+            1 => EventType::Dvs,
+            2 => EventType::Atis,
+            4 => EventType::Color,
+            event_type => return Err(Error::EventType(event_type)),
+        };
+        let (width, height) = if event_type == EventType::Generic {
+            (0, 0)
+        } else {
+            let mut resolution = [0u8; 4];
+            file.read_exact(&mut resolution)?;
+            (
+                u16::from_le_bytes([resolution[0], resolution[1]]),
+                u16::from_le_bytes([resolution[2], resolution[3]]),
+            )
+        };
+        Ok(Self {
+            file,
+            version,
+            event_type,
+            width,
+            height,
+            chunk_length,
+            buffer: Vec::with_capacity(chunk_length),
+            state: State::Idle,
+            t: 0,
+            x: 0,
+            y: 0,
+            flags: 0,
+            color: [0, 0],
+            bytes: Vec::new(),
+            bytes_length: 0,
+        })
+    }
+
+    pub fn version(&self) -> [u8; 3] {
+        self.version
+    }
+
+    pub fn event_type(&self) -> EventType {
+        self.event_type
+    }
+
+    // Generic streams have no resolution (width and height are 0).
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Chunk>, Error> {
+        self.buffer.clear();
+        (&mut self.file)
+            .take(self.chunk_length as u64)
+            .read_to_end(&mut self.buffer)?;
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let buffer = std::mem::take(&mut self.buffer);
+        let chunk = match self.event_type {
+            EventType::Generic => {
+                let mut events = Vec::new();
+                for byte in buffer.iter() {
+                    self.handle_generic_byte(*byte, &mut events);
+                }
+                Chunk::Generic(events)
+            }
+            EventType::Dvs => {
+                let mut events = Vec::new();
+                for byte in buffer.iter() {
+                    self.handle_dvs_byte(*byte, &mut events)?;
+                }
+                Chunk::Dvs(events)
+            }
+            EventType::Atis => {
+                let mut events = Vec::new();
+                for byte in buffer.iter() {
+                    self.handle_atis_byte(*byte, &mut events)?;
+                }
+                Chunk::Atis(events)
+            }
+            EventType::Color => {
+                let mut events = Vec::new();
+                for byte in buffer.iter() {
+                    self.handle_color_byte(*byte, &mut events)?;
+                }
+                Chunk::Color(events)
+            }
+        };
+        self.buffer = buffer;
+        Ok(Some(chunk))
+    }
+
+    fn handle_generic_byte(&mut self, byte: u8, events: &mut Vec<GenericEvent>) {
+        match self.state {
+            State::Idle => {
+                if byte == 0b11111111 {
+                    self.t += 0b11111110;
+                } else if byte != 0b11111110 {
+                    self.t += byte as u64;
+                    self.state = State::Byte(0);
+                }
+            }
+            State::Byte(0) => {
+                self.bytes_length = (byte >> 1) as usize;
+                if byte & 1 == 0 {
+                    self.start_generic_bytes(events);
+                } else {
+                    self.state = State::GenericSize(7);
+                }
+            }
+            State::GenericSize(shift) => {
+                self.bytes_length |= ((byte >> 1) as usize) << shift;
+                if byte & 1 == 0 {
+                    self.start_generic_bytes(events);
+                } else {
+                    self.state = State::GenericSize(shift + 7);
+                }
+            }
+            State::Byte(_) => {
+                self.bytes.push(byte);
+                if self.bytes.len() == self.bytes_length {
+                    self.push_generic_event(events);
+                }
+            }
+        }
+    }
+
+    fn start_generic_bytes(&mut self, events: &mut Vec<GenericEvent>) {
+        self.bytes.clear();
+        if self.bytes_length == 0 {
+            self.push_generic_event(events);
+        } else {
+            self.state = State::Byte(1);
+        }
+    }
+
+    fn push_generic_event(&mut self, events: &mut Vec<GenericEvent>) {
+        events.push(GenericEvent {
+            t: self.t,
+            bytes: std::mem::take(&mut self.bytes),
+        });
+        self.state = State::Idle;
+    }
+
+    // Handles the four little-endian coordinate bytes shared by DVS, ATIS, and color
+    // events, and returns true once y is complete.
+    fn handle_coordinates_byte(&mut self, index: u8, byte: u8) -> Result<bool, Error> {
+        match index {
+            0 => self.x = byte as u16,
+            1 => self.x |= (byte as u16) << 8,
+            2 => self.y = byte as u16,
+            _ => {
+                self.y |= (byte as u16) << 8;
+                if self.x >= self.width || self.y >= self.height {
+                    return Err(Error::OutOfBounds {
+                        x: self.x,
+                        y: self.y,
+                        width: self.width,
+                        height: self.height,
+                    });
+                }
+                return Ok(true);
+            }
+        }
+        self.state = State::Byte(index + 1);
+        Ok(false)
+    }
+
+    fn handle_dvs_byte(
+        &mut self,
+        byte: u8,
+        events: &mut Vec<crate::core::Event>,
+    ) -> Result<(), Error> {
+        match self.state {
+            State::Idle => {
+                if byte == 0b11111111 {
+                    self.t += 0b1111111;
+                } else if byte != 0b11111110 {
+                    self.t += (byte >> 1) as u64;
+                    self.flags = byte & 1;
+                    self.state = State::Byte(0);
+                }
+            }
+            State::Byte(index) => {
+                if self.handle_coordinates_byte(index, byte)? {
+                    events.push(crate::core::Event {
+                        t: self.t,
+                        x: self.x,
+                        y: self.y,
+                        polarity: if self.flags == 1 {
+                            neuromorphic_types::DvsPolarity::On
+                        } else {
+                            neuromorphic_types::DvsPolarity::Off
+                        },
+                    });
+                    self.state = State::Idle;
+                }
+            }
+            State::GenericSize(_) => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn handle_atis_byte(&mut self, byte: u8, events: &mut Vec<AtisEvent>) -> Result<(), Error> {
+        match self.state {
+            State::Idle => {
+                if byte & 0b11111100 == 0b11111100 {
+                    self.t += 0b111111 * (byte & 0b11) as u64;
+                } else {
+                    self.t += (byte >> 2) as u64;
+                    self.flags = byte & 0b11;
+                    self.state = State::Byte(0);
+                }
+            }
+            State::Byte(index) => {
+                if self.handle_coordinates_byte(index, byte)? {
+                    events.push(AtisEvent {
+                        t: self.t,
+                        x: self.x,
+                        y: self.y,
+                        exposure: self.flags & 1 == 1,
+                        polarity: self.flags & 0b10 == 0b10,
+                    });
+                    self.state = State::Idle;
+                }
+            }
+            State::GenericSize(_) => unreachable!(),
+        }
+        Ok(())
+    }
+
+    fn handle_color_byte(&mut self, byte: u8, events: &mut Vec<ColorEvent>) -> Result<(), Error> {
+        match self.state {
+            State::Idle => {
+                if byte == 0b11111111 {
+                    self.t += 0b11111110;
+                } else if byte != 0b11111110 {
+                    self.t += byte as u64;
+                    self.state = State::Byte(0);
+                }
+            }
+            State::Byte(index) if index < 4 => {
+                self.handle_coordinates_byte(index, byte)?;
+                if index == 3 {
+                    self.state = State::Byte(4);
+                }
+            }
+            State::Byte(4) => {
+                self.color[0] = byte;
+                self.state = State::Byte(5);
+            }
+            State::Byte(5) => {
+                self.color[1] = byte;
+                self.state = State::Byte(6);
+            }
+            State::Byte(_) => {
+                events.push(ColorEvent {
+                    t: self.t,
+                    x: self.x,
+                    y: self.y,
+                    r: self.color[0],
+                    g: self.color[1],
+                    b: byte,
+                });
+                self.state = State::Idle;
+            }
+            State::GenericSize(_) => unreachable!(),
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Decoder {
+    type Item = Result<Chunk, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().transpose()
+    }
+}
+
+// Encodes Event Stream 2 recordings (.es files). Events must be written in timestamp order with
+// the method that matches the stream's event type.
+pub struct Encoder {
+    file: std::io::BufWriter<std::fs::File>,
+    event_type: EventType,
+    width: u16,
+    height: u16,
+    t: u64,
+}
+
+impl Encoder {
+    pub fn new<P: AsRef<std::path::Path>>(
+        path: P,
+        event_type: EventType,
+        width: u16,
+        height: u16,
+    ) -> Result<Self, Error> {
+        let event_type_byte = match event_type {
+            EventType::Generic => return Err(Error::GenericEncoder),
+            EventType::Dvs => 1,
+            EventType::Atis => 2,
+            EventType::Color => 4,
+        };
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        file.write_all(MAGIC_NUMBER)?;
+        file.write_all(&[2, 0, 0, event_type_byte])?;
+        file.write_all(&width.to_le_bytes())?;
+        file.write_all(&height.to_le_bytes())?;
+        Ok(Self {
+            file,
+            event_type,
+            width,
+            height,
+            t: 0,
+        })
+    }
+
+    pub fn write_dvs(&mut self, event: crate::core::Event) -> Result<(), Error> {
+        debug_assert_eq!(self.event_type, EventType::Dvs);
+        let mut delta_t = self.delta_t(event.t, event.x, event.y)?;
+        while delta_t >= 0b1111111 {
+            self.file.write_all(&[0b11111111])?;
+            delta_t -= 0b1111111;
+        }
+        let on = matches!(event.polarity, neuromorphic_types::DvsPolarity::On);
+        self.file.write_all(&[((delta_t as u8) << 1) | on as u8])?;
+        self.write_coordinates(event.x, event.y)
+    }
+
+    pub fn write_atis(&mut self, event: AtisEvent) -> Result<(), Error> {
+        debug_assert_eq!(self.event_type, EventType::Atis);
+        let mut delta_t = self.delta_t(event.t, event.x, event.y)?;
+        while delta_t >= 0b111111 {
+            let overflows = (delta_t / 0b111111).min(0b11);
+            self.file.write_all(&[0b11111100 | overflows as u8])?;
+            delta_t -= 0b111111 * overflows;
+        }
+        self.file.write_all(&[((delta_t as u8) << 2)
+            | ((event.polarity as u8) << 1)
+            | event.exposure as u8])?;
+        self.write_coordinates(event.x, event.y)
+    }
+
+    pub fn write_color(&mut self, event: ColorEvent) -> Result<(), Error> {
+        debug_assert_eq!(self.event_type, EventType::Color);
+        let mut delta_t = self.delta_t(event.t, event.x, event.y)?;
+        while delta_t >= 0b11111110 {
+            self.file.write_all(&[0b11111111])?;
+            delta_t -= 0b11111110;
+        }
+        self.file.write_all(&[delta_t as u8])?;
+        self.write_coordinates(event.x, event.y)?;
+        self.file.write_all(&[event.r, event.g, event.b])?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.file.flush()?;
+        Ok(())
+    }
+
+    fn delta_t(&mut self, t: u64, x: u16, y: u16) -> Result<u64, Error> {
+        if t < self.t {
+            return Err(Error::Unsorted {
+                previous_t: self.t,
+                t,
+            });
+        }
+        if x >= self.width || y >= self.height {
+            return Err(Error::OutOfBounds {
+                x,
+                y,
+                width: self.width,
+                height: self.height,
+            });
+        }
+        let delta_t = t - self.t;
+        self.t = t;
+        Ok(delta_t)
+    }
+
+    fn write_coordinates(&mut self, x: u16, y: u16) -> Result<(), Error> {
+        self.file.write_all(&x.to_le_bytes())?;
+        self.file.write_all(&y.to_le_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+use std::io::BufRead;
+use std::io::Read;
+
+pub const DEFAULT_CHUNK_LENGTH: usize = 1 << 16; // words
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("the header does not specify the EVT 2.0 or EVT 3.0 format")]
+    Format,
+
+    #[error("the header does not specify the sensor geometry")]
+    Geometry,
+
+    #[error("chunk_length must be larger than zero")]
+    ChunkLength,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C, packed)]
+pub struct Trigger {
+    pub t: u64,
+    pub source: u8,
+    pub rising: bool,
+}
+
+pub struct Chunk {
+    pub events: Vec<crate::core::Event>,
+    pub triggers: Vec<Trigger>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Evt2,
+    Evt3,
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Encoding::Evt2 => write!(formatter, "EVT2"),
+            Encoding::Evt3 => write!(formatter, "EVT3"),
+        }
+    }
+}
+
+// Decodes Prophesee EVT 3.0 recordings (.raw files), see
+// https://docs.prophesee.ai/stable/data/encoding_formats/evt3.html, and EVT 2.0 recordings
+// (older Gen3 cameras), see https://docs.prophesee.ai/stable/data/encoding_formats/evt2.html.
+// The encoding is read from the header.
+pub struct Decoder {
+    file: std::io::BufReader<std::fs::File>,
+    encoding: Encoding,
+    width: u16,
+    height: u16,
+    chunk_length: usize,
+    buffer: Vec<u8>,
+    previous_high_t: u32, // EVT2 only
+    previous_msb_t: u32,
+    previous_lsb_t: u32,
+    overflows: u32,
+    t: u64,
+    x: u16,
+    y: u16,
+    polarity: neuromorphic_types::DvsPolarity,
+}
+
+impl Decoder {
+    pub fn new<P: AsRef<std::path::Path>>(path: P, chunk_length: usize) -> Result<Self, Error> {
+        if chunk_length == 0 {
+            return Err(Error::ChunkLength);
+        }
+        let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut encoding = None;
+        let mut geometry = None;
+        let mut sensor_generation = None;
+        let mut line = String::new();
+        while file.fill_buf()?.first() == Some(&b'%') {
+            line.clear();
+            file.read_line(&mut line)?;
+            let line = line.trim_end();
+            if line == "% end" {
+                break;
+            }
+            if let Some(evt) = line.strip_prefix("% evt ") {
+                match evt.trim() {
+                    "2.0" => encoding = Some(Encoding::Evt2),
+                    "3.0" => encoding = Some(Encoding::Evt3),
+                    _ => {}
+                }
+            } else if let Some(format) = line.strip_prefix("% format ") {
+                let mut parts = format.split(';');
+                match parts.next() {
+                    Some("EVT2") => encoding = Some(Encoding::Evt2),
+                    Some("EVT3") => encoding = Some(Encoding::Evt3),
+                    _ => {}
+                }
+                let mut width = None;
+                let mut height = None;
+                for part in parts {
+                    match part.split_once('=') {
+                        Some(("width", value)) => width = value.parse::<u16>().ok(),
+                        Some(("height", value)) => height = value.parse::<u16>().ok(),
+                        _ => {}
+                    }
+                }
+                if let (Some(width), Some(height)) = (width, height) {
+                    geometry = Some((width, height));
+                }
+            } else if let Some(value) = line.strip_prefix("% geometry ") {
+                if let Some((width, height)) = value.trim().split_once('x') {
+                    if let (Ok(width), Ok(height)) = (width.parse::<u16>(), height.parse::<u16>()) {
+                        geometry = Some((width, height));
+                    }
+                }
+            } else if let Some(value) = line.strip_prefix("% sensor_generation ") {
+                sensor_generation = Some(value.trim().to_owned());
+            }
+        }
+        let encoding = encoding.ok_or(Error::Format)?;
+        // old Gen3 headers only specify the sensor generation
+        let (width, height) = geometry
+            .or(match sensor_generation.as_deref() {
+                Some("3.0") => Some((480, 360)),
+                Some("3.1") => Some((640, 480)),
+                _ => None,
+            })
+            .ok_or(Error::Geometry)?;
+        Ok(Self {
+            file,
+            encoding,
+            width,
+            height,
+            chunk_length,
+            buffer: Vec::with_capacity(chunk_length * word_size(encoding)),
+            previous_high_t: 0,
+            previous_msb_t: 0,
+            previous_lsb_t: 0,
+            overflows: 0,
+            t: 0,
+            x: 0,
+            y: 0,
+            polarity: neuromorphic_types::DvsPolarity::Off,
+        })
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Chunk>, Error> {
+        // an incomplete trailing word from the previous chunk is kept at the start of the buffer
+        let word_size = word_size(self.encoding);
+        let remainder = self.buffer.len() % word_size;
+        self.buffer.drain(0..self.buffer.len() - remainder);
+        (&mut self.file)
+            .take((self.chunk_length * word_size - remainder) as u64)
+            .read_to_end(&mut self.buffer)?;
+        if self.buffer.len() < word_size {
+            return Ok(None);
+        }
+        let mut chunk = Chunk {
+            events: Vec::new(),
+            triggers: Vec::new(),
+        };
+        match self.encoding {
+            Encoding::Evt2 => self.decode_evt2(&mut chunk),
+            Encoding::Evt3 => self.decode_evt3(&mut chunk),
+        }
+        Ok(Some(chunk))
+    }
+
+    fn decode_evt2(&mut self, chunk: &mut Chunk) {
+        for index in 0..self.buffer.len() / 4 {
+            let word = u32::from_le_bytes([
+                self.buffer[index * 4],
+                self.buffer[index * 4 + 1],
+                self.buffer[index * 4 + 2],
+                self.buffer[index * 4 + 3],
+            ]);
+            match word >> 28 {
+                0b0000 | 0b0001 => {
+                    self.update_evt2_t(word);
+                    chunk.events.push(crate::core::Event {
+                        t: self.t,
+                        x: ((word >> 11) & 0b11111111111) as u16,
+                        y: (word & 0b11111111111) as u16,
+                        polarity: if word >> 28 == 1 {
+                            neuromorphic_types::DvsPolarity::On
+                        } else {
+                            neuromorphic_types::DvsPolarity::Off
+                        },
+                    });
+                }
+                0b1000 => {
+                    let high_t = word & 0xfffffff;
+                    if high_t < self.previous_high_t {
+                        // the 34-bit timestamp wrapped around (every 4.8 hours)
+                        self.overflows += 1;
+                    }
+                    self.previous_high_t = high_t;
+                }
+                0b1010 => {
+                    self.update_evt2_t(word);
+                    chunk.triggers.push(Trigger {
+                        t: self.t,
+                        source: ((word >> 8) & 0b11111) as u8,
+                        rising: (word & 1) == 1,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // The 6 low bits of EVT2 timestamps are stored in each event.
+    fn update_evt2_t(&mut self, word: u32) {
+        let t = ((self.overflows as u64) << 34)
+            | ((self.previous_high_t as u64) << 6)
+            | ((word >> 22) & 0b111111) as u64;
+        if t >= self.t {
+            self.t = t;
+        }
+    }
+
+    fn decode_evt3(&mut self, chunk: &mut Chunk) {
+        for index in 0..self.buffer.len() / 2 {
+            let word = u16::from_le_bytes([self.buffer[index * 2], self.buffer[index * 2 + 1]]);
+            match word >> 12 {
+                0b0000 => {
+                    self.y = word & 0b11111111111;
+                }
+                0b0010 => {
+                    self.x = word & 0b11111111111;
+                    self.polarity = polarity(word);
+                    chunk.events.push(crate::core::Event {
+                        t: self.t,
+                        x: self.x,
+                        y: self.y,
+                        polarity: self.polarity,
+                    });
+                }
+                0b0011 => {
+                    self.x = word & 0b11111111111;
+                    self.polarity = polarity(word);
+                }
+                0b0100 => {
+                    self.push_vector(word & 0b111111111111, 12, &mut chunk.events);
+                }
+                0b0101 => {
+                    self.push_vector(word & 0b11111111, 8, &mut chunk.events);
+                }
+                0b0110 => {
+                    let lsb_t = (word & 0b111111111111) as u32;
+                    if lsb_t != self.previous_lsb_t {
+                        self.previous_lsb_t = lsb_t;
+                        self.update_t();
+                    }
+                }
+                0b1000 => {
+                    let msb_t = (word & 0b111111111111) as u32;
+                    if msb_t != self.previous_msb_t {
+                        if msb_t < self.previous_msb_t {
+                            // the 24-bit timestamp wrapped around (every 16.8 s)
+                            self.overflows += 1;
+                        }
+                        self.previous_msb_t = msb_t;
+                        self.previous_lsb_t = 0;
+                        self.update_t();
+                    }
+                }
+                0b1010 => {
+                    chunk.triggers.push(Trigger {
+                        t: self.t,
+                        source: ((word >> 8) & 0b1111) as u8,
+                        rising: (word & 1) == 1,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn update_t(&mut self) {
+        let t = ((self.overflows as u64) << 24)
+            | ((self.previous_msb_t as u64) << 12)
+            | (self.previous_lsb_t as u64);
+        // timestamps are kept monotonic despite out-of-order low bits
+        if t >= self.t {
+            self.t = t;
+        }
+    }
+
+    fn push_vector(&mut self, mut mask: u16, length: u16, events: &mut Vec<crate::core::Event>) {
+        let x = self.x;
+        while mask != 0 {
+            let offset = mask.trailing_zeros() as u16;
+            events.push(crate::core::Event {
+                t: self.t,
+                x: x + offset,
+                y: self.y,
+                polarity: self.polarity,
+            });
+            mask &= mask - 1;
+        }
+        self.x = x + length;
+    }
+}
+
+impl Iterator for Decoder {
+    type Item = Result<Chunk, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk().transpose()
+    }
+}
+
+fn polarity(word: u16) -> neuromorphic_types::DvsPolarity {
+    if (word >> 11) & 1 == 1 {
+        neuromorphic_types::DvsPolarity::On
+    } else {
+        neuromorphic_types::DvsPolarity::Off
+    }
+}
+
+fn word_size(encoding: Encoding) -> usize {
+    match encoding {
+        Encoding::Evt2 => 4,
+        Encoding::Evt3 => 2,
+    }
+}
@@ -0,0 +1,398 @@
+const ROW_GROUP_LENGTH: usize = 1 << 16;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    #[error(transparent)]
+    Arrow(#[from] arrow_schema::ArrowError),
+
+    #[error("unknown format \"{0}\" (must be \"csv\" or \"parquet\")")]
+    Format(String),
+
+    #[cfg(feature = "hdf5")]
+    #[error(transparent)]
+    Hdf5(#[from] hdf5::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Parquet,
+}
+
+impl std::str::FromStr for Format {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "csv" => Ok(Format::Csv),
+            "parquet" => Ok(Format::Parquet),
+            _ => Err(Error::Format(string.to_owned())),
+        }
+    }
+}
+
+// Samples accumulated over several process calls, and optionally their spectra (spectrogram).
+#[derive(Debug, Clone)]
+pub struct Results {
+    ts: Vec<u64>, // µs
+    rpms: Vec<f32>,
+    confidences: Vec<f32>,
+    tip_speeds: Vec<f32>, // m/s
+    spectrum_length: Option<usize>,
+    spectrogram: Vec<f32>,
+    region_names: Vec<String>,
+    region_rows: Vec<RegionRow>,
+}
+
+// Region estimate of the sample ts[sample_index].
+#[derive(Debug, Clone, Copy)]
+struct RegionRow {
+    sample_index: usize,
+    name_index: usize, // in region_names
+    rpm: f32,
+    confidence: f32,
+}
+
+impl Results {
+    // The spectrogram is recorded if spectrum_length is not None.
+    pub fn new(spectrum_length: Option<usize>) -> Self {
+        Self {
+            ts: Vec::new(),
+            rpms: Vec::new(),
+            confidences: Vec::new(),
+            tip_speeds: Vec::new(),
+            spectrum_length,
+            spectrogram: Vec::new(),
+            region_names: Vec::new(),
+            region_rows: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ts.is_empty()
+    }
+
+    pub fn records_spectrogram(&self) -> bool {
+        self.spectrum_length.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.ts.clear();
+        self.rpms.clear();
+        self.confidences.clear();
+        self.tip_speeds.clear();
+        self.spectrogram.clear();
+        self.region_names.clear();
+        self.region_rows.clear();
+    }
+
+    // spectra must contain spectrum_length values per sample (see RpmEstimator::spectra), it is
+    // ignored if the spectrogram is not recorded.
+    pub fn push(&mut self, samples: &[crate::core::Sample], spectra: &[f32]) {
+        for sample in samples {
+            self.ts.push(sample.t);
+            self.rpms.push(sample.rpm);
+            self.confidences.push(sample.confidence);
+            self.tip_speeds.push(sample.tip_speed);
+        }
+        if let Some(spectrum_length) = self.spectrum_length {
+            debug_assert_eq!(spectra.len(), samples.len() * spectrum_length);
+            self.spectrogram.extend_from_slice(spectra);
+        }
+    }
+
+    // Must be called after push, with the same samples' region estimates (see
+    // RpmEstimator::regions_samples). Region estimates are only part of the Arrow record batch.
+    pub fn push_regions(
+        &mut self,
+        regions: &[crate::core::Region],
+        regions_samples: &[Vec<crate::core::RegionSample>],
+    ) {
+        let first_sample_index = self.len() - regions_samples.len();
+        for (index, region_samples) in regions_samples.iter().enumerate() {
+            for (region, region_sample) in regions.iter().zip(region_samples.iter()) {
+                let name_index = match self
+                    .region_names
+                    .iter()
+                    .position(|name| *name == region.name)
+                {
+                    Some(name_index) => name_index,
+                    None => {
+                        self.region_names.push(region.name.clone());
+                        self.region_names.len() - 1
+                    }
+                };
+                self.region_rows.push(RegionRow {
+                    sample_index: first_sample_index + index,
+                    name_index,
+                    rpm: region_sample.rpm,
+                    confidence: region_sample.confidence,
+                });
+            }
+        }
+    }
+
+    // One row per sample (region is null) followed by one row per region estimate of the same
+    // sample (region is the region's name, tip_speed is NaN). t is in µs.
+    pub fn to_record_batch(&self) -> Result<arrow_array::RecordBatch, Error> {
+        let length = self.len() + self.region_rows.len();
+        let mut ts = Vec::with_capacity(length);
+        let mut rpms = Vec::with_capacity(length);
+        let mut confidences = Vec::with_capacity(length);
+        let mut tip_speeds = Vec::with_capacity(length);
+        let mut regions = Vec::with_capacity(length);
+        let mut region_rows = self.region_rows.iter().peekable();
+        for index in 0..self.len() {
+            ts.push(self.ts[index]);
+            rpms.push(self.rpms[index]);
+            confidences.push(self.confidences[index]);
+            tip_speeds.push(self.tip_speeds[index]);
+            regions.push(None);
+            while let Some(row) = region_rows.next_if(|row| row.sample_index == index) {
+                ts.push(self.ts[index]);
+                rpms.push(row.rpm);
+                confidences.push(row.confidence);
+                tip_speeds.push(f32::NAN);
+                regions.push(Some(self.region_names[row.name_index].as_str()));
+            }
+        }
+        Ok(arrow_array::RecordBatch::try_new(
+            std::sync::Arc::new(arrow_schema::Schema::new(vec![
+                arrow_schema::Field::new("t", arrow_schema::DataType::UInt64, false),
+                arrow_schema::Field::new("rpm", arrow_schema::DataType::Float32, false),
+                arrow_schema::Field::new("confidence", arrow_schema::DataType::Float32, false),
+                arrow_schema::Field::new("tip_speed", arrow_schema::DataType::Float32, false),
+                arrow_schema::Field::new("region", arrow_schema::DataType::Utf8, true),
+            ])),
+            vec![
+                std::sync::Arc::new(arrow_array::UInt64Array::from(ts)),
+                std::sync::Arc::new(arrow_array::Float32Array::from(rpms)),
+                std::sync::Arc::new(arrow_array::Float32Array::from(confidences)),
+                std::sync::Arc::new(arrow_array::Float32Array::from(tip_speeds)),
+                std::sync::Arc::new(arrow_array::StringArray::from(regions)),
+            ],
+        )?)
+    }
+
+    // The spectrogram is written as one column per frequency bin (amplitude_0, amplitude_1...)
+    // in CSV files and as a list column (spectrum) in Parquet files.
+    pub fn write<P: AsRef<std::path::Path>>(&self, path: P, format: Format) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        match format {
+            Format::Csv => self.write_csv(std::io::BufWriter::new(file)),
+            Format::Parquet => self.write_parquet(file),
+        }
+    }
+
+    fn write_csv<W: std::io::Write>(&self, mut writer: W) -> Result<(), Error> {
+        write!(writer, "t,rpm,confidence,tip_speed")?;
+        let spectrum_length = self.spectrum_length.unwrap_or(0);
+        for index in 0..spectrum_length {
+            write!(writer, ",amplitude_{index}")?;
+        }
+        writeln!(writer)?;
+        for index in 0..self.len() {
+            write!(
+                writer,
+                "{},{},{},{}",
+                self.ts[index], self.rpms[index], self.confidences[index], self.tip_speeds[index]
+            )?;
+            if spectrum_length > 0 {
+                for amplitude in
+                    self.spectrogram[index * spectrum_length..(index + 1) * spectrum_length].iter()
+                {
+                    write!(writer, ",{amplitude}")?;
+                }
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_parquet(&self, file: std::fs::File) -> Result<(), Error> {
+        let schema = match self.spectrum_length {
+            Some(_) => {
+                "message results {
+                    required int64 t;
+                    required float rpm;
+                    required float confidence;
+                    required float tip_speed;
+                    required group spectrum (LIST) {
+                        repeated group list {
+                            required float element;
+                        }
+                    }
+                }"
+            }
+            None => {
+                "message results {
+                    required int64 t;
+                    required float rpm;
+                    required float confidence;
+                    required float tip_speed;
+                }"
+            }
+        };
+        let mut writer = parquet::file::writer::SerializedFileWriter::new(
+            file,
+            std::sync::Arc::new(parquet::schema::parser::parse_message_type(schema)?),
+            std::sync::Arc::new(
+                parquet::file::properties::WriterProperties::builder()
+                    .set_compression(parquet::basic::Compression::ZSTD(
+                        parquet::basic::ZstdLevel::default(),
+                    ))
+                    .build(),
+            ),
+        )?;
+        let mut start = 0;
+        while start < self.len() {
+            let end = (start + ROW_GROUP_LENGTH).min(self.len());
+            let mut row_group = writer.next_row_group()?;
+            let ts: Vec<i64> = self.ts[start..end].iter().map(|t| *t as i64).collect();
+            write_column::<parquet::data_type::Int64Type>(&mut row_group, &ts, None)?;
+            write_column::<parquet::data_type::FloatType>(
+                &mut row_group,
+                &self.rpms[start..end],
+                None,
+            )?;
+            write_column::<parquet::data_type::FloatType>(
+                &mut row_group,
+                &self.confidences[start..end],
+                None,
+            )?;
+            write_column::<parquet::data_type::FloatType>(
+                &mut row_group,
+                &self.tip_speeds[start..end],
+                None,
+            )?;
+            if let Some(spectrum_length) = self.spectrum_length {
+                // every list has spectrum_length elements, a repetition level of 0 starts a row
+                let definition_levels = vec![1i16; (end - start) * spectrum_length];
+                let repetition_levels: Vec<i16> = (0..(end - start) * spectrum_length)
+                    .map(|index| if index % spectrum_length == 0 { 0 } else { 1 })
+                    .collect();
+                write_column::<parquet::data_type::FloatType>(
+                    &mut row_group,
+                    &self.spectrogram[start * spectrum_length..end * spectrum_length],
+                    Some((&definition_levels, &repetition_levels)),
+                )?;
+            }
+            row_group.close()?;
+            start = end;
+        }
+        writer.close()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hdf5")]
+impl Results {
+    // Writes the samples (datasets t, rpm, confidence, and tip_speed), the spectrogram (samples
+    // x bins) and its frequencies if recorded, the configuration (attributes of the group
+    // "configuration", values formatted as in RpmConfig), and the statistics (attributes of the
+    // group "statistics") to a single HDF5 file.
+    pub fn write_hdf5<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        frequencies: &[f64],
+        configuration: &[(&str, String)],
+        statistics: &crate::core::Statistics,
+    ) -> Result<(), Error> {
+        let file = hdf5::File::create(path)?;
+        write_dataset(&file, "t", &self.ts, "µs")?;
+        write_dataset(&file, "rpm", &self.rpms, "rpm")?;
+        write_dataset(&file, "confidence", &self.confidences, "")?;
+        write_dataset(&file, "tip_speed", &self.tip_speeds, "m/s")?;
+        if let Some(spectrum_length) = self.spectrum_length {
+            let spectrogram = file
+                .new_dataset::<f32>()
+                .shape((self.len(), spectrum_length))
+                .create("spectrogram")?;
+            spectrogram.write_raw(&self.spectrogram)?;
+            write_dataset(&file, "frequencies", frequencies, "Hz")?;
+        }
+        let group = file.create_group("configuration")?;
+        for (name, value) in configuration {
+            write_string_attribute(&group, name, value)?;
+        }
+        let group = file.create_group("statistics")?;
+        for (name, value) in [
+            ("events", statistics.events),
+            ("out_of_order_events", statistics.out_of_order_events),
+            ("subsampled_events", statistics.subsampled_events),
+            ("noise_filtered_events", statistics.noise_filtered_events),
+            ("masked_events", statistics.masked_events),
+            ("hot_pixel_events", statistics.hot_pixel_events),
+            ("accepted_events", statistics.accepted_events),
+            ("active_pixels", statistics.active_pixels as u64),
+            ("timeline_overflows", statistics.timeline_overflows),
+            ("flicker_timelines", statistics.flicker_timelines as u64),
+            ("neighbourhood_flips", statistics.neighbourhood_flips),
+            ("transition_flips", statistics.transition_flips),
+            ("dropped_flips", statistics.dropped_flips),
+        ] {
+            group.new_attr::<u64>().create(name)?.write_scalar(&value)?;
+        }
+        // s
+        group
+            .new_attr::<f64>()
+            .create("processing_time")?
+            .write_scalar(&statistics.processing_duration.as_secs_f64())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hdf5")]
+fn write_dataset<T: hdf5::H5Type>(
+    file: &hdf5::File,
+    name: &str,
+    values: &[T],
+    unit: &str,
+) -> Result<(), Error> {
+    let dataset = file.new_dataset::<T>().shape(values.len()).create(name)?;
+    dataset.write_raw(values)?;
+    if !unit.is_empty() {
+        write_string_attribute(&dataset, "unit", unit)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "hdf5")]
+fn write_string_attribute(location: &hdf5::Location, name: &str, value: &str) -> Result<(), Error> {
+    let value: hdf5::types::VarLenUnicode = value
+        .parse()
+        .map_err(|error: hdf5::types::StringError| hdf5::Error::from(error.to_string()))?;
+    location
+        .new_attr::<hdf5::types::VarLenUnicode>()
+        .create(name)?
+        .write_scalar(&value)?;
+    Ok(())
+}
+
+fn write_column<Type: parquet::data_type::DataType>(
+    row_group: &mut parquet::file::writer::SerializedRowGroupWriter<'_, std::fs::File>,
+    values: &[Type::T],
+    levels: Option<(&[i16], &[i16])>,
+) -> Result<(), Error> {
+    let mut column = row_group
+        .next_column()?
+        .expect("the schema and the written columns match");
+    column.typed::<Type>().write_batch(
+        values,
+        levels.map(|(definition_levels, _)| definition_levels),
+        levels.map(|(_, repetition_levels)| repetition_levels),
+    )?;
+    column.close()?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// Hz, lights powered by 50 Hz and 60 Hz mains flicker at twice the mains frequency.
+pub const FLICKER_FREQUENCIES: [f64; 2] = [100.0, 120.0];
+
+// Returns the index (in FLICKER_FREQUENCIES) of the flicker frequency that the dominant peak
+// of spectrum is a harmonic of, within one bin, or None if the spectrum is empty or its peak is
+// not a flicker harmonic. The first skip bins (low frequencies) are ignored.
+pub fn flicker_harmonic(spectrum: &[f32], frequency_resolution: f64, skip: usize) -> Option<usize> {
+    let (peak, amplitude) = spectrum
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+    if *amplitude <= 0.0 {
+        return None;
+    }
+    let frequency = peak as f64 * frequency_resolution;
+    FLICKER_FREQUENCIES.iter().position(|flicker_frequency| {
+        let harmonic = (frequency / flicker_frequency).round();
+        harmonic >= 1.0 && (frequency - harmonic * flicker_frequency).abs() <= frequency_resolution
+    })
+}
+
+// Detects flicker across the spectra of the selected timelines (spectrum_length values each)
+// and writes the flicker harmonic of each spectrum in harmonics (see flicker_harmonic).
+// Flicker is detected if at least fraction of the non-empty spectra peak at harmonics of the
+// same flicker frequency, and the function then returns its index in FLICKER_FREQUENCIES.
+pub fn detect(
+    spectra: &[f32],
+    spectrum_length: usize,
+    frequency_resolution: f64,
+    skip: usize,
+    fraction: f64,
+    harmonics: &mut Vec<Option<usize>>,
+) -> Option<usize> {
+    harmonics.clear();
+    let mut counts = [0usize; FLICKER_FREQUENCIES.len()];
+    let mut non_empty = 0;
+    for spectrum in spectra.chunks_exact(spectrum_length) {
+        if spectrum.iter().any(|amplitude| *amplitude > 0.0) {
+            non_empty += 1;
+        }
+        let harmonic = flicker_harmonic(spectrum, frequency_resolution, skip);
+        if let Some(harmonic) = harmonic {
+            counts[harmonic] += 1;
+        }
+        harmonics.push(harmonic);
+    }
+    let (flicker, count) = counts
+        .into_iter()
+        .enumerate()
+        .max_by_key(|(_, count)| *count)
+        .expect("FLICKER_FREQUENCIES is not empty");
+    (count > 0 && count as f64 >= fraction * non_empty as f64).then_some(flicker)
+}
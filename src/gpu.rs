@@ -0,0 +1,281 @@
+// Batched discrete Fourier transform of the most active timelines on the GPU (wgpu compute
+// shader). Each invocation computes the amplitude of one frequency of one timeline and skips
+// zero samples, since timelines are sparse.
+const SHADER: &str = r#"
+struct Parameters {
+    fft_length: u32,
+    spectrum_length: u32,
+    count: u32,
+    padding: u32,
+}
+
+@group(0) @binding(0) var<uniform> parameters: Parameters;
+@group(0) @binding(1) var<storage, read> samples: array<f32>;
+@group(0) @binding(2) var<storage, read_write> amplitudes: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let frequency = id.x;
+    let timeline = id.y;
+    if (frequency >= parameters.spectrum_length || timeline >= parameters.count) {
+        return;
+    }
+    let offset = timeline * parameters.fft_length;
+    var re = 0.0;
+    var im = 0.0;
+    for (var index = 0u; index < parameters.fft_length; index = index + 1u) {
+        let sample = samples[offset + index];
+        if (sample != 0.0) {
+            // the product is reduced modulo fft_length to keep the angle accurate
+            let angle = 6.283185307179586 * f32((frequency * index) % parameters.fft_length)
+                / f32(parameters.fft_length);
+            re = re + sample * cos(angle);
+            im = im - sample * sin(angle);
+        }
+    }
+    amplitudes[timeline * parameters.spectrum_length + frequency] = sqrt(re * re + im * im);
+}
+"#;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+// The device and the pipeline are shared by clones, the buffers are not (see Buffers).
+pub struct Dft {
+    device: std::sync::Arc<wgpu::Device>,
+    queue: std::sync::Arc<wgpu::Queue>,
+    pipeline: std::sync::Arc<wgpu::ComputePipeline>,
+    buffers: Buffers,
+    fft_length: usize,
+    spectrum_length: usize,
+    capacity: usize, // timelines per dispatch
+}
+
+// Written by each dispatch.
+struct Buffers {
+    bind_group: wgpu::BindGroup,
+    parameters: wgpu::Buffer,
+    samples: wgpu::Buffer,
+    amplitudes: wgpu::Buffer,
+    staging: wgpu::Buffer,
+}
+
+impl Buffers {
+    fn new(
+        device: &wgpu::Device,
+        pipeline: &wgpu::ComputePipeline,
+        fft_length: usize,
+        spectrum_length: usize,
+        capacity: usize,
+    ) -> Self {
+        let parameters = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("parameters"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let samples = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("samples"),
+            size: (capacity * fft_length * std::mem::size_of::<f32>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let amplitudes_size = (capacity * spectrum_length * std::mem::size_of::<f32>()) as u64;
+        let amplitudes = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("amplitudes"),
+            size: amplitudes_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("staging"),
+            size: amplitudes_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("dft"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: parameters.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: samples.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: amplitudes.as_entire_binding(),
+                },
+            ],
+        });
+        Self {
+            bind_group,
+            parameters,
+            samples,
+            amplitudes,
+            staging,
+        }
+    }
+}
+
+impl Clone for Dft {
+    fn clone(&self) -> Self {
+        Self {
+            device: self.device.clone(),
+            queue: self.queue.clone(),
+            pipeline: self.pipeline.clone(),
+            buffers: Buffers::new(
+                &self.device,
+                &self.pipeline,
+                self.fft_length,
+                self.spectrum_length,
+                self.capacity,
+            ),
+            fft_length: self.fft_length,
+            spectrum_length: self.spectrum_length,
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl Dft {
+    // Returns None if there is no hardware adapter (software adapters are slower than the CPU
+    // FFT) or if the device cannot be created.
+    pub fn new(fft_length: usize, capacity: usize) -> Option<Self> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            compatible_surface: None,
+        }))?;
+        if adapter.get_info().device_type == wgpu::DeviceType::Cpu {
+            return None;
+        }
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("fidget-spinner"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits.clone(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .ok()?;
+        let spectrum_length = fft_length / 2 + 1;
+        let capacity = capacity
+            .min(limits.max_compute_workgroups_per_dimension as usize)
+            .min(
+                limits.max_storage_buffer_binding_size as usize
+                    / (fft_length * std::mem::size_of::<f32>()),
+            );
+        if capacity == 0 {
+            return None;
+        }
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("dft"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("dft"),
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+        let buffers = Buffers::new(&device, &pipeline, fft_length, spectrum_length, capacity);
+        Some(Self {
+            device: std::sync::Arc::new(device),
+            queue: std::sync::Arc::new(queue),
+            pipeline: std::sync::Arc::new(pipeline),
+            buffers,
+            fft_length,
+            spectrum_length,
+            capacity,
+        })
+    }
+
+    // samples contains fft_length samples per timeline, amplitudes receives spectrum_length
+    // amplitudes per timeline. Returns false if the device failed (amplitudes is then
+    // partially written).
+    pub fn transform(&self, samples: &[f32], amplitudes: &mut [f32]) -> bool {
+        for (samples, amplitudes) in samples
+            .chunks(self.capacity * self.fft_length)
+            .zip(amplitudes.chunks_mut(self.capacity * self.spectrum_length))
+        {
+            if !self.transform_batch(samples, amplitudes) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn transform_batch(&self, samples: &[f32], amplitudes: &mut [f32]) -> bool {
+        let count = samples.len() / self.fft_length;
+        let parameters: Vec<u8> = [
+            self.fft_length as u32,
+            self.spectrum_length as u32,
+            count as u32,
+            0,
+        ]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect();
+        self.queue
+            .write_buffer(&self.buffers.parameters, 0, &parameters);
+        let samples_bytes: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect();
+        self.queue
+            .write_buffer(&self.buffers.samples, 0, &samples_bytes);
+        let amplitudes_size = (amplitudes.len() * std::mem::size_of::<f32>()) as u64;
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("dft") });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("dft"),
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &self.buffers.bind_group, &[]);
+            pass.dispatch_workgroups(
+                (self.spectrum_length as u32).div_ceil(WORKGROUP_SIZE),
+                count as u32,
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(
+            &self.buffers.amplitudes,
+            0,
+            &self.buffers.staging,
+            0,
+            amplitudes_size,
+        );
+        self.queue.submit(Some(encoder.finish()));
+        let slice = self.buffers.staging.slice(0..amplitudes_size);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        let _ = self.device.poll(wgpu::Maintain::Wait);
+        if !matches!(receiver.recv(), Ok(Ok(()))) {
+            return false;
+        }
+        {
+            let bytes = slice.get_mapped_range();
+            for (amplitude, bytes) in amplitudes
+                .iter_mut()
+                .zip(bytes.chunks_exact(std::mem::size_of::<f32>()))
+            {
+                *amplitude = f32::from_le_bytes(bytes.try_into().expect("chunks have 4 bytes"));
+            }
+        }
+        self.buffers.staging.unmap();
+        true
+    }
+}
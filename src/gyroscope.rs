@@ -0,0 +1,63 @@
+// Computes a reference RPM from the IMU of a camera mounted on the rotating platform (the
+// gyroscope's z axis must be aligned with the rotation axis) and aligns it with the estimator's
+// sample windows.
+#[derive(Debug, Clone, Default)]
+pub struct Gyroscope {
+    previous_sample_t: u64,
+    pending_rates: Vec<(u64, f32)>, // (µs, °/s)
+}
+
+impl Gyroscope {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.previous_sample_t = 0;
+        self.pending_rates.clear();
+    }
+
+    // rates contains (t, gyroscope_z) pairs, with gyroscope_z in °/s (AEDAT4 convention). The
+    // reference of a sample is the average absolute angular rate of the IMU samples in its
+    // window (IMU samples after the last sample are kept for the next call). Samples without any
+    // IMU sample in their window have no reference.
+    pub fn compare(
+        &mut self,
+        samples: &[crate::core::Sample],
+        rates: &[(u64, f32)],
+    ) -> Vec<crate::tachometer::Comparison> {
+        self.pending_rates.extend_from_slice(rates);
+        self.pending_rates.sort_by_key(|(t, _)| *t);
+        let mut comparisons = Vec::with_capacity(samples.len());
+        let mut rate_index = 0;
+        for sample in samples {
+            let mut rates_sum = 0.0;
+            let mut rates_count = 0;
+            while rate_index < self.pending_rates.len()
+                && self.pending_rates[rate_index].0 <= sample.t
+            {
+                let (t, rate) = self.pending_rates[rate_index];
+                if t > self.previous_sample_t && rate.is_finite() {
+                    rates_sum += rate.abs() as f64;
+                    rates_count += 1;
+                }
+                rate_index += 1;
+            }
+            // 1 °/s is 60 / 360 RPM
+            let reference = if rates_count > 0 {
+                Some((rates_sum / rates_count as f64 / 6.0) as f32)
+            } else {
+                None
+            };
+            comparisons.push(crate::tachometer::Comparison {
+                t: sample.t,
+                estimated: sample.rpm,
+                reference,
+                error: reference.map(|reference| sample.rpm - reference),
+            });
+            self.previous_sample_t = sample.t;
+        }
+        self.pending_rates.drain(0..rate_index);
+        comparisons
+    }
+}
@@ -0,0 +1,109 @@
+pub const DEFAULT_HOT_PIXEL_WINDOW: u64 = 1000000; // µs
+pub const HOT_PIXEL_MINIMUM_EVENTS: u32 = 100; // per window
+
+// Counts events per pixel over consecutive windows. At the end of each window, pixels whose
+// count is larger than ratio times the mean count of active pixels are flagged as hot until the
+// next window. Manual overrides take precedence over detection and survive resets.
+#[derive(Clone)]
+pub struct Detector {
+    width: u16,
+    ratio: Option<f64>,
+    window: u64,
+    window_start_t: Option<u64>,
+    counts: Vec<u32>,
+    detected: Vec<bool>,
+    overrides: Vec<Option<bool>>,
+}
+
+impl Detector {
+    pub fn new(width: u16, height: u16, ratio: Option<f64>, window: u64) -> Self {
+        let length = width as usize * height as usize;
+        Self {
+            width,
+            ratio,
+            window,
+            window_start_t: None,
+            counts: if ratio.is_some() {
+                vec![0; length]
+            } else {
+                Vec::new()
+            },
+            detected: vec![false; length],
+            overrides: vec![None; length],
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.window_start_t = None;
+        self.counts.fill(0);
+        self.detected.fill(false);
+    }
+
+    // Updates the detector and returns true if the event comes from a hot pixel.
+    pub fn update(&mut self, x: u16, y: u16, t: u64) -> bool {
+        let index = x as usize + y as usize * self.width as usize;
+        if let Some(ratio) = self.ratio {
+            match self.window_start_t {
+                Some(window_start_t) => {
+                    if t >= window_start_t + self.window {
+                        self.evaluate(ratio);
+                        self.window_start_t = Some(t);
+                    }
+                }
+                None => self.window_start_t = Some(t),
+            }
+            self.counts[index] = self.counts[index].saturating_add(1);
+        }
+        self.overrides[index].unwrap_or(self.detected[index])
+    }
+
+    pub fn is_hot(&self, x: u16, y: u16) -> bool {
+        let index = x as usize + y as usize * self.width as usize;
+        self.overrides[index].unwrap_or(self.detected[index])
+    }
+
+    // None restores automatic detection for this pixel.
+    pub fn set_override(&mut self, x: u16, y: u16, hot: Option<bool>) {
+        self.overrides[x as usize + y as usize * self.width as usize] = hot;
+    }
+
+    // Row-major, one entry per pixel.
+    pub fn overrides(&self) -> &[Option<bool>] {
+        &self.overrides
+    }
+
+    pub fn hot_pixels(&self) -> Vec<(u16, u16)> {
+        self.overrides
+            .iter()
+            .zip(self.detected.iter())
+            .enumerate()
+            .filter(|(_, (hot_override, detected))| hot_override.unwrap_or(**detected))
+            .map(|(index, _)| {
+                (
+                    (index % self.width as usize) as u16,
+                    (index / self.width as usize) as u16,
+                )
+            })
+            .collect()
+    }
+
+    fn evaluate(&mut self, ratio: f64) {
+        let mut sum = 0u64;
+        let mut active = 0u64;
+        for count in self.counts.iter() {
+            if *count > 0 {
+                sum += *count as u64;
+                active += 1;
+            }
+        }
+        let threshold = if active > 0 {
+            (ratio * sum as f64 / active as f64).max(HOT_PIXEL_MINIMUM_EVENTS as f64)
+        } else {
+            f64::INFINITY
+        };
+        for (detected, count) in self.detected.iter_mut().zip(self.counts.iter_mut()) {
+            *detected = *count as f64 > threshold;
+            *count = 0;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use std::sync::atomic::Ordering;
+
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64; // packets
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("queue_capacity must be larger than zero")]
+    QueueCapacity,
+}
+
+// Written by the processing thread and read by any thread without blocking the estimator
+// (the spectrum lock is only held while copying).
+pub struct State {
+    running: std::sync::atomic::AtomicBool,
+    samples: std::sync::atomic::AtomicU64,
+    rpm: std::sync::atomic::AtomicU32,
+    smoothed_rpm: std::sync::atomic::AtomicU32,
+    confidence: std::sync::atomic::AtomicU32,
+    dropped_packets: std::sync::atomic::AtomicU64,
+    spectrum: std::sync::Mutex<Vec<f32>>,
+    error: std::sync::Mutex<Option<String>>,
+}
+
+impl State {
+    pub fn running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    // Signals both threads to stop and records the first error, if any.
+    pub fn stop(&self, error: Option<String>) {
+        if let Some(error) = error {
+            let mut state_error = self.error.lock().unwrap();
+            if state_error.is_none() {
+                *state_error = Some(error);
+            }
+        }
+        self.running.store(false, Ordering::Release);
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.lock().unwrap().clone()
+    }
+
+    pub fn samples(&self) -> u64 {
+        self.samples.load(Ordering::Acquire)
+    }
+
+    pub fn rpm(&self) -> f32 {
+        f32::from_bits(self.rpm.load(Ordering::Acquire))
+    }
+
+    pub fn smoothed_rpm(&self) -> f32 {
+        f32::from_bits(self.smoothed_rpm.load(Ordering::Acquire))
+    }
+
+    pub fn confidence(&self) -> f32 {
+        f32::from_bits(self.confidence.load(Ordering::Acquire))
+    }
+
+    pub fn dropped_packets(&self) -> u64 {
+        self.dropped_packets.load(Ordering::Acquire)
+    }
+
+    pub fn spectrum(&self) -> Vec<f32> {
+        self.spectrum.lock().unwrap().clone()
+    }
+}
+
+// Runs the estimator on a background thread fed by a bounded lock-free queue. If the
+// estimator falls behind, the oldest packets are dropped so that latency stays bounded.
+pub struct Pipeline {
+    queue: std::sync::Arc<crossbeam_queue::ArrayQueue<Vec<crate::core::Event>>>,
+    state: std::sync::Arc<State>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Pipeline {
+    pub fn new(
+        mut estimator: crate::core::RpmEstimator,
+        parameters: crate::core::Parameters,
+        queue_capacity: usize,
+    ) -> Result<Self, Error> {
+        if queue_capacity == 0 {
+            return Err(Error::QueueCapacity);
+        }
+        let queue = std::sync::Arc::new(
+            crossbeam_queue::ArrayQueue::<Vec<crate::core::Event>>::new(queue_capacity),
+        );
+        let state = std::sync::Arc::new(State {
+            running: std::sync::atomic::AtomicBool::new(true),
+            samples: std::sync::atomic::AtomicU64::new(0),
+            rpm: std::sync::atomic::AtomicU32::new(0.0f32.to_bits()),
+            smoothed_rpm: std::sync::atomic::AtomicU32::new(0.0f32.to_bits()),
+            confidence: std::sync::atomic::AtomicU32::new(0.0f32.to_bits()),
+            dropped_packets: std::sync::atomic::AtomicU64::new(0),
+            spectrum: std::sync::Mutex::new(estimator.spectrum().to_vec()),
+            error: std::sync::Mutex::new(None),
+        });
+        let worker = {
+            let queue = queue.clone();
+            let state = state.clone();
+            std::thread::spawn(move || {
+                // queued packets are processed before stopping, unless the estimator fails
+                loop {
+                    match queue.pop() {
+                        Some(events) => {
+                            if let Err(error) = estimator.process(&events, &parameters) {
+                                state.stop(Some(error.to_string()));
+                                break;
+                            }
+                            if let Some(sample) = estimator.samples().last() {
+                                state
+                                    .spectrum
+                                    .lock()
+                                    .unwrap()
+                                    .copy_from_slice(estimator.spectrum());
+                                state.rpm.store(sample.rpm.to_bits(), Ordering::Release);
+                                state
+                                    .smoothed_rpm
+                                    .store(sample.smoothed_rpm.to_bits(), Ordering::Release);
+                                state
+                                    .confidence
+                                    .store(sample.confidence.to_bits(), Ordering::Release);
+                                state
+                                    .samples
+                                    .fetch_add(estimator.samples().len() as u64, Ordering::AcqRel);
+                            }
+                        }
+                        None => {
+                            if !state.running() {
+                                break;
+                            }
+                            std::thread::park_timeout(std::time::Duration::from_millis(10));
+                        }
+                    }
+                }
+            })
+        };
+        Ok(Self {
+            queue,
+            state,
+            worker: Some(worker),
+        })
+    }
+
+    pub fn state(&self) -> &std::sync::Arc<State> {
+        &self.state
+    }
+
+    pub fn sender(&self) -> Sender {
+        Sender {
+            queue: self.queue.clone(),
+            state: self.state.clone(),
+            worker: self
+                .worker
+                .as_ref()
+                .expect("the worker is only taken by stop")
+                .thread()
+                .clone(),
+        }
+    }
+
+    // Stops the processing thread and waits for it to finish.
+    pub fn stop(&mut self) {
+        self.state.stop(None);
+        if let Some(worker) = self.worker.take() {
+            worker.thread().unpark();
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+// Pushes packets into the pipeline's queue, typically from an acquisition thread.
+#[derive(Clone)]
+pub struct Sender {
+    queue: std::sync::Arc<crossbeam_queue::ArrayQueue<Vec<crate::core::Event>>>,
+    state: std::sync::Arc<State>,
+    worker: std::thread::Thread,
+}
+
+impl Sender {
+    pub fn state(&self) -> &std::sync::Arc<State> {
+        &self.state
+    }
+
+    pub fn push(&self, events: Vec<crate::core::Event>) {
+        if self.queue.force_push(events).is_some() {
+            self.state.dropped_packets.fetch_add(1, Ordering::AcqRel);
+        }
+        self.worker.unpark();
+    }
+}
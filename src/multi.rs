@@ -0,0 +1,152 @@
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("at least one camera is required")]
+    NoCameras,
+
+    #[error("expected {expected} offsets (one per camera, got {actual})")]
+    OffsetsLength { expected: usize, actual: usize },
+
+    #[error("expected {expected} event packets (one per camera, got {actual})")]
+    PacketsLength { expected: usize, actual: usize },
+
+    #[error("the stacked cameras are too large ({0} rows, the maximum is 65535)")]
+    Height(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Camera {
+    pub width: u16,
+    pub height: u16,
+    pub offset: i64, // µs, added to the camera's timestamps
+}
+
+// Maps the events of several cameras onto a single sensor (cameras are stacked vertically,
+// with an empty band between them so that downsampled pixels and sign checks never mix
+// cameras) and merges them in timestamp order after applying each camera's clock offset.
+// Events are held back until every camera has reached their timestamp, hence the output lags
+// behind the slowest camera.
+pub struct Synchronizer {
+    cameras: Vec<Camera>,
+    rows: Vec<u16>,
+    width: u16,
+    height: u16,
+    pending: Vec<std::collections::VecDeque<crate::core::Event>>,
+    watermarks: Vec<Option<u64>>,
+}
+
+impl Synchronizer {
+    pub fn new(
+        cameras: Vec<Camera>,
+        spatial_downsampling: u16,
+        sign_check_radius: u16,
+    ) -> Result<Self, Error> {
+        if cameras.is_empty() {
+            return Err(Error::NoCameras);
+        }
+        let spatial_downsampling = spatial_downsampling.max(1) as usize;
+        let gap = spatial_downsampling * (sign_check_radius as usize * 2 + 1);
+        let mut rows = Vec::with_capacity(cameras.len());
+        let mut row = 0usize;
+        for (index, camera) in cameras.iter().enumerate() {
+            if index > 0 {
+                row += gap;
+            }
+            rows.push(row);
+            row += (camera.height as usize).div_ceil(spatial_downsampling) * spatial_downsampling;
+        }
+        if row > u16::MAX as usize {
+            return Err(Error::Height(row));
+        }
+        Ok(Self {
+            rows: rows.into_iter().map(|row| row as u16).collect(),
+            width: cameras.iter().map(|camera| camera.width).max().unwrap_or(0),
+            height: row as u16,
+            pending: vec![std::collections::VecDeque::new(); cameras.len()],
+            watermarks: vec![None; cameras.len()],
+            cameras,
+        })
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    pub fn cameras(&self) -> &[Camera] {
+        &self.cameras
+    }
+
+    pub fn reset(&mut self) {
+        for pending in self.pending.iter_mut() {
+            pending.clear();
+        }
+        self.watermarks.fill(None);
+    }
+
+    // Events whose shifted timestamp would be negative are dropped.
+    pub fn push<Events: IntoIterator<Item = crate::core::Event>>(
+        &mut self,
+        camera: usize,
+        events: Events,
+    ) {
+        let Camera { offset, .. } = self.cameras[camera];
+        let row = self.rows[camera];
+        let pending = &mut self.pending[camera];
+        for event in events {
+            let t = event.t as i64 + offset;
+            if t >= 0 {
+                pending.push_back(crate::core::Event {
+                    t: t as u64,
+                    x: event.x,
+                    y: event.y + row,
+                    polarity: event.polarity,
+                });
+            }
+        }
+        if let Some(last) = pending.back() {
+            self.watermarks[camera] =
+                Some(self.watermarks[camera].map_or(last.t, |watermark| watermark.max(last.t)));
+        }
+    }
+
+    // Appends the merged events that are older than or as old as every camera's latest event.
+    pub fn drain(&mut self, events: &mut Vec<crate::core::Event>) {
+        let mut watermark = u64::MAX;
+        for camera_watermark in self.watermarks.iter() {
+            match camera_watermark {
+                Some(camera_watermark) => watermark = watermark.min(*camera_watermark),
+                None => return,
+            }
+        }
+        self.merge(watermark, events);
+    }
+
+    // Appends all the pending events (for instance at the end of the recordings).
+    pub fn flush(&mut self, events: &mut Vec<crate::core::Event>) {
+        self.merge(u64::MAX, events);
+    }
+
+    fn merge(&mut self, watermark: u64, events: &mut Vec<crate::core::Event>) {
+        loop {
+            let mut next: Option<(usize, u64)> = None;
+            for (camera, pending) in self.pending.iter().enumerate() {
+                if let Some(event) = pending.front() {
+                    if event.t <= watermark && next.is_none_or(|(_, t)| event.t < t) {
+                        next = Some((camera, event.t));
+                    }
+                }
+            }
+            match next {
+                Some((camera, _)) => events.push(
+                    self.pending[camera]
+                        .pop_front()
+                        .expect("the camera has a pending event"),
+                ),
+                None => break,
+            }
+        }
+    }
+}
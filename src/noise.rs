@@ -0,0 +1,90 @@
+pub const DEFAULT_NOISE_FILTER_NEIGHBOURS: usize = 1;
+
+// Full-resolution pre-filter. The refractory period drops events that follow the previous
+// accepted event of the same pixel too closely. The background activity filter drops events
+// unless at least `neighbours` of the 8 adjacent pixels fired within `window`.
+#[derive(Clone)]
+pub struct Filter {
+    width: u16,
+    height: u16,
+    refractory_period: Option<u64>,
+    window: Option<u64>,
+    neighbours: usize,
+    accepted_ts: Vec<u64>,
+    ts: Vec<u64>,
+}
+
+impl Filter {
+    pub fn new(
+        width: u16,
+        height: u16,
+        refractory_period: Option<u64>,
+        window: Option<u64>,
+        neighbours: usize,
+    ) -> Self {
+        let length = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            refractory_period,
+            window,
+            neighbours,
+            accepted_ts: if refractory_period.is_some() {
+                vec![u64::MAX; length]
+            } else {
+                Vec::new()
+            },
+            ts: if window.is_some() {
+                vec![u64::MAX; length]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.refractory_period.is_some() || self.window.is_some()
+    }
+
+    pub fn reset(&mut self) {
+        self.accepted_ts.fill(u64::MAX);
+        self.ts.fill(u64::MAX);
+    }
+
+    // Returns true if the event passes the filter (timestamps must be monotonic).
+    pub fn filter(&mut self, x: u16, y: u16, t: u64) -> bool {
+        let index = x as usize + y as usize * self.width as usize;
+        let mut pass = true;
+        if let Some(window) = self.window {
+            let mut neighbours = 0;
+            'outer: for neighbour_y in y.saturating_sub(1)..=(y + 1).min(self.height - 1) {
+                for neighbour_x in x.saturating_sub(1)..=(x + 1).min(self.width - 1) {
+                    if neighbour_x == x && neighbour_y == y {
+                        continue;
+                    }
+                    let neighbour_t =
+                        self.ts[neighbour_x as usize + neighbour_y as usize * self.width as usize];
+                    if neighbour_t != u64::MAX && t - neighbour_t <= window {
+                        neighbours += 1;
+                        if neighbours >= self.neighbours {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+            self.ts[index] = t;
+            pass = neighbours >= self.neighbours;
+        }
+        if let Some(refractory_period) = self.refractory_period {
+            if pass {
+                let accepted_t = self.accepted_ts[index];
+                if accepted_t != u64::MAX && t - accepted_t < refractory_period {
+                    pass = false;
+                } else {
+                    self.accepted_ts[index] = t;
+                }
+            }
+        }
+        pass
+    }
+}
@@ -0,0 +1,101 @@
+pub const STAGES: usize = 4;
+
+// Process is the whole process (or flush) call, which includes the other stages. The event loop
+// is the difference between Process and the sum of the other stages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Process,
+    Ranking,   // activity scores, selection of the most active timelines, and tracking
+    Transform, // FFT (or Lomb-Scargle periodogram) of the selected timelines
+    Peak,      // summed spectrum, peak detection, and per-cluster, region, and bin estimates
+}
+
+impl Stage {
+    pub const ALL: [Stage; STAGES] = [
+        Stage::Process,
+        Stage::Ranking,
+        Stage::Transform,
+        Stage::Peak,
+    ];
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Stage::Process => "process",
+            Stage::Ranking => "ranking",
+            Stage::Transform => "transform",
+            Stage::Peak => "peak",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timing {
+    pub calls: u64,
+    pub total: std::time::Duration,
+    pub maximum: std::time::Duration,
+}
+
+// Returned by Profiler::start. With the "tracing" feature, the stage is also a tracing span
+// (at the trace level) that is exited when the timer is stopped or dropped.
+pub struct Timer {
+    stage: Stage,
+    start: Option<std::time::Instant>,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+// Per-stage timings, recorded only while enabled since reading the clock is not free.
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    enabled: bool,
+    timings: [Timing; STAGES],
+}
+
+impl Profiler {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    // Enabling the profiler resets the timings.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled {
+            self.reset();
+        }
+        self.enabled = enabled;
+    }
+
+    pub fn reset(&mut self) {
+        self.timings = [Timing::default(); STAGES];
+    }
+
+    pub fn timing(&self, stage: Stage) -> Timing {
+        self.timings[stage as usize]
+    }
+
+    pub fn start(&self, stage: Stage) -> Timer {
+        Timer {
+            stage,
+            start: self.enabled.then(std::time::Instant::now),
+            #[cfg(feature = "tracing")]
+            _span: match stage {
+                Stage::Process => tracing::trace_span!("process"),
+                Stage::Ranking => tracing::trace_span!("ranking"),
+                Stage::Transform => tracing::trace_span!("transform"),
+                Stage::Peak => tracing::trace_span!("peak"),
+            }
+            .entered(),
+        }
+    }
+
+    pub fn stop(&mut self, timer: Timer) {
+        if let Some(start) = timer.start.filter(|_| self.enabled) {
+            let duration = start.elapsed();
+            let timing = &mut self.timings[timer.stage as usize];
+            timing.calls += 1;
+            timing.total += duration;
+            timing.maximum = timing.maximum.max(duration);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::io::Write;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:5555";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("unknown format \"{0}\" (expected \"json\" or \"msgpack\")")]
+    Format(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Msgpack,
+}
+
+impl std::str::FromStr for Format {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "json" => Ok(Format::Json),
+            "msgpack" => Ok(Format::Msgpack),
+            _ => Err(Error::Format(string.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Message {
+    t: u64,
+    rpm: f32,
+    confidence: f32,
+}
+
+impl Message {
+    // JSON messages are newline-delimited, MessagePack messages are maps with three keys.
+    fn encode(&self, format: Format, buffer: &mut Vec<u8>) {
+        buffer.clear();
+        match format {
+            Format::Json => {
+                let _ = writeln!(
+                    buffer,
+                    "{{\"t\":{},\"rpm\":{},\"confidence\":{}}}",
+                    self.t,
+                    json_number(self.rpm),
+                    json_number(self.confidence)
+                );
+            }
+            Format::Msgpack => {
+                buffer.push(0x83);
+                buffer.extend_from_slice(&[0xa1, b't', 0xcf]);
+                buffer.extend_from_slice(&self.t.to_be_bytes());
+                buffer.extend_from_slice(&[0xa3, b'r', b'p', b'm', 0xca]);
+                buffer.extend_from_slice(&self.rpm.to_be_bytes());
+                buffer.push(0xaa);
+                buffer.extend_from_slice(b"confidence");
+                buffer.push(0xca);
+                buffer.extend_from_slice(&self.confidence.to_be_bytes());
+            }
+        }
+    }
+}
+
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_owned()
+    }
+}
+
+// Streams samples to every connected TCP client from a background thread. Slow or
+// disconnected clients are dropped rather than blocking the estimator. Clones share the same
+// thread, which stops when close is called or when every clone has been dropped.
+#[derive(Clone)]
+pub struct Publisher {
+    sender: std::sync::mpsc::Sender<Message>,
+    running: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    local_address: std::net::SocketAddr,
+}
+
+impl Publisher {
+    pub fn new<Address: std::net::ToSocketAddrs>(
+        address: Address,
+        format: Format,
+    ) -> Result<Self, Error> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let local_address = listener.local_addr()?;
+        let (sender, receiver) = std::sync::mpsc::channel::<Message>();
+        let running = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+        {
+            let running = running.clone();
+            std::thread::spawn(move || {
+                let mut clients: Vec<std::net::TcpStream> = Vec::new();
+                let mut buffer = Vec::new();
+                while running.load(std::sync::atomic::Ordering::Acquire) {
+                    while let Ok((client, _)) = listener.accept() {
+                        if client.set_nonblocking(false).is_ok()
+                            && client.set_nodelay(true).is_ok()
+                            && client
+                                .set_write_timeout(Some(std::time::Duration::from_millis(100)))
+                                .is_ok()
+                        {
+                            clients.push(client);
+                        }
+                    }
+                    match receiver.recv_timeout(std::time::Duration::from_millis(50)) {
+                        Ok(message) => {
+                            message.encode(format, &mut buffer);
+                            clients.retain_mut(|client| client.write_all(&buffer).is_ok());
+                        }
+                        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
+                        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            });
+        }
+        Ok(Self {
+            sender,
+            running,
+            local_address,
+        })
+    }
+
+    pub fn local_address(&self) -> std::net::SocketAddr {
+        self.local_address
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(std::sync::atomic::Ordering::Acquire)
+    }
+
+    pub fn publish(&self, samples: &[crate::core::Sample]) {
+        if !self.is_running() {
+            return;
+        }
+        for sample in samples {
+            let _ = self.sender.send(Message {
+                t: sample.t,
+                rpm: sample.rpm,
+                confidence: sample.confidence,
+            });
+        }
+    }
+
+    pub fn close(&self) {
+        self.running
+            .store(false, std::sync::atomic::Ordering::Release);
+    }
+}
@@ -0,0 +1,4219 @@
+use numpy::prelude::*;
+use numpy::Element;
+use pyo3::prelude::*;
+
+#[cfg(feature = "publisher")]
+use crate::publisher;
+use crate::{
+    aedat, aedat4, alarm, core, dat, es, evt3, export, gyroscope, hot_pixels, live, multi, noise,
+    profiling, render, smoothing, state, strobe, tachometer, tracking,
+};
+
+#[pyclass(get_all, frozen)]
+pub struct Cluster {
+    rpm: f32,
+    frequency: f32,
+    confidence: f32,
+    x0: u16,
+    y0: u16,
+    x1: u16,
+    y1: u16,
+    timelines: usize,
+}
+
+impl From<&core::Cluster> for Cluster {
+    fn from(cluster: &core::Cluster) -> Self {
+        Self {
+            rpm: cluster.rpm,
+            frequency: cluster.frequency,
+            confidence: cluster.confidence,
+            x0: cluster.x0,
+            y0: cluster.y0,
+            x1: cluster.x1,
+            y1: cluster.y1,
+            timelines: cluster.timelines,
+        }
+    }
+}
+
+#[pyclass(get_all, frozen)]
+pub struct Geometry {
+    x: f32,
+    y: f32,
+    semi_major: f32,
+    semi_minor: f32,
+    angle: f32,
+    tilt: f32,
+}
+
+impl From<&tracking::Geometry> for Geometry {
+    fn from(geometry: &tracking::Geometry) -> Self {
+        Self {
+            x: geometry.x,
+            y: geometry.y,
+            semi_major: geometry.semi_major,
+            semi_minor: geometry.semi_minor,
+            angle: geometry.angle,
+            tilt: geometry.tilt,
+        }
+    }
+}
+
+#[pyclass(get_all, frozen)]
+pub struct RadialBin {
+    radius: f32,
+    rpm: f32,
+    frequency: f32,
+    confidence: f32,
+    timelines: usize,
+}
+
+impl From<&core::RadialBin> for RadialBin {
+    fn from(radial_bin: &core::RadialBin) -> Self {
+        Self {
+            radius: radial_bin.radius,
+            rpm: radial_bin.rpm,
+            frequency: radial_bin.frequency,
+            confidence: radial_bin.confidence,
+            timelines: radial_bin.timelines,
+        }
+    }
+}
+
+#[pyclass(get_all, frozen)]
+pub struct Statistics {
+    events: u64,
+    out_of_order_events: u64,
+    subsampled_events: u64,
+    noise_filtered_events: u64,
+    masked_events: u64,
+    hot_pixel_events: u64,
+    accepted_events: u64,
+    active_pixels: usize,
+    timeline_overflows: u64,
+    flicker_timelines: usize,
+    neighbourhood_flips: u64,
+    transition_flips: u64,
+    dropped_flips: u64,
+    processing_time: f64, // s
+}
+
+impl From<core::Statistics> for Statistics {
+    fn from(statistics: core::Statistics) -> Self {
+        Self {
+            events: statistics.events,
+            out_of_order_events: statistics.out_of_order_events,
+            subsampled_events: statistics.subsampled_events,
+            noise_filtered_events: statistics.noise_filtered_events,
+            masked_events: statistics.masked_events,
+            hot_pixel_events: statistics.hot_pixel_events,
+            accepted_events: statistics.accepted_events,
+            active_pixels: statistics.active_pixels,
+            timeline_overflows: statistics.timeline_overflows,
+            flicker_timelines: statistics.flicker_timelines,
+            neighbourhood_flips: statistics.neighbourhood_flips,
+            transition_flips: statistics.transition_flips,
+            dropped_flips: statistics.dropped_flips,
+            processing_time: statistics.processing_duration.as_secs_f64(),
+        }
+    }
+}
+
+#[pyclass(get_all, frozen)]
+pub struct Timing {
+    calls: u64,
+    total_time: f64,   // s
+    maximum_time: f64, // s
+}
+
+impl From<profiling::Timing> for Timing {
+    fn from(timing: profiling::Timing) -> Self {
+        Self {
+            calls: timing.calls,
+            total_time: timing.total.as_secs_f64(),
+            maximum_time: timing.maximum.as_secs_f64(),
+        }
+    }
+}
+
+// A unit name ("us", "ns", or "ms") or a scale (µs per timestamp tick).
+#[derive(FromPyObject)]
+enum TimeUnit {
+    Scale(f64),
+    Name(String),
+}
+
+// Estimator configuration, validated on construction (see core::Configuration). hop_duration
+// (s) takes precedence over sampling_frequency (Hz) when both are given, and is read back as
+// sampling_frequency.
+#[pyclass]
+#[derive(Clone)]
+pub struct RpmConfig {
+    configuration: core::Configuration,
+}
+
+const RPM_CONFIG_FIELDS: [&str; 53] = [
+    "width",
+    "height",
+    "fft_samples",
+    "fft_frequency",
+    "timestamp_policy",
+    "sampling_frequency",
+    "events_per_sample",
+    "method",
+    "harmonics",
+    "window_duration",
+    "hot_pixel_ratio",
+    "hot_pixel_window",
+    "refractory_period",
+    "noise_filter_window",
+    "noise_filter_neighbours",
+    "spatial_downsampling",
+    "anti_aliasing",
+    "most_active_timelines",
+    "window_function",
+    "zero_padding",
+    "peak_interpolation",
+    "smoothing",
+    "smoothing_alpha",
+    "process_noise",
+    "measurement_noise",
+    "activity_tau",
+    "ranking",
+    "ranking_window",
+    "timeline_length",
+    "mode",
+    "warmup_events",
+    "warmup_timelines",
+    "activity_fraction",
+    "strobe_length",
+    "flicker_rejection",
+    "time_unit",
+    "wrap_period",
+    "roi_tracking",
+    "radial_bins",
+    "low_frequency",
+    "low_frequency_rate",
+    "refine",
+    "change_threshold",
+    "spectral_smoothing",
+    "sign_check_radius",
+    "sign_check_tolerance",
+    "sign_detection",
+    "debounce_events",
+    "max_latency_ms",
+    "subsampling_threshold",
+    "subsampling",
+    "blades",
+    "flips_capacity",
+];
+
+#[pymethods]
+impl RpmConfig {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        width = core::DEFAULT_WIDTH,
+        height = core::DEFAULT_HEIGHT,
+        fft_samples = core::DEFAULT_FFT_SAMPLES,
+        fft_frequency = core::DEFAULT_FFT_FREQUENCY,
+        timestamp_policy = "skip",
+        sampling_frequency = core::DEFAULT_SAMPLING_FREQUENCY,
+        events_per_sample = None,
+        method = "autocorrelation",
+        harmonics = core::DEFAULT_HARMONICS,
+        window_duration = None,
+        hop_duration = None,
+        hot_pixel_ratio = None,
+        hot_pixel_window = hot_pixels::DEFAULT_HOT_PIXEL_WINDOW,
+        refractory_period = None,
+        noise_filter_window = None,
+        noise_filter_neighbours = noise::DEFAULT_NOISE_FILTER_NEIGHBOURS,
+        spatial_downsampling = core::DEFAULT_SPATIAL_DOWNSAMPLING,
+        anti_aliasing = false,
+        most_active_timelines = core::DEFAULT_MOST_ACTIVE_TIMELINES,
+        window_function = "rectangular",
+        zero_padding = 1,
+        peak_interpolation = false,
+        smoothing = "none",
+        smoothing_alpha = smoothing::DEFAULT_SMOOTHING_ALPHA,
+        process_noise = smoothing::DEFAULT_PROCESS_NOISE,
+        measurement_noise = smoothing::DEFAULT_MEASUREMENT_NOISE,
+        activity_tau = core::DEFAULT_ACTIVITY_TAU,
+        ranking = "activity",
+        ranking_window = core::DEFAULT_RANKING_WINDOW,
+        timeline_length = core::DEFAULT_TIMELINE_LENGTH,
+        mode = "rotation",
+        warmup_events = 0,
+        warmup_timelines = 0,
+        activity_fraction = None,
+        strobe_length = 0,
+        flicker_rejection = None,
+        time_unit = TimeUnit::Name("us".to_owned()),
+        wrap_period = None,
+        roi_tracking = false,
+        radial_bins = 0,
+        low_frequency = "off",
+        low_frequency_rate = core::DEFAULT_LOW_FREQUENCY_RATE,
+        refine = false,
+        change_threshold = None,
+        spectral_smoothing = None,
+        sign_check_radius = core::DEFAULT_SIGN_CHECK_RADIUS,
+        sign_check_tolerance = 0,
+        sign_detection = "neighbourhood",
+        debounce_events = core::DEFAULT_DEBOUNCE_EVENTS,
+        max_latency_ms = None,
+        subsampling_threshold = None,
+        subsampling = "stratified",
+        blades = None,
+        flips_capacity = core::DEFAULT_FLIPS_CAPACITY,
+    ))]
+    fn new(
+        width: u16,
+        height: u16,
+        fft_samples: usize,
+        fft_frequency: f64,
+        timestamp_policy: &str,
+        sampling_frequency: f64,
+        events_per_sample: Option<usize>,
+        method: &str,
+        harmonics: usize,
+        window_duration: Option<f64>,
+        hop_duration: Option<f64>,
+        hot_pixel_ratio: Option<f64>,
+        hot_pixel_window: u64,
+        refractory_period: Option<u64>,
+        noise_filter_window: Option<u64>,
+        noise_filter_neighbours: usize,
+        spatial_downsampling: u16,
+        anti_aliasing: bool,
+        most_active_timelines: usize,
+        window_function: &str,
+        zero_padding: usize,
+        peak_interpolation: bool,
+        smoothing: &str,
+        smoothing_alpha: f64,
+        process_noise: f64,
+        measurement_noise: f64,
+        activity_tau: u64,
+        ranking: &str,
+        ranking_window: u64,
+        timeline_length: usize,
+        mode: &str,
+        warmup_events: u64,
+        warmup_timelines: usize,
+        activity_fraction: Option<f64>,
+        strobe_length: usize,
+        flicker_rejection: Option<f64>,
+        time_unit: TimeUnit,
+        wrap_period: Option<u64>,
+        roi_tracking: bool,
+        radial_bins: usize,
+        low_frequency: &str,
+        low_frequency_rate: f64,
+        refine: bool,
+        change_threshold: Option<f64>,
+        spectral_smoothing: Option<f64>,
+        sign_check_radius: u16,
+        sign_check_tolerance: usize,
+        sign_detection: &str,
+        debounce_events: u16,
+        max_latency_ms: Option<f64>,
+        subsampling_threshold: Option<f64>,
+        subsampling: &str,
+        blades: Option<usize>,
+        flips_capacity: usize,
+    ) -> PyResult<Self> {
+        let builder = core::Configuration::builder()
+            .width(width)
+            .height(height)
+            .fft_samples(fft_samples)
+            .fft_frequency(fft_frequency)
+            .timestamp_policy(timestamp_policy.parse()?)
+            .sampling_frequency(sampling_frequency)
+            .events_per_sample(events_per_sample)
+            .method(method.parse()?)
+            .harmonics(harmonics)
+            .window_duration(window_duration)
+            .hot_pixel_ratio(hot_pixel_ratio)
+            .hot_pixel_window(hot_pixel_window)
+            .refractory_period(refractory_period)
+            .noise_filter_window(noise_filter_window)
+            .noise_filter_neighbours(noise_filter_neighbours)
+            .spatial_downsampling(spatial_downsampling)
+            .anti_aliasing(anti_aliasing)
+            .most_active_timelines(most_active_timelines)
+            .window_function(window_function.parse()?)
+            .zero_padding(zero_padding)
+            .peak_interpolation(peak_interpolation)
+            .smoothing(smoothing.parse()?)
+            .smoothing_alpha(smoothing_alpha)
+            .process_noise(process_noise)
+            .measurement_noise(measurement_noise)
+            .activity_tau(activity_tau)
+            .ranking(ranking.parse()?)
+            .ranking_window(ranking_window)
+            .timeline_length(timeline_length)
+            .mode(mode.parse()?)
+            .warmup_events(warmup_events)
+            .warmup_timelines(warmup_timelines)
+            .activity_fraction(activity_fraction)
+            .strobe_length(strobe_length)
+            .flicker_rejection(flicker_rejection)
+            .time_unit(match time_unit {
+                TimeUnit::Scale(scale) => core::TimeUnit::Scale(scale),
+                TimeUnit::Name(name) => name.parse()?,
+            })
+            .wrap_period(wrap_period)
+            .roi_tracking(roi_tracking)
+            .radial_bins(radial_bins)
+            .low_frequency(low_frequency.parse()?)
+            .low_frequency_rate(low_frequency_rate)
+            .refine(refine)
+            .change_threshold(change_threshold)
+            .spectral_smoothing(spectral_smoothing)
+            .sign_check_radius(sign_check_radius)
+            .sign_check_tolerance(sign_check_tolerance)
+            .sign_detection(sign_detection.parse()?)
+            .debounce_events(debounce_events)
+            .max_latency_ms(max_latency_ms)
+            .subsampling_threshold(subsampling_threshold)
+            .subsampling(subsampling.parse()?)
+            .blades(blades)
+            .flips_capacity(flips_capacity);
+        let builder = match hop_duration {
+            Some(hop_duration) => builder.hop_duration(hop_duration),
+            None => builder,
+        };
+        Ok(Self {
+            configuration: builder.build()?,
+        })
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.configuration.width
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.configuration.height
+    }
+
+    #[getter]
+    fn fft_samples(&self) -> usize {
+        self.configuration.fft_samples
+    }
+
+    #[getter]
+    fn fft_frequency(&self) -> f64 {
+        self.configuration.fft_frequency
+    }
+
+    #[getter]
+    fn timestamp_policy(&self) -> String {
+        self.configuration.timestamp_policy.to_string()
+    }
+
+    #[getter]
+    fn sampling_frequency(&self) -> f64 {
+        self.configuration.sampling_frequency
+    }
+
+    #[getter]
+    fn events_per_sample(&self) -> Option<usize> {
+        self.configuration.events_per_sample
+    }
+
+    #[getter]
+    fn method(&self) -> String {
+        self.configuration.method.to_string()
+    }
+
+    #[getter]
+    fn harmonics(&self) -> usize {
+        self.configuration.harmonics
+    }
+
+    #[getter]
+    fn window_duration(&self) -> Option<f64> {
+        self.configuration.window_duration
+    }
+
+    #[getter]
+    fn hot_pixel_ratio(&self) -> Option<f64> {
+        self.configuration.hot_pixel_ratio
+    }
+
+    #[getter]
+    fn hot_pixel_window(&self) -> u64 {
+        self.configuration.hot_pixel_window
+    }
+
+    #[getter]
+    fn refractory_period(&self) -> Option<u64> {
+        self.configuration.refractory_period
+    }
+
+    #[getter]
+    fn noise_filter_window(&self) -> Option<u64> {
+        self.configuration.noise_filter_window
+    }
+
+    #[getter]
+    fn noise_filter_neighbours(&self) -> usize {
+        self.configuration.noise_filter_neighbours
+    }
+
+    #[getter]
+    fn spatial_downsampling(&self) -> u16 {
+        self.configuration.spatial_downsampling
+    }
+
+    #[getter]
+    fn anti_aliasing(&self) -> bool {
+        self.configuration.anti_aliasing
+    }
+
+    #[getter]
+    fn most_active_timelines(&self) -> usize {
+        self.configuration.most_active_timelines
+    }
+
+    #[getter]
+    fn window_function(&self) -> String {
+        self.configuration.window_function.to_string()
+    }
+
+    #[getter]
+    fn zero_padding(&self) -> usize {
+        self.configuration.zero_padding
+    }
+
+    #[getter]
+    fn peak_interpolation(&self) -> bool {
+        self.configuration.peak_interpolation
+    }
+
+    #[getter]
+    fn smoothing(&self) -> String {
+        self.configuration.smoothing.to_string()
+    }
+
+    #[getter]
+    fn smoothing_alpha(&self) -> f64 {
+        self.configuration.smoothing_alpha
+    }
+
+    #[getter]
+    fn process_noise(&self) -> f64 {
+        self.configuration.process_noise
+    }
+
+    #[getter]
+    fn measurement_noise(&self) -> f64 {
+        self.configuration.measurement_noise
+    }
+
+    #[getter]
+    fn activity_tau(&self) -> u64 {
+        self.configuration.activity_tau
+    }
+
+    #[getter]
+    fn ranking(&self) -> String {
+        self.configuration.ranking.to_string()
+    }
+
+    #[getter]
+    fn ranking_window(&self) -> u64 {
+        self.configuration.ranking_window
+    }
+
+    #[getter]
+    fn timeline_length(&self) -> usize {
+        self.configuration.timeline_length
+    }
+
+    #[getter]
+    fn mode(&self) -> String {
+        self.configuration.mode.to_string()
+    }
+
+    #[getter]
+    fn warmup_events(&self) -> u64 {
+        self.configuration.warmup_events
+    }
+
+    #[getter]
+    fn warmup_timelines(&self) -> usize {
+        self.configuration.warmup_timelines
+    }
+
+    #[getter]
+    fn activity_fraction(&self) -> Option<f64> {
+        self.configuration.activity_fraction
+    }
+
+    #[getter]
+    fn strobe_length(&self) -> usize {
+        self.configuration.strobe_length
+    }
+
+    #[getter]
+    fn flicker_rejection(&self) -> Option<f64> {
+        self.configuration.flicker_rejection
+    }
+
+    #[getter]
+    fn wrap_period(&self) -> Option<u64> {
+        self.configuration.wrap_period
+    }
+
+    #[getter]
+    fn roi_tracking(&self) -> bool {
+        self.configuration.roi_tracking
+    }
+
+    #[getter]
+    fn radial_bins(&self) -> usize {
+        self.configuration.radial_bins
+    }
+
+    #[getter]
+    fn low_frequency(&self) -> String {
+        self.configuration.low_frequency.to_string()
+    }
+
+    #[getter]
+    fn low_frequency_rate(&self) -> f64 {
+        self.configuration.low_frequency_rate
+    }
+
+    #[getter]
+    fn refine(&self) -> bool {
+        self.configuration.refine
+    }
+
+    #[getter]
+    fn change_threshold(&self) -> Option<f64> {
+        self.configuration.change_threshold
+    }
+
+    #[getter]
+    fn spectral_smoothing(&self) -> Option<f64> {
+        self.configuration.spectral_smoothing
+    }
+
+    #[getter]
+    fn sign_check_radius(&self) -> u16 {
+        self.configuration.sign_check_radius
+    }
+
+    #[getter]
+    fn sign_check_tolerance(&self) -> usize {
+        self.configuration.sign_check_tolerance
+    }
+
+    #[getter]
+    fn sign_detection(&self) -> String {
+        self.configuration.sign_detection.to_string()
+    }
+
+    #[getter]
+    fn debounce_events(&self) -> u16 {
+        self.configuration.debounce_events
+    }
+
+    #[getter]
+    fn max_latency_ms(&self) -> Option<f64> {
+        self.configuration.max_latency_ms
+    }
+
+    #[getter]
+    fn subsampling_threshold(&self) -> Option<f64> {
+        self.configuration.subsampling_threshold
+    }
+
+    #[getter]
+    fn subsampling(&self) -> String {
+        self.configuration.subsampling.to_string()
+    }
+
+    #[getter]
+    fn blades(&self) -> Option<usize> {
+        self.configuration.blades
+    }
+
+    #[getter]
+    fn flips_capacity(&self) -> usize {
+        self.configuration.flips_capacity
+    }
+
+    #[getter]
+    fn time_unit<'py>(&self, python: Python<'py>) -> Bound<'py, PyAny> {
+        match self.configuration.time_unit {
+            core::TimeUnit::Scale(scale) => pyo3::types::PyFloat::new(python, scale).into_any(),
+            time_unit => pyo3::types::PyString::new(python, &time_unit.to_string()).into_any(),
+        }
+    }
+
+    fn __repr__(slf: &Bound<'_, Self>) -> PyResult<String> {
+        let mut arguments = Vec::with_capacity(RPM_CONFIG_FIELDS.len());
+        for name in RPM_CONFIG_FIELDS {
+            arguments.push(format!("{name}={}", slf.getattr(name)?.repr()?));
+        }
+        Ok(format!("RpmConfig({})", arguments.join(", ")))
+    }
+}
+
+// Returns config, or the configuration built from the keyword arguments (see RpmConfig).
+fn rpm_config(
+    python: Python,
+    config: Option<RpmConfig>,
+    configuration: Option<&Bound<'_, pyo3::types::PyDict>>,
+) -> PyResult<core::Configuration> {
+    match config {
+        Some(config) => {
+            if configuration.is_some_and(|configuration| !configuration.is_empty()) {
+                return Err(pyo3::exceptions::PyException::new_err(
+                    "config cannot be combined with configuration keyword arguments",
+                ));
+            }
+            Ok(config.configuration)
+        }
+        None => Ok(python
+            .get_type::<RpmConfig>()
+            .call((), configuration)?
+            .extract::<RpmConfig>()?
+            .configuration),
+    }
+}
+
+#[pyclass]
+pub struct RpmCalculator {
+    estimator: core::RpmEstimator,
+    tachometer: tachometer::Tachometer,
+    gyroscope: gyroscope::Gyroscope,
+    results: export::Results,
+    callback: Option<Callback>,
+    alarm_callback: Option<PyObject>,
+    #[cfg(feature = "publisher")]
+    publisher: Option<publisher::Publisher>,
+}
+
+struct Callback {
+    function: PyObject,
+    spectrum: bool,
+}
+
+#[pymethods]
+impl RpmCalculator {
+    // The configuration is either config or the keyword arguments of RpmConfig. Flips are only
+    // recorded (see all_flips) if record_flips is set.
+    #[new]
+    #[pyo3(signature = (
+        config = None,
+        *,
+        record_spectrogram = false,
+        record_flips = false,
+        **configuration,
+    ))]
+    fn new(
+        python: Python,
+        config: Option<RpmConfig>,
+        record_spectrogram: bool,
+        record_flips: bool,
+        configuration: Option<&Bound<'_, pyo3::types::PyDict>>,
+    ) -> PyResult<Self> {
+        let mut estimator = core::RpmEstimator::new(rpm_config(python, config, configuration)?)?;
+        estimator.set_record_spectra(record_spectrogram);
+        estimator.set_record_flips(record_flips);
+        Ok(Self {
+            results: export::Results::new(record_spectrogram.then(|| estimator.spectrum().len())),
+            estimator,
+            tachometer: tachometer::Tachometer::new(),
+            gyroscope: gyroscope::Gyroscope::new(),
+            callback: None,
+            alarm_callback: None,
+            #[cfg(feature = "publisher")]
+            publisher: None,
+        })
+    }
+
+    #[pyo3(signature = (
+        events,
+        spectrum = None,
+        autocorrelation = None,
+        autocorrelation_detections = None,
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+        strict = true,
+    ))]
+    pub fn process(
+        &mut self,
+        events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+        spectrum: Option<&pyo3::Bound<'_, numpy::PyArray1<f32>>>,
+        autocorrelation: Option<&pyo3::Bound<'_, numpy::PyArray1<f32>>>,
+        autocorrelation_detections: Option<&pyo3::Bound<'_, numpy::PyArray1<f32>>>,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+        strict: bool,
+    ) -> PyResult<Option<PyObject>> {
+        Python::with_gil(|python| -> PyResult<Option<PyObject>> {
+            let events = unsafe { raw_dvs_packets(python, events, strict)? };
+            let estimator = &mut self.estimator;
+            python.allow_threads(|| {
+                estimator.process_events(
+                    events.into_iter().flatten(),
+                    &core::Parameters {
+                        amplitude_threshold,
+                        autocorrelation_threshold,
+                        frequency_multiplier,
+                    },
+                )
+            })?;
+            if let Some(spectrum) = spectrum {
+                copy_to_array("spectrum", self.estimator.spectrum(), spectrum)?;
+            }
+            if let Some(autocorrelation) = autocorrelation {
+                copy_to_array(
+                    "autocorrelation",
+                    self.estimator.autocorrelation(),
+                    autocorrelation,
+                )?;
+            }
+            if let Some(autocorrelation_detections) = autocorrelation_detections {
+                copy_to_array(
+                    "autocorrelation_detections",
+                    &self.estimator.autocorrelation_detections().to_array(),
+                    autocorrelation_detections,
+                )?;
+            }
+            self.record_results();
+            self.call_callback(python)?;
+            Ok(self.publish_samples(python))
+        })
+    }
+
+    // Same as process (without output arrays), but returns an awaitable and processes the events
+    // on a worker thread of a Tokio runtime, so that the asyncio event loop is not blocked. The
+    // calculator is borrowed until the awaitable completes, other calls raise an exception in
+    // the meantime. The callback (see set_callback) is called from the worker thread.
+    #[pyo3(signature = (
+        events,
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+        strict = true,
+    ))]
+    fn process_async<'py>(
+        slf: Py<Self>,
+        python: Python<'py>,
+        events: PyObject,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+        strict: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        pyo3_async_runtimes::tokio::future_into_py(python, async move {
+            pyo3_async_runtimes::tokio::get_runtime()
+                .spawn_blocking(move || {
+                    Python::with_gil(|python| {
+                        slf.try_borrow_mut(python)?.process(
+                            events.bind(python),
+                            None,
+                            None,
+                            None,
+                            amplitude_threshold,
+                            autocorrelation_threshold,
+                            frequency_multiplier,
+                            strict,
+                        )
+                    })
+                })
+                .await
+                .map_err(|error| pyo3::exceptions::PyRuntimeError::new_err(error.to_string()))?
+        })
+    }
+
+    // Evaluates the last, partial window at the end of a stream (see core::RpmEstimator::flush).
+    // t_end (in time_unit) defaults to the timestamp of the last event.
+    #[pyo3(signature = (
+        t_end = None,
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+    ))]
+    fn flush(
+        &mut self,
+        python: Python,
+        t_end: Option<u64>,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+    ) -> PyResult<Option<PyObject>> {
+        let estimator = &mut self.estimator;
+        python.allow_threads(|| {
+            estimator.flush(
+                t_end,
+                &core::Parameters {
+                    amplitude_threshold,
+                    autocorrelation_threshold,
+                    frequency_multiplier,
+                },
+            )
+        })?;
+        self.record_results();
+        self.call_callback(python)?;
+        Ok(self.publish_samples(python))
+    }
+
+    // Processes a recording chunk by chunk without loading it in memory and returns the samples
+    // of the whole file. progress is called after each chunk with the number of events processed
+    // so far and the timestamp of the last event (µs). The per-sample accessors (confidences,
+    // phases...) describe the last chunk only.
+    #[pyo3(signature = (
+        path,
+        chunk_length = None,
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+        progress = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn process_file<'py>(
+        &mut self,
+        python: Python<'py>,
+        path: std::path::PathBuf,
+        chunk_length: Option<usize>,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+        progress: Option<PyObject>,
+    ) -> PyResult<pyo3::Bound<'py, pyo3::PyAny>> {
+        let mut recording = Recording::new(&path, chunk_length)?;
+        let parameters = core::Parameters {
+            amplitude_threshold,
+            autocorrelation_threshold,
+            frequency_multiplier,
+        };
+        let mut samples = Vec::new();
+        let mut events_count = 0u64;
+        loop {
+            let estimator = &mut self.estimator;
+            let Some(last_t) = python.allow_threads(|| -> PyResult<Option<Option<u64>>> {
+                let Some(events) = recording.next_events()? else {
+                    return Ok(None);
+                };
+                events_count += events.len() as u64;
+                let last_t = events.last().map(|event| event.t);
+                estimator.process(&events, &parameters)?;
+                Ok(Some(last_t))
+            })?
+            else {
+                break;
+            };
+            #[cfg(feature = "publisher")]
+            if let Some(publisher) = self.publisher.as_ref() {
+                publisher.publish(self.estimator.samples());
+            }
+            self.record_results();
+            self.call_callback(python)?;
+            samples.extend(self.estimator.samples().iter().map(|sample| RpmSample {
+                t: sample.t,
+                rpm: sample.rpm as f64,
+            }));
+            if let Some(progress) = progress.as_ref() {
+                progress.call1(python, (events_count, last_t))?;
+            }
+        }
+        Ok(slice_to_array(python, ArrayType::Rpm, &samples))
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.estimator.configuration().width
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.estimator.configuration().height
+    }
+
+    // spectrum and autocorrelation arrays passed to process must have fft_samples *
+    // zero_padding / 2 elements.
+    #[getter]
+    fn fft_samples(&self) -> usize {
+        self.estimator.configuration().fft_samples
+    }
+
+    // Hz, spacing of the spectrum bins.
+    #[getter]
+    fn frequency_resolution(&self) -> f64 {
+        self.estimator.frequency_resolution()
+    }
+
+    // Hz, samples per second (or None if samples are triggered by events_per_sample).
+    #[getter]
+    fn sampling_frequency(&self) -> Option<f64> {
+        let configuration = self.estimator.configuration();
+        configuration
+            .events_per_sample
+            .is_none()
+            .then_some(configuration.sampling_frequency)
+    }
+
+    // Nyquist frequency in RPM, before frequency_multiplier.
+    #[getter]
+    fn max_measurable_rpm(&self) -> f64 {
+        self.estimator.maximum_frequency() * 60.0
+    }
+
+    // Effective configuration (hop_duration is read back as sampling_frequency).
+    fn config(&self) -> RpmConfig {
+        RpmConfig {
+            configuration: self.estimator.configuration(),
+        }
+    }
+
+    fn confidences(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.confidence)
+            .collect()
+    }
+
+    fn smoothed_rpms(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.smoothed_rpm)
+            .collect()
+    }
+
+    fn phases(&self) -> Vec<Option<f32>> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.phase)
+            .collect()
+    }
+
+    fn shaft_frequencies(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.shaft_frequency)
+            .collect()
+    }
+
+    fn blade_counts(&self) -> Vec<usize> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.blade_count)
+            .collect()
+    }
+
+    // Fraction of ON events since the previous sample (NaN if there were none).
+    fn on_ratios(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.window.on_ratio)
+            .collect()
+    }
+
+    // events/s since the previous sample.
+    fn event_rates(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.window.event_rate)
+            .collect()
+    }
+
+    // flips/s since the previous sample registered by the neighbourhood and transition sign
+    // detectors (see RpmConfig.sign_detection), NaN in vibration mode.
+    fn flip_rates(&self) -> Vec<(f32, f32)> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| {
+                (
+                    sample.window.neighbourhood_flip_rate,
+                    sample.window.transition_flip_rate,
+                )
+            })
+            .collect()
+    }
+
+    // pixels, RMS distance of the events since the previous sample to their centroid.
+    fn spreads(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.window.spread)
+            .collect()
+    }
+
+    // (x, y, radius) of the spinner in sensor pixels, estimated from the timelines' activity.
+    fn tracks(&self) -> Vec<Option<(f32, f32, f32)>> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.track.map(|track| (track.x, track.y, track.radius)))
+            .collect()
+    }
+
+    // m/s, blade tip speed (NaN if no scale was set, see set_scale).
+    fn tip_speeds(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.tip_speed)
+            .collect()
+    }
+
+    // True if the sample's estimate comes from the low frequency analysis (see
+    // RpmConfig.low_frequency).
+    fn low_frequency_estimates(&self) -> Vec<bool> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.low_frequency)
+            .collect()
+    }
+
+    // In [0, 1], larger values hint at an unbalanced rotor or a bent blade, NaN if
+    // RpmConfig.blades is None or if the sample has no frequency.
+    fn imbalance_indices(&self) -> Vec<f32> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.imbalance_index)
+            .collect()
+    }
+
+    // "clockwise" or "counterclockwise" as seen by the sensor, None if unknown.
+    fn directions(&self) -> Vec<Option<String>> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.direction.map(|direction| direction.to_string()))
+            .collect()
+    }
+
+    // Degradations applied to each sample (see RpmConfig.max_latency_ms), among
+    // "fewer_timelines", "skipped_windows", and "coarse_grid". The list is empty if the sample
+    // was computed without degradation.
+    fn degradations(&self) -> Vec<Vec<String>> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| {
+                sample
+                    .degradation
+                    .applied()
+                    .iter()
+                    .map(|degradation| degradation.to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    // (t, kind) changes detected during the last call (see RpmConfig.change_threshold), kind is
+    // "acceleration", "deceleration", "stop", "start", or "reversal".
+    fn changes(&self) -> Vec<(u64, String)> {
+        self.estimator
+            .changes()
+            .iter()
+            .map(|change| (change.t, change.kind.to_string()))
+            .collect()
+    }
+
+    // Ellipse fitted to the outline of the active area, per sample.
+    fn geometry(&self) -> Vec<Option<Geometry>> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.geometry.as_ref().map(Geometry::from))
+            .collect()
+    }
+
+    // Hz, lighting flicker frequency detected since the last reset (None if flicker_rejection
+    // is None or if no flicker was detected).
+    fn flicker_frequency(&self) -> Option<f64> {
+        self.estimator.flicker_frequency()
+    }
+
+    // Number of active timelines whose spectra were summed by each sample.
+    fn timelines_counts(&self) -> Vec<usize> {
+        self.estimator
+            .samples()
+            .iter()
+            .map(|sample| sample.timelines)
+            .collect()
+    }
+
+    fn spectrum<'py>(&self, python: Python<'py>) -> Bound<'py, numpy::PyArray1<f32>> {
+        numpy::PyArray1::from_slice(python, self.estimator.spectrum())
+    }
+
+    // Frequency of each spectrum bin (Hz), or RPM (frequency * 60 * frequency_multiplier) if rpm
+    // is set.
+    #[pyo3(signature = (rpm = false, frequency_multiplier = 1.0))]
+    fn frequencies<'py>(
+        &self,
+        python: Python<'py>,
+        rpm: bool,
+        frequency_multiplier: f64,
+    ) -> Bound<'py, numpy::PyArray1<f64>> {
+        let mut frequencies = self.estimator.frequencies();
+        if rpm {
+            for frequency in frequencies.iter_mut() {
+                *frequency *= 60.0 * frequency_multiplier;
+            }
+        }
+        numpy::PyArray1::from_vec(python, frequencies)
+    }
+
+    fn autocorrelation<'py>(&self, python: Python<'py>) -> Bound<'py, numpy::PyArray1<f32>> {
+        numpy::PyArray1::from_slice(python, self.estimator.autocorrelation())
+    }
+
+    fn autocorrelation_detections<'py>(
+        &self,
+        python: Python<'py>,
+    ) -> Bound<'py, numpy::PyArray1<f32>> {
+        numpy::PyArray1::from_slice(
+            python,
+            &self.estimator.autocorrelation_detections().to_array(),
+        )
+    }
+
+    // Returns the spectra of the most active timelines at the last sample as a 2D array (one row
+    // per timeline, by decreasing activity) and the top-left pixel of each timeline's block.
+    #[allow(clippy::type_complexity)]
+    fn timeline_spectra<'py>(
+        &self,
+        python: Python<'py>,
+    ) -> PyResult<(Bound<'py, numpy::PyArray2<f32>>, Vec<(u16, u16)>)> {
+        let timeline_spectra = self.estimator.timeline_spectra();
+        let spectrum_length = self.estimator.spectrum().len();
+        let spectra = numpy::PyArray1::from_iter(
+            python,
+            timeline_spectra
+                .iter()
+                .flat_map(|timeline_spectrum| timeline_spectrum.spectrum.iter().copied()),
+        )
+        .reshape([timeline_spectra.len(), spectrum_length])?;
+        Ok((
+            spectra,
+            timeline_spectra
+                .iter()
+                .map(|timeline_spectrum| (timeline_spectrum.x, timeline_spectrum.y))
+                .collect(),
+        ))
+    }
+
+    fn timeline_overflows(&self) -> u64 {
+        self.estimator.timeline_overflows()
+    }
+
+    fn stats(&self) -> Statistics {
+        self.estimator.statistics().into()
+    }
+
+    // Records the duration of the process, ranking, transform, and peak stages of the following
+    // calls (see profiling::Stage). Profiling is off by default since it reads the clock
+    // several times per sample. reset clears the timings.
+    fn enable_profiling(&mut self) {
+        self.estimator.set_profiling(true);
+    }
+
+    fn disable_profiling(&mut self) {
+        self.estimator.set_profiling(false);
+    }
+
+    // Timings per stage name since profiling was enabled (all zero if it never was).
+    fn profile(&self) -> std::collections::HashMap<String, Timing> {
+        profiling::Stage::ALL
+            .iter()
+            .map(|stage| (stage.to_string(), self.estimator.timing(*stage).into()))
+            .collect()
+    }
+
+    // RGB image of the events of the last t_window µs with the used timelines and the clusters
+    // of the last sample (see render::render). The shape is (height, width, 3).
+    #[pyo3(signature = (t_window))]
+    fn render<'py>(
+        &self,
+        python: Python<'py>,
+        t_window: u64,
+    ) -> PyResult<Bound<'py, numpy::PyArray3<u8>>> {
+        let configuration = self.estimator.configuration();
+        numpy::PyArray1::from_vec(python, render::render(&self.estimator, t_window)).reshape([
+            configuration.height as usize,
+            configuration.width as usize,
+            3,
+        ])
+    }
+
+    // Phase-folded images of the last strobe_length accepted events, see strobe::strobe.
+    fn strobe_image<'py>(
+        &self,
+        python: Python<'py>,
+        bins: usize,
+    ) -> PyResult<Bound<'py, numpy::PyArray3<u8>>> {
+        let configuration = self.estimator.configuration();
+        numpy::PyArray1::from_vec(python, strobe::strobe(&self.estimator, bins)?).reshape([
+            bins,
+            configuration.height as usize,
+            configuration.width as usize,
+        ])
+    }
+
+    // The shape is (height, width) after spatial downsampling.
+    fn activity_map<'py>(&self, python: Python<'py>) -> PyResult<Bound<'py, numpy::PyArray2<f32>>> {
+        let (width, height) = self.estimator.downsampled_dimensions();
+        numpy::PyArray1::from_vec(python, self.estimator.activity_map())
+            .reshape([height as usize, width as usize])
+    }
+
+    fn reset(&mut self) {
+        self.estimator.reset();
+        self.tachometer.reset();
+        self.gyroscope.reset();
+        self.results.clear();
+    }
+
+    // Calls callback(t, rpm, confidence) for every sample, or callback(t, rpm, confidence,
+    // spectrum) if spectrum is set, after each process, process_file chunk, and flush call
+    // (with the GIL held). The callback must not call the calculator's methods, and exceptions
+    // that it raises are propagated. None removes the callback.
+    #[pyo3(signature = (callback, spectrum = false))]
+    fn set_callback(&mut self, callback: Option<PyObject>, spectrum: bool) {
+        self.callback = callback.map(|function| Callback { function, spectrum });
+        self.update_record_spectra();
+    }
+
+    // Writes the samples accumulated since the last reset (t in µs, rpm, and confidence) and
+    // the spectrogram if record_spectrogram is set. format is "csv" or "parquet".
+    #[pyo3(signature = (path, format = "csv"))]
+    fn export_results(
+        &self,
+        python: Python,
+        path: std::path::PathBuf,
+        format: &str,
+    ) -> PyResult<()> {
+        let format: export::Format = format.parse()?;
+        let results = &self.results;
+        Ok(python.allow_threads(|| results.write(path, format))?)
+    }
+
+    // Samples accumulated since the last reset and their region estimates as an Arrow record
+    // batch with the columns t (µs), rpm, confidence, and region (null for the whole sensor).
+    // The returned object implements the Arrow PyCapsule interface, for instance
+    // polars.DataFrame(calculator.results_to_arrow()) or pyarrow.record_batch(...).
+    fn results_to_arrow(&self) -> PyResult<ArrowRecordBatch> {
+        Ok(ArrowRecordBatch {
+            record_batch: self.results.to_record_batch()?,
+        })
+    }
+
+    // Writes the samples accumulated since the last reset, the spectrogram (if
+    // record_spectrogram is set), the configuration, and the statistics to an HDF5 file.
+    #[cfg(feature = "hdf5")]
+    #[pyo3(signature = (path))]
+    fn save_hdf5(&self, python: Python, path: std::path::PathBuf) -> PyResult<()> {
+        let config = Bound::new(python, self.config())?;
+        let mut configuration = Vec::with_capacity(RPM_CONFIG_FIELDS.len());
+        for name in RPM_CONFIG_FIELDS {
+            configuration.push((name, config.getattr(name)?.str()?.to_string()));
+        }
+        let frequencies = self.estimator.frequencies();
+        let statistics = self.estimator.statistics();
+        let results = &self.results;
+        Ok(python.allow_threads(|| {
+            results.write_hdf5(path, &frequencies, &configuration, &statistics)
+        })?)
+    }
+
+    // Pickle support. The tachometer, the gyroscope (and the publisher, if any) are not part of the state.
+    fn __getstate__<'py>(&self, python: Python<'py>) -> Bound<'py, pyo3::types::PyBytes> {
+        pyo3::types::PyBytes::new(python, &self.estimator.to_state())
+    }
+
+    fn __setstate__(&mut self, state: &[u8]) -> PyResult<()> {
+        let record_spectrogram = self.results.records_spectrogram();
+        let record_flips = self.estimator.record_flips();
+        self.estimator = core::RpmEstimator::from_state(state)?;
+        self.estimator.set_record_flips(record_flips);
+        self.tachometer.reset();
+        self.gyroscope.reset();
+        self.results =
+            export::Results::new(record_spectrogram.then(|| self.estimator.spectrum().len()));
+        self.update_record_spectra();
+        Ok(())
+    }
+
+    #[pyo3(signature = (path))]
+    fn save_state(&self, path: std::path::PathBuf) -> PyResult<()> {
+        Ok(std::fs::write(path, self.estimator.to_state())?)
+    }
+
+    #[pyo3(signature = (path))]
+    fn load_state(&mut self, path: std::path::PathBuf) -> PyResult<()> {
+        self.__setstate__(&std::fs::read(path)?)
+    }
+
+    // Deep copy of the calculator (estimator state, tachometer, gyroscope, and accumulated
+    // results). A calculator processes one packet at a time (concurrent calls raise an
+    // exception), so threads that analyse several streams should each use a clone of a warmed-up
+    // calculator. The callbacks and the publisher are not copied.
+    fn clone(&self) -> Self {
+        let mut calculator = Self {
+            estimator: self.estimator.clone(),
+            tachometer: self.tachometer.clone(),
+            gyroscope: self.gyroscope.clone(),
+            results: self.results.clone(),
+            callback: None,
+            alarm_callback: None,
+            #[cfg(feature = "publisher")]
+            publisher: None,
+        };
+        calculator.update_record_spectra();
+        calculator
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    #[pyo3(signature = (_memo))]
+    fn __deepcopy__(&self, _memo: &Bound<'_, PyAny>) -> Self {
+        self.clone()
+    }
+
+    // Compares the samples of the last process call with the reference RPM computed from
+    // rising edges. triggers must be an EvtTrigger array (optionally filtered by source) or an
+    // AedatTrigger array (external rising edges and pulses are used).
+    #[pyo3(signature = (
+        triggers,
+        pulses_per_revolution = tachometer::DEFAULT_PULSES_PER_REVOLUTION,
+        source = None,
+        strict = true,
+    ))]
+    #[allow(clippy::type_complexity)]
+    fn process_triggers(
+        &mut self,
+        python: Python,
+        triggers: &pyo3::Bound<'_, pyo3::types::PyAny>,
+        pulses_per_revolution: u32,
+        source: Option<u8>,
+        strict: bool,
+    ) -> PyResult<Vec<(f32, Option<f32>, Option<f32>)>> {
+        let rising_ts = rising_edges(python, triggers, source, strict)?;
+        Ok(self
+            .tachometer
+            .compare(self.estimator.samples(), &rising_ts, pulses_per_revolution)?
+            .iter()
+            .map(|comparison| (comparison.estimated, comparison.reference, comparison.error))
+            .collect())
+    }
+
+    // Compares the samples of the last process call with the reference RPM computed from the
+    // gyroscope's z axis, for cameras mounted on the rotating platform. imu must be an AedatImu
+    // array.
+    #[pyo3(signature = (imu, strict = true))]
+    #[allow(clippy::type_complexity)]
+    fn process_imu(
+        &mut self,
+        python: Python,
+        imu: &pyo3::Bound<'_, pyo3::types::PyAny>,
+        strict: bool,
+    ) -> PyResult<Vec<(f32, Option<f32>, Option<f32>)>> {
+        let (array, length, layout) = check_array(python, ArrayType::AedatImu, imu, strict)?;
+        let rates: Vec<(u64, f32)> =
+            unsafe { RawRecords::<aedat::Imu>::new(python, array, length, &layout) }
+                .map(|sample| (sample.t, sample.gyroscope[2]))
+                .collect();
+        Ok(self
+            .gyroscope
+            .compare(self.estimator.samples(), &rates)
+            .iter()
+            .map(|comparison| (comparison.estimated, comparison.reference, comparison.error))
+            .collect())
+    }
+
+    #[pyo3(signature = (x0, y0, x1, y1))]
+    fn set_roi(&mut self, x0: u16, y0: u16, x1: u16, y1: u16) -> PyResult<()> {
+        Ok(self.estimator.set_roi(Some(core::Roi { x0, y0, x1, y1 }))?)
+    }
+
+    fn clear_roi(&mut self) -> PyResult<()> {
+        Ok(self.estimator.set_roi(None)?)
+    }
+
+    // (x0, y0, x1, y1), the whole sensor if no ROI was set. The ROI moves if roi_tracking is
+    // enabled.
+    fn roi(&self) -> (u16, u16, u16, u16) {
+        let roi = self.estimator.roi();
+        (roi.x0, roi.y0, roi.x1, roi.y1)
+    }
+
+    // Regions are estimated independently, see region_rpms.
+    #[pyo3(signature = (name, x0, y0, x1, y1))]
+    fn add_region(&mut self, name: &str, x0: u16, y0: u16, x1: u16, y1: u16) -> PyResult<()> {
+        Ok(self
+            .estimator
+            .add_region(name, core::Roi { x0, y0, x1, y1 })?)
+    }
+
+    fn remove_region(&mut self, name: &str) -> PyResult<()> {
+        Ok(self.estimator.remove_region(name)?)
+    }
+
+    fn regions(&self) -> Vec<(String, u16, u16, u16, u16)> {
+        self.estimator
+            .regions()
+            .iter()
+            .map(|region| {
+                (
+                    region.name.clone(),
+                    region.roi.x0,
+                    region.roi.y0,
+                    region.roi.x1,
+                    region.roi.y1,
+                )
+            })
+            .collect()
+    }
+
+    // One dict per sample of the last process call, mapping region names to RPMs (in insertion
+    // order).
+    fn region_rpms<'py>(
+        &self,
+        python: Python<'py>,
+    ) -> PyResult<Vec<Bound<'py, pyo3::types::PyDict>>> {
+        let regions = self.estimator.regions();
+        self.estimator
+            .regions_samples()
+            .iter()
+            .map(|regions_samples| {
+                let rpms = pyo3::types::PyDict::new(python);
+                for (region, region_sample) in regions.iter().zip(regions_samples.iter()) {
+                    rpms.set_item(&region.name, region_sample.rpm)?;
+                }
+                Ok(rpms)
+            })
+            .collect()
+    }
+
+    #[pyo3(signature = (mask))]
+    fn set_mask(&mut self, mask: Option<numpy::PyReadonlyArray2<'_, bool>>) -> PyResult<()> {
+        match mask {
+            Some(mask) => {
+                let configuration = self.estimator.configuration();
+                let shape = mask.shape();
+                if shape[0] != configuration.height as usize
+                    || shape[1] != configuration.width as usize
+                {
+                    return Err(pyo3::exceptions::PyException::new_err(format!(
+                        "mask must have the shape ({}, {}) (got ({}, {}))",
+                        configuration.height, configuration.width, shape[0], shape[1]
+                    )));
+                }
+                Ok(self
+                    .estimator
+                    .set_mask(Some(mask.as_array().iter().copied().collect()))?)
+            }
+            None => Ok(self.estimator.set_mask(None)?),
+        }
+    }
+
+    // Tip speeds (see tip_speeds) are computed from the fitted rotor geometry and
+    // meters_per_pixel, or from the physical rotor_radius (m). Both None disable tip speeds.
+    #[pyo3(signature = (meters_per_pixel = None, *, rotor_radius = None))]
+    fn set_scale(
+        &mut self,
+        meters_per_pixel: Option<f64>,
+        rotor_radius: Option<f64>,
+    ) -> PyResult<()> {
+        let scale = match (meters_per_pixel, rotor_radius) {
+            (Some(meters_per_pixel), None) => Some(core::Scale::MetersPerPixel(meters_per_pixel)),
+            (None, Some(rotor_radius)) => Some(core::Scale::RotorRadius(rotor_radius)),
+            (None, None) => None,
+            (Some(_), Some(_)) => {
+                return Err(pyo3::exceptions::PyException::new_err(
+                    "meters_per_pixel cannot be combined with rotor_radius",
+                ));
+            }
+        };
+        Ok(self.estimator.set_scale(scale)?)
+    }
+
+    // Raises an alarm when the (smoothed) RPM leaves [min_rpm, max_rpm], cleared once the RPM
+    // is back within the bounds by hysteresis (see core::alarm::Alarm). The alarm is evaluated
+    // in Rust for every sample, callback(t, state) is called for every transition after each
+    // process call (with the GIL held). Both bounds None remove the alarm.
+    #[pyo3(signature = (min_rpm = None, max_rpm = None, hysteresis = 0.0, callback = None))]
+    fn set_alarm(
+        &mut self,
+        min_rpm: Option<f32>,
+        max_rpm: Option<f32>,
+        hysteresis: f32,
+        callback: Option<PyObject>,
+    ) -> PyResult<()> {
+        if min_rpm.is_none() && max_rpm.is_none() {
+            self.estimator.set_alarm(None)?;
+            self.alarm_callback = None;
+            return Ok(());
+        }
+        self.estimator.set_alarm(Some(alarm::Alarm {
+            min_rpm,
+            max_rpm,
+            hysteresis,
+        }))?;
+        self.alarm_callback = callback;
+        Ok(())
+    }
+
+    // Degradation level of the next call, "none", "fewer_timelines", "skipped_windows", or
+    // "coarse_grid" (each level includes the previous ones).
+    #[getter]
+    fn degradation(&self) -> String {
+        self.estimator.degradation().to_string()
+    }
+
+    // "normal", "low", or "high".
+    #[getter]
+    fn alarm_state(&self) -> String {
+        self.estimator.alarm_state().to_string()
+    }
+
+    // (t, state) alarm transitions during the last call.
+    fn alarm_transitions(&self) -> Vec<(u64, String)> {
+        self.estimator
+            .alarm_transitions()
+            .iter()
+            .map(|transition| (transition.t, transition.state.to_string()))
+            .collect()
+    }
+
+    // Publishes the samples computed by subsequent process calls.
+    #[cfg(feature = "publisher")]
+    fn attach_publisher(&mut self, publisher: PyRef<'_, Publisher>) {
+        self.publisher = Some(publisher.publisher.clone());
+    }
+
+    #[cfg(feature = "publisher")]
+    fn detach_publisher(&mut self) {
+        self.publisher = None;
+    }
+
+    fn hot_pixels(&self) -> Vec<(u16, u16)> {
+        self.estimator.hot_pixels()
+    }
+
+    #[pyo3(signature = (x, y, hot))]
+    fn set_hot_pixel(&mut self, x: u16, y: u16, hot: Option<bool>) -> PyResult<()> {
+        Ok(self.estimator.set_hot_pixel(x, y, hot)?)
+    }
+
+    // Returns (blade_count, confidence) for the last sample, or None.
+    #[pyo3(signature = (maximum_blade_count = core::DEFAULT_MAXIMUM_BLADE_COUNT))]
+    fn estimate_blade_count(&self, maximum_blade_count: usize) -> PyResult<Option<(usize, f32)>> {
+        Ok(self
+            .estimator
+            .estimate_blade_count(maximum_blade_count)?
+            .map(|blade_count| (blade_count.blade_count, blade_count.confidence)))
+    }
+
+    fn clusters(&self) -> Vec<Vec<Cluster>> {
+        self.estimator
+            .clusters()
+            .iter()
+            .map(|clusters| clusters.iter().map(Cluster::from).collect())
+            .collect()
+    }
+
+    // Sign flips (t in µs, and x and y of the top-left pixel of the flipping block) recorded
+    // since the previous all_flips or flips call, as a Flip array. Every flip that feeds the
+    // timelines is reported, including those of timelines that are not ranked. Each block keeps
+    // its last RpmConfig.flips_capacity flips (see Statistics.dropped_flips).
+    fn all_flips<'py>(&mut self, python: Python<'py>) -> pyo3::Bound<'py, pyo3::PyAny> {
+        slice_to_array(python, ArrayType::Flip, &self.estimator.take_flips())
+    }
+
+    // Same as all_flips for the block that contains the pixel (x, y), the flips of other blocks
+    // are kept for later calls.
+    #[pyo3(signature = (x, y))]
+    fn flips<'py>(
+        &mut self,
+        python: Python<'py>,
+        x: u16,
+        y: u16,
+    ) -> PyResult<pyo3::Bound<'py, pyo3::PyAny>> {
+        Ok(slice_to_array(
+            python,
+            ArrayType::Flip,
+            &self.estimator.take_block_flips(x, y)?,
+        ))
+    }
+
+    // One list of bins per sample (see RpmConfig.radial_bins), empty if the rotor was not
+    // located.
+    fn radial_profiles(&self) -> Vec<Vec<RadialBin>> {
+        self.estimator
+            .radial_profiles()
+            .iter()
+            .map(|profile| profile.iter().map(RadialBin::from).collect())
+            .collect()
+    }
+}
+
+impl RpmCalculator {
+    fn record_results(&mut self) {
+        self.results
+            .push(self.estimator.samples(), self.estimator.spectra());
+        self.results
+            .push_regions(self.estimator.regions(), self.estimator.regions_samples());
+    }
+
+    // Spectra are recorded for the spectrogram and for callbacks that expect them.
+    fn update_record_spectra(&mut self) {
+        self.estimator.set_record_spectra(
+            self.results.records_spectrogram()
+                || self
+                    .callback
+                    .as_ref()
+                    .is_some_and(|callback| callback.spectrum),
+        );
+    }
+
+    fn call_callback(&self, python: Python) -> PyResult<()> {
+        if let Some(alarm_callback) = self.alarm_callback.as_ref() {
+            for transition in self.estimator.alarm_transitions() {
+                alarm_callback.call1(python, (transition.t, transition.state.to_string()))?;
+            }
+        }
+        let Some(callback) = self.callback.as_ref() else {
+            return Ok(());
+        };
+        let spectrum_length = self.estimator.spectrum().len();
+        for (index, sample) in self.estimator.samples().iter().enumerate() {
+            if callback.spectrum {
+                let spectrum = numpy::PyArray1::from_slice(
+                    python,
+                    &self.estimator.spectra()
+                        [index * spectrum_length..(index + 1) * spectrum_length],
+                );
+                callback
+                    .function
+                    .call1(python, (sample.t, sample.rpm, sample.confidence, spectrum))?;
+            } else {
+                callback
+                    .function
+                    .call1(python, (sample.t, sample.rpm, sample.confidence))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Publishes the samples of the last process call (if a publisher is attached) and returns
+    // them as an Rpm array, or None if there are no samples.
+    fn publish_samples(&self, python: Python) -> Option<PyObject> {
+        let samples = self.estimator.samples();
+        #[cfg(feature = "publisher")]
+        if let Some(publisher) = self.publisher.as_ref() {
+            publisher.publish(samples);
+        }
+        if samples.is_empty() {
+            None
+        } else {
+            let samples: Vec<RpmSample> = samples
+                .iter()
+                .map(|sample| RpmSample {
+                    t: sample.t,
+                    rpm: sample.rpm as f64,
+                })
+                .collect();
+            Some(slice_to_array(python, ArrayType::Rpm, &samples).unbind())
+        }
+    }
+}
+
+// Fuses several cameras observing the same rotor. The cameras are stacked on a single sensor
+// (see multi::Synchronizer) processed by one RpmCalculator, so the most active timelines are
+// selected across cameras and their spectra are summed. The keyword arguments are passed to
+// RpmCalculator (except width and height, which are computed from the cameras).
+#[pyclass]
+pub struct MultiRpmCalculator {
+    synchronizer: multi::Synchronizer,
+    calculator: Py<RpmCalculator>,
+    events: Vec<core::Event>,
+}
+
+#[pymethods]
+impl MultiRpmCalculator {
+    #[new]
+    #[pyo3(signature = (cameras, offsets = None, **configuration))]
+    fn new(
+        python: Python,
+        cameras: Vec<(u16, u16)>,
+        offsets: Option<Vec<i64>>,
+        configuration: Option<&pyo3::Bound<'_, pyo3::types::PyDict>>,
+    ) -> PyResult<Self> {
+        let offsets = offsets.unwrap_or_else(|| vec![0; cameras.len()]);
+        if offsets.len() != cameras.len() {
+            return Err(multi::Error::OffsetsLength {
+                expected: cameras.len(),
+                actual: offsets.len(),
+            }
+            .into());
+        }
+        let configuration = match configuration {
+            Some(configuration) => configuration.copy()?,
+            None => pyo3::types::PyDict::new(python),
+        };
+        for key in ["width", "height"] {
+            if configuration.contains(key)? {
+                return Err(pyo3::exceptions::PyException::new_err(format!(
+                    "{key} is computed from the cameras and cannot be set"
+                )));
+            }
+        }
+        let spatial_downsampling = match configuration.get_item("spatial_downsampling")? {
+            Some(spatial_downsampling) => spatial_downsampling.extract()?,
+            None => core::DEFAULT_SPATIAL_DOWNSAMPLING,
+        };
+        let sign_check_radius = match configuration.get_item("sign_check_radius")? {
+            Some(sign_check_radius) => sign_check_radius.extract()?,
+            None => core::DEFAULT_SIGN_CHECK_RADIUS,
+        };
+        let synchronizer = multi::Synchronizer::new(
+            cameras
+                .into_iter()
+                .zip(offsets)
+                .map(|((width, height), offset)| multi::Camera {
+                    width,
+                    height,
+                    offset,
+                })
+                .collect(),
+            spatial_downsampling,
+            sign_check_radius,
+        )?;
+        configuration.set_item("width", synchronizer.width())?;
+        configuration.set_item("height", synchronizer.height())?;
+        let calculator = python
+            .get_type::<RpmCalculator>()
+            .call((), Some(&configuration))?
+            .downcast_into::<RpmCalculator>()?
+            .unbind();
+        Ok(Self {
+            synchronizer,
+            calculator,
+            events: Vec::new(),
+        })
+    }
+
+    // The underlying calculator, for spectra, confidences, and other per-sample outputs.
+    #[getter]
+    fn calculator(&self, python: Python) -> Py<RpmCalculator> {
+        self.calculator.clone_ref(python)
+    }
+
+    // Clock offset of each camera (µs).
+    #[getter]
+    fn offsets(&self) -> Vec<i64> {
+        self.synchronizer
+            .cameras()
+            .iter()
+            .map(|camera| camera.offset)
+            .collect()
+    }
+
+    // packets must contain one event array per camera (possibly empty). Events are processed
+    // once every camera has reached their timestamp, call flush after the last packets.
+    #[pyo3(signature = (
+        packets,
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+        strict = true,
+    ))]
+    fn process(
+        &mut self,
+        python: Python,
+        packets: Vec<pyo3::Bound<'_, pyo3::types::PyAny>>,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+        strict: bool,
+    ) -> PyResult<Option<PyObject>> {
+        let cameras_count = self.synchronizer.cameras().len();
+        if packets.len() != cameras_count {
+            return Err(multi::Error::PacketsLength {
+                expected: cameras_count,
+                actual: packets.len(),
+            }
+            .into());
+        }
+        for (camera, packet) in packets.iter().enumerate() {
+            let events = unsafe { raw_dvs_packets(python, packet, strict)? };
+            self.synchronizer.push(camera, events.into_iter().flatten());
+        }
+        self.events.clear();
+        self.synchronizer.drain(&mut self.events);
+        self.process_events(
+            python,
+            &core::Parameters {
+                amplitude_threshold,
+                autocorrelation_threshold,
+                frequency_multiplier,
+            },
+        )
+    }
+
+    // Processes the events held back by process.
+    #[pyo3(signature = (
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+    ))]
+    fn flush(
+        &mut self,
+        python: Python,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+    ) -> PyResult<Option<PyObject>> {
+        self.events.clear();
+        self.synchronizer.flush(&mut self.events);
+        self.process_events(
+            python,
+            &core::Parameters {
+                amplitude_threshold,
+                autocorrelation_threshold,
+                frequency_multiplier,
+            },
+        )
+    }
+
+    // The calculator is shared with Python (see calculator), hence it may be borrowed by another
+    // thread, in which case an exception is raised.
+    fn reset(&mut self, python: Python) -> PyResult<()> {
+        self.calculator.try_borrow_mut(python)?.reset();
+        self.synchronizer.reset();
+        Ok(())
+    }
+}
+
+impl MultiRpmCalculator {
+    fn process_events(
+        &mut self,
+        python: Python,
+        parameters: &core::Parameters,
+    ) -> PyResult<Option<PyObject>> {
+        let mut calculator = self.calculator.try_borrow_mut(python)?;
+        let estimator = &mut calculator.estimator;
+        let events = &self.events;
+        python.allow_threads(|| estimator.process(events, parameters))?;
+        calculator.record_results();
+        calculator.call_callback(python)?;
+        Ok(calculator.publish_samples(python))
+    }
+}
+
+#[pyclass]
+pub struct Aedat4Reader {
+    decoder: aedat4::Decoder,
+    stream: aedat4::Stream,
+}
+
+#[pymethods]
+impl Aedat4Reader {
+    #[new]
+    fn new(path: std::path::PathBuf) -> PyResult<Self> {
+        let decoder = aedat4::Decoder::new(path)?;
+        let stream = *decoder
+            .streams()
+            .first()
+            .ok_or(aedat4::Error::NoEventStream)?;
+        Ok(Self { decoder, stream })
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.stream.width
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.stream.height
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(
+        &mut self,
+        python: Python<'py>,
+    ) -> PyResult<Option<pyo3::Bound<'py, pyo3::PyAny>>> {
+        loop {
+            match self.decoder.next() {
+                Some(packet) => {
+                    let packet = packet?;
+                    if packet.stream_id == self.stream.id {
+                        return Ok(Some(slice_to_array(python, ArrayType::Dvs, &packet.events)));
+                    }
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+// Exports a record batch without copies through the Arrow PyCapsule interface, see
+// https://arrow.apache.org/docs/format/CDataInterface/PyCapsuleInterface.html.
+#[pyclass(frozen)]
+pub struct ArrowRecordBatch {
+    record_batch: arrow_array::RecordBatch,
+}
+
+#[pymethods]
+impl ArrowRecordBatch {
+    fn __len__(&self) -> usize {
+        self.record_batch.num_rows()
+    }
+
+    fn __arrow_c_schema__<'py>(
+        &self,
+        python: Python<'py>,
+    ) -> PyResult<Bound<'py, pyo3::types::PyCapsule>> {
+        let schema =
+            arrow_schema::ffi::FFI_ArrowSchema::try_from(self.record_batch.schema().as_ref())
+                .map_err(export::Error::from)?;
+        pyo3::types::PyCapsule::new(python, schema, Some(c"arrow_schema".to_owned()))
+    }
+
+    // requested_schema is ignored, consumers cast the batch if needed.
+    #[pyo3(signature = (requested_schema = None))]
+    fn __arrow_c_array__<'py>(
+        &self,
+        python: Python<'py>,
+        requested_schema: Option<PyObject>,
+    ) -> PyResult<(
+        Bound<'py, pyo3::types::PyCapsule>,
+        Bound<'py, pyo3::types::PyCapsule>,
+    )> {
+        let _ = requested_schema;
+        let struct_array = arrow_array::StructArray::from(self.record_batch.clone());
+        let (array, schema) = arrow_array::ffi::to_ffi(&arrow_array::Array::to_data(&struct_array))
+            .map_err(export::Error::from)?;
+        Ok((
+            pyo3::types::PyCapsule::new(python, schema, Some(c"arrow_schema".to_owned()))?,
+            pyo3::types::PyCapsule::new(python, array, Some(c"arrow_array".to_owned()))?,
+        ))
+    }
+
+    #[pyo3(signature = (requested_schema = None))]
+    fn __arrow_c_stream__<'py>(
+        &self,
+        python: Python<'py>,
+        requested_schema: Option<PyObject>,
+    ) -> PyResult<Bound<'py, pyo3::types::PyCapsule>> {
+        let _ = requested_schema;
+        let reader = arrow_array::RecordBatchIterator::new(
+            [Ok(self.record_batch.clone())],
+            self.record_batch.schema(),
+        );
+        pyo3::types::PyCapsule::new(
+            python,
+            arrow_array::ffi_stream::FFI_ArrowArrayStream::new(Box::new(reader)),
+            Some(c"arrow_array_stream".to_owned()),
+        )
+    }
+}
+
+#[pyclass]
+pub struct AedatReader {
+    decoder: aedat::Decoder,
+}
+
+#[pymethods]
+impl AedatReader {
+    #[new]
+    #[pyo3(signature = (path, chunk_length = aedat::DEFAULT_CHUNK_LENGTH))]
+    fn new(path: std::path::PathBuf, chunk_length: usize) -> PyResult<Self> {
+        Ok(Self {
+            decoder: aedat::Decoder::new(path, chunk_length)?,
+        })
+    }
+
+    #[getter]
+    fn version(&self) -> String {
+        self.decoder.version().to_string()
+    }
+
+    #[getter]
+    fn width(&self) -> Option<u16> {
+        self.decoder.width()
+    }
+
+    #[getter]
+    fn height(&self) -> Option<u16> {
+        self.decoder.height()
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    // Returns a (events, imu) tuple of Dvs and AedatImu arrays.
+    #[allow(clippy::type_complexity)]
+    fn __next__<'py>(
+        &mut self,
+        python: Python<'py>,
+    ) -> PyResult<Option<(pyo3::Bound<'py, pyo3::PyAny>, pyo3::Bound<'py, pyo3::PyAny>)>> {
+        match self.decoder.next() {
+            Some(chunk) => {
+                let chunk = chunk?;
+                Ok(Some((
+                    slice_to_array(python, ArrayType::Dvs, &chunk.events),
+                    slice_to_array(python, ArrayType::AedatImu, &chunk.imu),
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[pyclass]
+pub struct Evt3Reader {
+    decoder: evt3::Decoder,
+}
+
+#[pymethods]
+impl Evt3Reader {
+    #[new]
+    #[pyo3(signature = (path, chunk_length = evt3::DEFAULT_CHUNK_LENGTH))]
+    fn new(path: std::path::PathBuf, chunk_length: usize) -> PyResult<Self> {
+        Ok(Self {
+            decoder: evt3::Decoder::new(path, chunk_length)?,
+        })
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.decoder.width()
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.decoder.height()
+    }
+
+    // "EVT2" or "EVT3", read from the header.
+    #[getter]
+    fn encoding(&self) -> String {
+        self.decoder.encoding().to_string()
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    // Returns a (events, triggers) tuple of Dvs and EvtTrigger arrays.
+    #[allow(clippy::type_complexity)]
+    fn __next__<'py>(
+        &mut self,
+        python: Python<'py>,
+    ) -> PyResult<Option<(pyo3::Bound<'py, pyo3::PyAny>, pyo3::Bound<'py, pyo3::PyAny>)>> {
+        match self.decoder.next() {
+            Some(chunk) => {
+                let chunk = chunk?;
+                Ok(Some((
+                    slice_to_array(python, ArrayType::Dvs, &chunk.events),
+                    slice_to_array(python, ArrayType::EvtTrigger, &chunk.triggers),
+                )))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[pyclass]
+pub struct DatReader {
+    decoder: dat::Decoder,
+}
+
+#[pymethods]
+impl DatReader {
+    #[new]
+    #[pyo3(signature = (path, chunk_length = dat::DEFAULT_CHUNK_LENGTH))]
+    fn new(path: std::path::PathBuf, chunk_length: usize) -> PyResult<Self> {
+        Ok(Self {
+            decoder: dat::Decoder::new(path, chunk_length)?,
+        })
+    }
+
+    #[getter]
+    fn version(&self) -> u8 {
+        self.decoder.version()
+    }
+
+    #[getter]
+    fn event_type(&self) -> u8 {
+        self.decoder.event_type()
+    }
+
+    #[getter]
+    fn width(&self) -> u16 {
+        self.decoder.width()
+    }
+
+    #[getter]
+    fn height(&self) -> u16 {
+        self.decoder.height()
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(
+        &mut self,
+        python: Python<'py>,
+    ) -> PyResult<Option<pyo3::Bound<'py, pyo3::PyAny>>> {
+        match self.decoder.next() {
+            Some(events) => Ok(Some(slice_to_array(python, ArrayType::Dat, &events?))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[pyclass]
+pub struct EsReader {
+    decoder: es::Decoder,
+}
+
+#[pymethods]
+impl EsReader {
+    #[new]
+    #[pyo3(signature = (path, chunk_length = es::DEFAULT_CHUNK_LENGTH))]
+    fn new(path: std::path::PathBuf, chunk_length: usize) -> PyResult<Self> {
+        Ok(Self {
+            decoder: es::Decoder::new(path, chunk_length)?,
+        })
+    }
+
+    #[getter]
+    fn version(&self) -> String {
+        let [major, minor, patch] = self.decoder.version();
+        format!("{major}.{minor}.{patch}")
+    }
+
+    #[getter]
+    fn event_type(&self) -> &'static str {
+        match self.decoder.event_type() {
+            es::EventType::Generic => "generic",
+            es::EventType::Dvs => "dvs",
+            es::EventType::Atis => "atis",
+            es::EventType::Color => "color",
+        }
+    }
+
+    #[getter]
+    fn width(&self) -> Option<u16> {
+        match self.decoder.event_type() {
+            es::EventType::Generic => None,
+            _ => Some(self.decoder.width()),
+        }
+    }
+
+    #[getter]
+    fn height(&self) -> Option<u16> {
+        match self.decoder.event_type() {
+            es::EventType::Generic => None,
+            _ => Some(self.decoder.height()),
+        }
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(
+        &mut self,
+        python: Python<'py>,
+    ) -> PyResult<Option<pyo3::Bound<'py, pyo3::PyAny>>> {
+        match self.decoder.next() {
+            Some(chunk) => Ok(Some(match chunk? {
+                es::Chunk::Generic(events) => generic_events_to_array(python, &events),
+                es::Chunk::Dvs(events) => slice_to_array(python, ArrayType::Dvs, &events),
+                es::Chunk::Atis(events) => slice_to_array(python, ArrayType::EsAtis, &events),
+                es::Chunk::Color(events) => slice_to_array(python, ArrayType::EsColor, &events),
+            })),
+            None => Ok(None),
+        }
+    }
+}
+
+#[pyclass]
+pub struct LiveRpm {
+    pipeline: live::Pipeline,
+    acquisition: Option<std::thread::JoinHandle<()>>,
+    configuration: core::Configuration,
+}
+
+#[pymethods]
+impl LiveRpm {
+    // The callback is called repeatedly on a background thread and must return a Dvs array
+    // (possibly empty), or None to end the stream.
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    #[pyo3(signature = (
+        callback,
+        config = None,
+        *,
+        amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+        autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+        frequency_multiplier = 1.0,
+        queue_capacity = live::DEFAULT_QUEUE_CAPACITY,
+        strict = true,
+        **configuration,
+    ))]
+    fn new(
+        python: Python,
+        callback: PyObject,
+        config: Option<RpmConfig>,
+        amplitude_threshold: f32,
+        autocorrelation_threshold: f32,
+        frequency_multiplier: f32,
+        queue_capacity: usize,
+        strict: bool,
+        configuration: Option<&Bound<'_, pyo3::types::PyDict>>,
+    ) -> PyResult<Self> {
+        let configuration = rpm_config(python, config, configuration)?;
+        let pipeline = live::Pipeline::new(
+            core::RpmEstimator::new(configuration)?,
+            core::Parameters {
+                amplitude_threshold,
+                autocorrelation_threshold,
+                frequency_multiplier,
+            },
+            queue_capacity,
+        )?;
+        let sender = pipeline.sender();
+        let acquisition = std::thread::spawn(move || {
+            while sender.state().running() {
+                let packet = Python::with_gil(|python| -> PyResult<Option<Vec<core::Event>>> {
+                    let packet = callback.call0(python)?;
+                    if packet.is_none(python) {
+                        return Ok(None);
+                    }
+                    Ok(Some(
+                        unsafe { raw_dvs_packets(python, packet.bind(python), strict)? }
+                            .into_iter()
+                            .flatten()
+                            .collect(),
+                    ))
+                });
+                match packet {
+                    Ok(Some(events)) => sender.push(events),
+                    Ok(None) => sender.state().stop(None),
+                    Err(error) => sender.state().stop(Some(error.to_string())),
+                }
+            }
+        });
+        Ok(Self {
+            pipeline,
+            acquisition: Some(acquisition),
+            configuration,
+        })
+    }
+
+    fn is_running(&self) -> bool {
+        self.pipeline.state().running()
+    }
+
+    fn config(&self) -> RpmConfig {
+        RpmConfig {
+            configuration: self.configuration,
+        }
+    }
+
+    // Returns None until the first sample has been computed.
+    fn latest_rpm(&self) -> PyResult<Option<f32>> {
+        let state = self.pipeline.state();
+        if let Some(error) = state.error() {
+            return Err(pyo3::exceptions::PyException::new_err(error));
+        }
+        Ok(if state.samples() == 0 {
+            None
+        } else {
+            Some(state.rpm())
+        })
+    }
+
+    fn latest_smoothed_rpm(&self) -> Option<f32> {
+        let state = self.pipeline.state();
+        if state.samples() == 0 {
+            None
+        } else {
+            Some(state.smoothed_rpm())
+        }
+    }
+
+    fn latest_confidence(&self) -> Option<f32> {
+        let state = self.pipeline.state();
+        if state.samples() == 0 {
+            None
+        } else {
+            Some(state.confidence())
+        }
+    }
+
+    fn latest_spectrum<'py>(&self, python: Python<'py>) -> Bound<'py, numpy::PyArray1<f32>> {
+        numpy::PyArray1::from_vec(python, self.pipeline.state().spectrum())
+    }
+
+    fn samples(&self) -> u64 {
+        self.pipeline.state().samples()
+    }
+
+    fn dropped_packets(&self) -> u64 {
+        self.pipeline.state().dropped_packets()
+    }
+
+    // Waits for the callback to return and for queued packets to be processed, and raises the
+    // first error reported by either thread.
+    fn stop(&mut self, python: Python) -> PyResult<()> {
+        let pipeline = &mut self.pipeline;
+        let acquisition = self.acquisition.take();
+        python.allow_threads(|| {
+            pipeline.state().stop(None);
+            if let Some(acquisition) = acquisition {
+                let _ = acquisition.join();
+            }
+            pipeline.stop();
+        });
+        match self.pipeline.state().error() {
+            Some(error) => Err(pyo3::exceptions::PyException::new_err(error)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "publisher")]
+#[pyclass]
+pub struct Publisher {
+    publisher: publisher::Publisher,
+}
+
+#[cfg(feature = "publisher")]
+#[pymethods]
+impl Publisher {
+    #[new]
+    #[pyo3(signature = (address = publisher::DEFAULT_ADDRESS, format = "json"))]
+    fn new(address: &str, format: &str) -> PyResult<Self> {
+        Ok(Self {
+            publisher: publisher::Publisher::new(address, format.parse()?)?,
+        })
+    }
+
+    #[getter]
+    fn address(&self) -> String {
+        self.publisher.local_address().to_string()
+    }
+
+    fn is_running(&self) -> bool {
+        self.publisher.is_running()
+    }
+
+    fn close(&self) {
+        self.publisher.close();
+    }
+}
+
+// Same layout as ArrayType::Rpm.
+#[derive(Clone, Copy)]
+#[repr(C, packed)]
+struct RpmSample {
+    t: u64,
+    rpm: f64,
+}
+
+// Reads records directly from the array's buffer (the strides are read once, instead of calling
+// PyArray_GetPtr for every record) so that the GIL can be released while they are processed.
+// Multi-dimensional arrays are read in C order, views with arbitrary strides (for instance
+// slices of a np.memmap) are read in place without copies. Records with padding or reordered
+// fields are copied field by field into a packed record. T must have the packed layout of the
+// checked fields, and the caller must keep the array alive (and unmodified) during iteration.
+pub struct RawRecords<T> {
+    data: *const u8,
+    stride: isize,
+    dimensions_and_strides: Option<Vec<(numpy::npyffi::npy_intp, isize)>>, // non-contiguous
+    gather: Option<Vec<(usize, usize, usize, bool)>>,
+    index: numpy::npyffi::npy_intp,
+    length: numpy::npyffi::npy_intp,
+    record: std::marker::PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for RawRecords<T> {}
+
+type RawEvents = RawRecords<core::Event>;
+
+impl<T: Copy> RawRecords<T> {
+    unsafe fn new(
+        python: Python,
+        array: *mut numpy::npyffi::PyArrayObject,
+        length: numpy::npyffi::npy_intp,
+        layout: &Layout,
+    ) -> Self {
+        let dimensions_length = (*array).nd as usize;
+        let c_contiguous = (*array).flags & numpy::npyffi::flags::NPY_ARRAY_C_CONTIGUOUS != 0;
+        Self::from_raw_parts(
+            (*array).data as *const u8,
+            length,
+            if dimensions_length == 1 {
+                *(*array).strides
+            } else {
+                numpy::npyffi::PyDataType_ELSIZE(python, (*array).descr) as isize
+            },
+            (dimensions_length > 1 && !c_contiguous).then(|| {
+                (0..dimensions_length)
+                    .map(|dimension| {
+                        (
+                            *(*array).dimensions.add(dimension),
+                            *(*array).strides.add(dimension),
+                        )
+                    })
+                    .collect()
+            }),
+            layout,
+        )
+    }
+
+    // Reads length records from data, which does not need to belong to a numpy array. Records
+    // are stride bytes apart, unless dimensions_and_strides (in bytes, one pair per dimension) is
+    // given, in which case they are walked in C order. data must stay valid during iteration.
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn from_raw_parts(
+        data: *const u8,
+        length: numpy::npyffi::npy_intp,
+        stride: isize,
+        dimensions_and_strides: Option<Vec<(numpy::npyffi::npy_intp, isize)>>,
+        layout: &Layout,
+    ) -> Self {
+        Self {
+            data,
+            stride,
+            dimensions_and_strides,
+            gather: layout.gather(),
+            index: 0,
+            length,
+            record: std::marker::PhantomData,
+        }
+    }
+
+    // Pointer to the first byte of the next record.
+    fn next_record(&mut self) -> Option<*const u8> {
+        if self.index < self.length {
+            let offset = match self.dimensions_and_strides.as_ref() {
+                Some(dimensions_and_strides) => {
+                    // C order, the last dimension varies fastest
+                    let mut index = self.index;
+                    let mut offset = 0;
+                    for (dimension, stride) in dimensions_and_strides.iter().rev() {
+                        offset += (index % dimension) * stride;
+                        index /= dimension;
+                    }
+                    offset
+                }
+                None => self.index * self.stride,
+            };
+            let data = unsafe { self.data.offset(offset) };
+            self.index += 1;
+            Some(data)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Copy> Iterator for RawRecords<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().map(|data| match self.gather.as_ref() {
+            Some(gather) => unsafe {
+                let mut record = std::mem::MaybeUninit::<T>::uninit();
+                for (offset, packed_offset, size, swapped) in gather.iter() {
+                    let field = (record.as_mut_ptr() as *mut u8).add(*packed_offset);
+                    std::ptr::copy_nonoverlapping(data.add(*offset), field, *size);
+                    if *swapped {
+                        std::slice::from_raw_parts_mut(field, *size).reverse();
+                    }
+                }
+                record.assume_init()
+            },
+            None => unsafe { std::ptr::read_unaligned(data as *const T) },
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.length - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T: Copy> ExactSizeIterator for RawRecords<T> {}
+
+// Same layout as ArrayType::AedatTrigger.
+#[derive(Clone, Copy)]
+#[repr(C, packed)]
+struct AedatTrigger {
+    t: u64,
+    source: u8,
+}
+
+// DVS events whose timestamp or polarity types differ from core::Event's (for instance
+// Metavision's int64 timestamps and int16 polarities). Integer timestamps are in µs and
+// floating-point timestamps in s, events with negative (or non-finite) timestamps are dropped.
+// Positive polarities are On, hence both 0/1 and -1/+1 encodings are supported.
+struct ConvertedDvsEvents {
+    records: RawRecords<()>,
+    offsets: [usize; 4], // t, x, y, and polarity
+    swapped: [bool; 4],  // t, x, y, and polarity
+    t_type: FieldType,
+    polarity_type: FieldType,
+}
+
+impl Iterator for ConvertedDvsEvents {
+    type Item = core::Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [t_offset, x_offset, y_offset, polarity_offset] = self.offsets;
+        let [t_swapped, x_swapped, y_swapped, polarity_swapped] = self.swapped;
+        loop {
+            let data = self.records.next_record()?;
+            let t = match self.t_type {
+                FieldType::I64 => {
+                    u64::try_from(unsafe { read_field::<i64>(data, t_offset, t_swapped) }).ok()
+                }
+                FieldType::F64 => {
+                    let t = (unsafe { read_field::<f64>(data, t_offset, t_swapped) } * 1e6).round();
+                    (t.is_finite() && t >= 0.0).then_some(t as u64)
+                }
+                _ => Some(unsafe { read_field::<u64>(data, t_offset, t_swapped) }),
+            };
+            let Some(t) = t else {
+                continue;
+            };
+            let on = unsafe {
+                match self.polarity_type {
+                    FieldType::I8 => read_field::<i8>(data, polarity_offset, false) > 0,
+                    FieldType::I16 => {
+                        read_field::<i16>(data, polarity_offset, polarity_swapped) > 0
+                    }
+                    FieldType::I32 => {
+                        read_field::<i32>(data, polarity_offset, polarity_swapped) > 0
+                    }
+                    FieldType::I64 => {
+                        read_field::<i64>(data, polarity_offset, polarity_swapped) > 0
+                    }
+                    _ => read_field::<u8>(data, polarity_offset, false) > 0,
+                }
+            };
+            return Some(core::Event {
+                t,
+                x: unsafe { read_field(data, x_offset, x_swapped) },
+                y: unsafe { read_field(data, y_offset, y_swapped) },
+                polarity: if on {
+                    neuromorphic_types::DvsPolarity::On
+                } else {
+                    neuromorphic_types::DvsPolarity::Off
+                },
+            });
+        }
+    }
+}
+
+// T must be a primitive number if swapped is true (big-endian field).
+unsafe fn read_field<T: Copy>(record: *const u8, offset: usize, swapped: bool) -> T {
+    let mut value = std::ptr::read_unaligned(record.add(offset) as *const T);
+    if swapped {
+        std::slice::from_raw_parts_mut(&mut value as *mut T as *mut u8, std::mem::size_of::<T>())
+            .reverse();
+    }
+    value
+}
+
+// Converts the supported event arrays to DVS events. ATIS exposure measurements are dropped
+// and the lowest DAT payload bit is used as the polarity.
+enum RawDvsEvents {
+    Dvs(RawEvents),
+    ConvertedDvs(ConvertedDvsEvents),
+    EsAtis(RawRecords<es::AtisEvent>),
+    Dat(RawRecords<dat::Event>),
+}
+
+impl RawDvsEvents {
+    // The caller must keep the array alive (and unmodified) during iteration.
+    unsafe fn new(
+        python: Python,
+        events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+        strict: bool,
+    ) -> PyResult<Self> {
+        match check_array(python, ArrayType::Dvs, events, strict) {
+            Ok((array, length, layout)) => Ok(if layout.fields == ArrayType::Dvs.fields() {
+                Self::Dvs(RawEvents::new(python, array, length, &layout))
+            } else {
+                Self::ConvertedDvs(ConvertedDvsEvents {
+                    records: RawRecords::new(python, array, length, &layout),
+                    offsets: [
+                        layout.offsets[0],
+                        layout.offsets[1],
+                        layout.offsets[2],
+                        layout.offsets[3],
+                    ],
+                    swapped: [
+                        layout.swapped[0],
+                        layout.swapped[1],
+                        layout.swapped[2],
+                        layout.swapped[3],
+                    ],
+                    t_type: layout.fields.0[0].field_type,
+                    polarity_type: layout.fields.0[3].field_type,
+                })
+            }),
+            Err(error) => {
+                if let Ok((array, length, layout)) =
+                    check_array(python, ArrayType::EsAtis, events, strict)
+                {
+                    Ok(Self::EsAtis(RawRecords::new(
+                        python, array, length, &layout,
+                    )))
+                } else if let Ok((array, length, layout)) =
+                    check_array(python, ArrayType::Dat, events, strict)
+                {
+                    Ok(Self::Dat(RawRecords::new(python, array, length, &layout)))
+                } else {
+                    Err(error)
+                }
+            }
+        }
+    }
+}
+
+// Accepts an event array or a list or tuple of event arrays (processed in order, without
+// concatenation). The caller must keep the arrays alive (and unmodified) during iteration.
+unsafe fn raw_dvs_packets(
+    python: Python,
+    object: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    strict: bool,
+) -> PyResult<Vec<RawDvsEvents>> {
+    if numpy::npyffi::array::PyArray_Check(python, object.as_ptr()) != 0 {
+        return Ok(vec![RawDvsEvents::new(python, object, strict)?]);
+    }
+    if let Ok(list) = object.downcast::<pyo3::types::PyList>() {
+        list.iter()
+            .map(|packet| RawDvsEvents::new(python, &packet, strict))
+            .collect()
+    } else if let Ok(tuple) = object.downcast::<pyo3::types::PyTuple>() {
+        tuple
+            .iter()
+            .map(|packet| RawDvsEvents::new(python, &packet, strict))
+            .collect()
+    } else {
+        Err(CheckArrayError::PyArrayCheck.into())
+    }
+}
+
+impl Iterator for RawDvsEvents {
+    type Item = core::Event;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            RawDvsEvents::Dvs(events) => events.next(),
+            RawDvsEvents::ConvertedDvs(events) => events.next(),
+            RawDvsEvents::EsAtis(events) => events.find_map(atis_to_dvs),
+            RawDvsEvents::Dat(events) => events.next().map(dat_to_dvs),
+        }
+    }
+}
+
+fn atis_to_dvs(event: es::AtisEvent) -> Option<core::Event> {
+    (!event.exposure).then_some(core::Event {
+        t: event.t,
+        x: event.x,
+        y: event.y,
+        polarity: if event.polarity {
+            neuromorphic_types::DvsPolarity::On
+        } else {
+            neuromorphic_types::DvsPolarity::Off
+        },
+    })
+}
+
+fn dat_to_dvs(event: dat::Event) -> core::Event {
+    core::Event {
+        t: event.t,
+        x: event.x,
+        y: event.y,
+        polarity: if event.payload & 1 == 1 {
+            neuromorphic_types::DvsPolarity::On
+        } else {
+            neuromorphic_types::DvsPolarity::Off
+        },
+    }
+}
+
+// Returns the given resolution, or the smallest one that contains the events.
+fn resolution(
+    width: Option<u16>,
+    height: Option<u16>,
+    coordinates: impl Iterator<Item = (u16, u16)>,
+) -> (u16, u16) {
+    match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        _ => {
+            let (maximum_x, maximum_y) =
+                coordinates.fold((0, 0), |(maximum_x, maximum_y), (x, y)| {
+                    (
+                        maximum_x.max(x.saturating_add(1)),
+                        maximum_y.max(y.saturating_add(1)),
+                    )
+                });
+            (width.unwrap_or(maximum_x), height.unwrap_or(maximum_y))
+        }
+    }
+}
+
+// Processes a whole recording in one call and returns a dictionary of numpy arrays with one row
+// per sample: t (µs), rpm, smoothed_rpm, confidence, blade_count, the spinner's track (x, y,
+// and radius in sensor pixels) and ellipse (semi_major, semi_minor, angle, and tilt, see
+// Geometry), and imbalance_index (see RpmCalculator.imbalance_indices), NaN where unknown.
+// spectrogram has one spectrum per sample and frequencies (Hz) one value per spectrum bin.
+// events is either any array accepted by RpmCalculator.process or the path of a recording (see
+// RpmCalculator.process_file). The configuration is either config or the keyword arguments of
+// RpmConfig, width and height default to the recording's resolution (or its largest coordinates
+// plus one if the header does not give it). Recordings are processed chunk by chunk. The last,
+// partial window is flushed.
+#[pyfunction]
+#[pyo3(signature = (
+    events,
+    config = None,
+    *,
+    amplitude_threshold = core::DEFAULT_AMPLITUDE_THRESHOLD,
+    autocorrelation_threshold = core::DEFAULT_AUTOCORRELATION_THRESHOLD,
+    frequency_multiplier = 1.0,
+    strict = true,
+    **configuration,
+))]
+#[allow(clippy::too_many_arguments)]
+fn analyze<'py>(
+    python: Python<'py>,
+    events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    config: Option<RpmConfig>,
+    amplitude_threshold: f32,
+    autocorrelation_threshold: f32,
+    frequency_multiplier: f32,
+    strict: bool,
+    configuration: Option<&Bound<'_, pyo3::types::PyDict>>,
+) -> PyResult<Bound<'py, pyo3::types::PyDict>> {
+    let path = events.extract::<std::path::PathBuf>().ok();
+    let configuration = match config {
+        Some(_) => configuration.cloned(),
+        None => {
+            let configuration = match configuration {
+                Some(configuration) => configuration.copy()?,
+                None => pyo3::types::PyDict::new(python),
+            };
+            let width = configuration
+                .get_item("width")?
+                .map(|width| width.extract())
+                .transpose()?;
+            let height = configuration
+                .get_item("height")?
+                .map(|height| height.extract())
+                .transpose()?;
+            let (width, height) = match path.as_ref() {
+                Some(path) => recording_resolution(python, path, width, height)?,
+                None => resolution(
+                    width,
+                    height,
+                    unsafe { raw_dvs_packets(python, events, strict)? }
+                        .into_iter()
+                        .flatten()
+                        .map(|event| (event.x, event.y)),
+                ),
+            };
+            configuration.set_item("width", width)?;
+            configuration.set_item("height", height)?;
+            Some(configuration)
+        }
+    };
+    let mut estimator =
+        core::RpmEstimator::new(rpm_config(python, config, configuration.as_ref())?)?;
+    estimator.set_record_spectra(true);
+    let parameters = core::Parameters {
+        amplitude_threshold,
+        autocorrelation_threshold,
+        frequency_multiplier,
+    };
+    // recordings are processed chunk by chunk, arrays in place
+    let mut recording = path.map(|path| Recording::new(&path, None)).transpose()?;
+    let packets = match recording {
+        Some(_) => Vec::new(),
+        None => unsafe { raw_dvs_packets(python, events, strict)? },
+    };
+    let (samples, spectra) = python.allow_threads(|| -> PyResult<_> {
+        let mut samples = Vec::new();
+        let mut spectra = Vec::new();
+        let mut record = |estimator: &core::RpmEstimator| {
+            samples.extend_from_slice(estimator.samples());
+            spectra.extend_from_slice(estimator.spectra());
+        };
+        match recording.as_mut() {
+            Some(recording) => {
+                while let Some(events) = recording.next_events()? {
+                    estimator.process(&events, &parameters)?;
+                    record(&estimator);
+                }
+            }
+            None => {
+                estimator.process_events(packets.into_iter().flatten(), &parameters)?;
+                record(&estimator);
+            }
+        }
+        estimator.flush(None, &parameters)?;
+        record(&estimator);
+        Ok((samples, spectra))
+    })?;
+    let column = |value: fn(&core::Sample) -> f32| -> Bound<'py, numpy::PyArray1<f32>> {
+        numpy::PyArray1::from_iter(python, samples.iter().map(value))
+    };
+    let results = pyo3::types::PyDict::new(python);
+    results.set_item(
+        "t",
+        numpy::PyArray1::from_iter(python, samples.iter().map(|sample| sample.t)),
+    )?;
+    results.set_item("rpm", column(|sample| sample.rpm))?;
+    results.set_item("smoothed_rpm", column(|sample| sample.smoothed_rpm))?;
+    results.set_item("confidence", column(|sample| sample.confidence))?;
+    results.set_item(
+        "blade_count",
+        numpy::PyArray1::from_iter(python, samples.iter().map(|sample| sample.blade_count)),
+    )?;
+    results.set_item(
+        "x",
+        column(|sample| sample.track.map_or(f32::NAN, |track| track.x)),
+    )?;
+    results.set_item(
+        "y",
+        column(|sample| sample.track.map_or(f32::NAN, |track| track.y)),
+    )?;
+    results.set_item(
+        "radius",
+        column(|sample| sample.track.map_or(f32::NAN, |track| track.radius)),
+    )?;
+    results.set_item(
+        "semi_major",
+        column(|sample| {
+            sample
+                .geometry
+                .map_or(f32::NAN, |geometry| geometry.semi_major)
+        }),
+    )?;
+    results.set_item(
+        "semi_minor",
+        column(|sample| {
+            sample
+                .geometry
+                .map_or(f32::NAN, |geometry| geometry.semi_minor)
+        }),
+    )?;
+    results.set_item(
+        "angle",
+        column(|sample| sample.geometry.map_or(f32::NAN, |geometry| geometry.angle)),
+    )?;
+    results.set_item(
+        "tilt",
+        column(|sample| sample.geometry.map_or(f32::NAN, |geometry| geometry.tilt)),
+    )?;
+    results.set_item("imbalance_index", column(|sample| sample.imbalance_index))?;
+    let spectrum_length = estimator.spectrum().len();
+    results.set_item(
+        "spectrogram",
+        numpy::PyArray1::from_vec(python, spectra).reshape([samples.len(), spectrum_length])?,
+    )?;
+    results.set_item(
+        "frequencies",
+        numpy::PyArray1::from_vec(python, estimator.frequencies()),
+    )?;
+    Ok(results)
+}
+
+// Writes an EsAtis, EsColor, or DVS array (or any array accepted by RpmCalculator.process) to an
+// Event Stream file. Missing width and height are inferred from the events.
+#[pyfunction]
+#[pyo3(signature = (path, events, width = None, height = None, strict = true))]
+fn write_es(
+    python: Python,
+    path: std::path::PathBuf,
+    events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    width: Option<u16>,
+    height: Option<u16>,
+    strict: bool,
+) -> PyResult<()> {
+    if let Ok((array, length, layout)) = check_array(python, ArrayType::EsAtis, events, true) {
+        let records =
+            || unsafe { RawRecords::<es::AtisEvent>::new(python, array, length, &layout) };
+        let (width, height) = resolution(width, height, records().map(|event| (event.x, event.y)));
+        let mut encoder = es::Encoder::new(&path, es::EventType::Atis, width, height)?;
+        let records = records();
+        python.allow_threads(|| -> Result<(), es::Error> {
+            for event in records {
+                encoder.write_atis(event)?;
+            }
+            encoder.flush()
+        })?;
+    } else if let Ok((array, length, layout)) =
+        check_array(python, ArrayType::EsColor, events, true)
+    {
+        let records =
+            || unsafe { RawRecords::<es::ColorEvent>::new(python, array, length, &layout) };
+        let (width, height) = resolution(width, height, records().map(|event| (event.x, event.y)));
+        let mut encoder = es::Encoder::new(&path, es::EventType::Color, width, height)?;
+        let records = records();
+        python.allow_threads(|| -> Result<(), es::Error> {
+            for event in records {
+                encoder.write_color(event)?;
+            }
+            encoder.flush()
+        })?;
+    } else {
+        let (width, height) = resolution(
+            width,
+            height,
+            unsafe { RawDvsEvents::new(python, events, strict)? }.map(|event| (event.x, event.y)),
+        );
+        let mut encoder = es::Encoder::new(&path, es::EventType::Dvs, width, height)?;
+        let records = unsafe { RawDvsEvents::new(python, events, strict)? };
+        python.allow_threads(|| -> Result<(), es::Error> {
+            for event in records {
+                encoder.write_dvs(event)?;
+            }
+            encoder.flush()
+        })?;
+    }
+    Ok(())
+}
+
+// Returns the events in [x0, x1[ x [y0, y1[ as a Dvs array, with coordinates relative to
+// (x0, y0). events can be any array accepted by RpmCalculator.process.
+#[pyfunction]
+#[pyo3(signature = (events, x0, y0, x1, y1, strict = true))]
+fn crop<'py>(
+    python: Python<'py>,
+    events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    x0: u16,
+    y0: u16,
+    x1: u16,
+    y1: u16,
+    strict: bool,
+) -> PyResult<pyo3::Bound<'py, pyo3::PyAny>> {
+    if x0 >= x1 || y0 >= y1 {
+        return Err(pyo3::exceptions::PyException::new_err(format!(
+            "the crop must satisfy x0 < x1 and y0 < y1 (got x0={x0}, y0={y0}, x1={x1}, y1={y1})"
+        )));
+    }
+    let events = unsafe { RawDvsEvents::new(python, events, strict)? };
+    let cropped: Vec<core::Event> = python.allow_threads(|| {
+        events
+            .filter(|event| event.x >= x0 && event.x < x1 && event.y >= y0 && event.y < y1)
+            .map(|event| core::Event {
+                x: event.x - x0,
+                y: event.y - y0,
+                ..event
+            })
+            .collect()
+    });
+    Ok(slice_to_array(python, ArrayType::Dvs, &cropped))
+}
+
+// Divides the coordinates by factor (rounding down) and returns a Dvs array. Events are not
+// merged, hence the output has as many events as the input.
+#[pyfunction]
+#[pyo3(signature = (events, factor, strict = true))]
+fn spatial_downsample<'py>(
+    python: Python<'py>,
+    events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    factor: u16,
+    strict: bool,
+) -> PyResult<pyo3::Bound<'py, pyo3::PyAny>> {
+    if factor == 0 {
+        return Err(pyo3::exceptions::PyException::new_err(
+            "factor must be larger than 0",
+        ));
+    }
+    let events = unsafe { RawDvsEvents::new(python, events, strict)? };
+    let downsampled: Vec<core::Event> = python.allow_threads(|| {
+        events
+            .map(|event| core::Event {
+                x: event.x / factor,
+                y: event.y / factor,
+                ..event
+            })
+            .collect()
+    });
+    Ok(slice_to_array(python, ArrayType::Dvs, &downsampled))
+}
+
+// Returns the events with t0 <= t < t1 (µs) as a Dvs array. Events do not need to be sorted.
+#[pyfunction]
+#[pyo3(signature = (events, t0, t1, strict = true))]
+fn time_slice<'py>(
+    python: Python<'py>,
+    events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    t0: u64,
+    t1: u64,
+    strict: bool,
+) -> PyResult<pyo3::Bound<'py, pyo3::PyAny>> {
+    let events = unsafe { RawDvsEvents::new(python, events, strict)? };
+    let sliced: Vec<core::Event> = python.allow_threads(|| {
+        events
+            .filter(|event| event.t >= t0 && event.t < t1)
+            .collect()
+    });
+    Ok(slice_to_array(python, ArrayType::Dvs, &sliced))
+}
+
+// Writes a Dat or DVS array (or any array accepted by RpmCalculator.process, the polarity is
+// stored in the payload) to a version 2 DAT file with CD events. Missing width and height are
+// inferred from the events.
+#[pyfunction]
+#[pyo3(signature = (path, events, width = None, height = None, strict = true))]
+fn write_dat(
+    python: Python,
+    path: std::path::PathBuf,
+    events: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    width: Option<u16>,
+    height: Option<u16>,
+    strict: bool,
+) -> PyResult<()> {
+    if let Ok((array, length, layout)) = check_array(python, ArrayType::Dat, events, true) {
+        let records = || unsafe { RawRecords::<dat::Event>::new(python, array, length, &layout) };
+        let (width, height) = resolution(width, height, records().map(|event| (event.x, event.y)));
+        let mut encoder = dat::Encoder::new(&path, dat::CD_EVENT_TYPE, width, height)?;
+        let records = records();
+        python.allow_threads(|| -> Result<(), dat::Error> {
+            for event in records {
+                encoder.write(event)?;
+            }
+            encoder.flush()
+        })?;
+    } else {
+        let (width, height) = resolution(
+            width,
+            height,
+            unsafe { RawDvsEvents::new(python, events, strict)? }.map(|event| (event.x, event.y)),
+        );
+        let mut encoder = dat::Encoder::new(&path, dat::CD_EVENT_TYPE, width, height)?;
+        let records = unsafe { RawDvsEvents::new(python, events, strict)? };
+        python.allow_threads(|| -> Result<(), dat::Error> {
+            for event in records {
+                encoder.write(dat::Event {
+                    t: event.t,
+                    x: event.x,
+                    y: event.y,
+                    payload: matches!(event.polarity, neuromorphic_types::DvsPolarity::On) as u8,
+                })?;
+            }
+            encoder.flush()
+        })?;
+    }
+    Ok(())
+}
+
+// Returns the given resolution, or the recording's. Recordings whose header does not name the
+// sensor (AEDAT 2.0 and 3.x with unknown cameras) are decoded once to find their largest
+// coordinates.
+fn recording_resolution(
+    python: Python,
+    path: &std::path::Path,
+    width: Option<u16>,
+    height: Option<u16>,
+) -> PyResult<(u16, u16)> {
+    if let (Some(width), Some(height)) = (width, height) {
+        return Ok((width, height));
+    }
+    let mut recording = Recording::new(path, None)?;
+    if let Some((header_width, header_height)) = recording.resolution() {
+        return Ok((
+            width.unwrap_or(header_width),
+            height.unwrap_or(header_height),
+        ));
+    }
+    python.allow_threads(|| -> PyResult<(u16, u16)> {
+        let (mut maximum_x, mut maximum_y) = (0, 0);
+        while let Some(events) = recording.next_events()? {
+            let (x, y) = resolution(None, None, events.iter().map(|event| (event.x, event.y)));
+            maximum_x = maximum_x.max(x);
+            maximum_y = maximum_y.max(y);
+        }
+        Ok((width.unwrap_or(maximum_x), height.unwrap_or(maximum_y)))
+    })
+}
+
+// Reads DVS events from a recording, the format is inferred from the file extension
+// (.aedat for AEDAT 2.0, 3.x, or 4.0, .aedat4, .raw for EVT2 or EVT3, .dat, or .es).
+enum Recording {
+    Aedat(aedat::Decoder),
+    Aedat4 {
+        decoder: aedat4::Decoder,
+        stream_id: u32,
+    },
+    Evt3(evt3::Decoder),
+    Dat(dat::Decoder),
+    Es(es::Decoder),
+}
+
+impl Recording {
+    fn new(path: &std::path::Path, chunk_length: Option<usize>) -> PyResult<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        // .aedat files may use any AEDAT version, the first header line tells them apart
+        let is_aedat4 = match extension.as_deref() {
+            Some("aedat4") => true,
+            Some("aedat") => {
+                let mut file = std::io::BufReader::new(std::fs::File::open(path)?);
+                aedat::read_version(&mut file)?.major == 4
+            }
+            _ => false,
+        };
+        Ok(match extension.as_deref() {
+            _ if is_aedat4 => {
+                let decoder = aedat4::Decoder::new(path)?;
+                let stream_id = decoder
+                    .streams()
+                    .first()
+                    .ok_or(aedat4::Error::NoEventStream)?
+                    .id;
+                Recording::Aedat4 { decoder, stream_id }
+            }
+            Some("aedat") => Recording::Aedat(aedat::Decoder::new(
+                path,
+                chunk_length.unwrap_or(aedat::DEFAULT_CHUNK_LENGTH),
+            )?),
+            Some("raw") => Recording::Evt3(evt3::Decoder::new(
+                path,
+                chunk_length.unwrap_or(evt3::DEFAULT_CHUNK_LENGTH),
+            )?),
+            Some("dat") => Recording::Dat(dat::Decoder::new(
+                path,
+                chunk_length.unwrap_or(dat::DEFAULT_CHUNK_LENGTH),
+            )?),
+            Some("es") => Recording::Es(es::Decoder::new(
+                path,
+                chunk_length.unwrap_or(es::DEFAULT_CHUNK_LENGTH),
+            )?),
+            _ => {
+                return Err(pyo3::exceptions::PyException::new_err(format!(
+                "unsupported file extension in {:?} (expected .aedat, .aedat4, .raw, .dat, or .es)",
+                path
+            )))
+            }
+        })
+    }
+
+    // None if the header does not give the sensor resolution.
+    fn resolution(&self) -> Option<(u16, u16)> {
+        match self {
+            Recording::Aedat(decoder) => decoder.width().zip(decoder.height()),
+            Recording::Aedat4 { decoder, stream_id } => decoder
+                .streams()
+                .iter()
+                .find(|stream| stream.id == *stream_id)
+                .map(|stream| (stream.width, stream.height)),
+            Recording::Evt3(decoder) => Some((decoder.width(), decoder.height())),
+            Recording::Dat(decoder) => Some((decoder.width(), decoder.height())),
+            Recording::Es(decoder) => Some((decoder.width(), decoder.height())),
+        }
+    }
+
+    // Returns None at the end of the file.
+    fn next_events(&mut self) -> PyResult<Option<Vec<core::Event>>> {
+        match self {
+            Recording::Aedat(decoder) => Ok(decoder.next().transpose()?.map(|chunk| chunk.events)),
+            Recording::Aedat4 { decoder, stream_id } => {
+                for packet in decoder.by_ref() {
+                    let packet = packet?;
+                    if packet.stream_id == *stream_id {
+                        return Ok(Some(packet.events));
+                    }
+                }
+                Ok(None)
+            }
+            Recording::Evt3(decoder) => Ok(decoder.next().transpose()?.map(|chunk| chunk.events)),
+            Recording::Dat(decoder) => Ok(decoder
+                .next()
+                .transpose()?
+                .map(|events| events.into_iter().map(dat_to_dvs).collect())),
+            Recording::Es(decoder) => match decoder.next().transpose()? {
+                Some(es::Chunk::Dvs(events)) => Ok(Some(events)),
+                Some(es::Chunk::Atis(events)) => {
+                    Ok(Some(events.into_iter().filter_map(atis_to_dvs).collect()))
+                }
+                Some(es::Chunk::Generic(_)) | Some(es::Chunk::Color(_)) => {
+                    Err(pyo3::exceptions::PyException::new_err(
+                        "only DVS and ATIS Event Stream files can be processed",
+                    ))
+                }
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+// T must have the same layout as the array type's (packed) dtype.
+fn slice_to_array<'py, T: Copy>(
+    python: Python<'py>,
+    array_type: ArrayType,
+    values: &[T],
+) -> pyo3::Bound<'py, pyo3::PyAny> {
+    debug_assert_eq!(
+        array_type
+            .fields()
+            .iter()
+            .map(|field| field.size())
+            .sum::<usize>(),
+        std::mem::size_of::<T>()
+    );
+    let array = array_type.new_array(python, values.len() as numpy::npyffi::npy_intp);
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            values.as_ptr() as *const u8,
+            (*array).data as *mut u8,
+            std::mem::size_of_val(values),
+        );
+        pyo3::Bound::from_owned_ptr(python, array as *mut pyo3::ffi::PyObject)
+    }
+}
+
+fn generic_events_to_array<'py>(
+    python: Python<'py>,
+    events: &[es::GenericEvent],
+) -> pyo3::Bound<'py, pyo3::PyAny> {
+    let array = ArrayType::EsGeneric.new_array(python, events.len() as numpy::npyffi::npy_intp);
+    unsafe {
+        let data = (*array).data as *mut u8;
+        let stride = *(*array).strides;
+        for (index, event) in events.iter().enumerate() {
+            let event_cell = data.offset(index as isize * stride);
+            std::ptr::write_unaligned(event_cell as *mut u64, event.t);
+            let bytes_cell =
+                event_cell.add(std::mem::size_of::<u64>()) as *mut *mut pyo3::ffi::PyObject;
+            pyo3::ffi::Py_XDECREF(std::ptr::read_unaligned(bytes_cell));
+            std::ptr::write_unaligned(
+                bytes_cell,
+                pyo3::ffi::PyBytes_FromStringAndSize(
+                    event.bytes.as_ptr() as *const std::ffi::c_char,
+                    event.bytes.len() as pyo3::ffi::Py_ssize_t,
+                ),
+            );
+        }
+        pyo3::Bound::from_owned_ptr(python, array as *mut pyo3::ffi::PyObject)
+    }
+}
+
+fn rising_edges(
+    python: Python,
+    triggers: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    source: Option<u8>,
+    strict: bool,
+) -> PyResult<Vec<u64>> {
+    let mut rising_ts = Vec::new();
+    match check_array(python, ArrayType::EvtTrigger, triggers, strict) {
+        Ok((array, length, layout)) => {
+            for trigger in
+                unsafe { RawRecords::<evt3::Trigger>::new(python, array, length, &layout) }
+            {
+                if trigger.rising && source.is_none_or(|source| source == trigger.source) {
+                    rising_ts.push(trigger.t);
+                }
+            }
+        }
+        Err(error) => {
+            let Ok((array, length, layout)) =
+                check_array(python, ArrayType::AedatTrigger, triggers, strict)
+            else {
+                return Err(error);
+            };
+            // 1 is ExternalSignalRisingEdge and 3 is ExternalSignalPulse
+            for trigger in
+                unsafe { RawRecords::<AedatTrigger>::new(python, array, length, &layout) }
+            {
+                if matches!(trigger.source, 1 | 3) {
+                    rising_ts.push(trigger.t);
+                }
+            }
+        }
+    }
+    Ok(rising_ts)
+}
+
+fn copy_to_array(
+    name: &str,
+    values: &[f32],
+    array: &pyo3::Bound<'_, numpy::PyArray1<f32>>,
+) -> PyResult<()> {
+    // read-only arrays (for instance np.memmap opened with mode="r") cannot be written
+    let mut array = array.try_readwrite().map_err(|_| {
+        pyo3::exceptions::PyException::new_err(format!(
+            "{} must be writeable (and not borrowed by another call)",
+            name
+        ))
+    })?;
+    let mut array = array.as_array_mut();
+    if array.len() != values.len() {
+        return Err(pyo3::exceptions::PyException::new_err(format!(
+            "{} must have {} elements (got {})",
+            name,
+            values.len(),
+            array.len()
+        )));
+    }
+    // strided views are written element by element
+    for (target, value) in array.iter_mut().zip(values.iter()) {
+        *target = *value;
+    }
+    Ok(())
+}
+
+impl From<core::Error> for PyErr {
+    fn from(error: core::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<export::Error> for PyErr {
+    fn from(error: export::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<strobe::Error> for PyErr {
+    fn from(error: strobe::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<aedat::Error> for PyErr {
+    fn from(error: aedat::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<aedat4::Error> for PyErr {
+    fn from(error: aedat4::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+#[cfg(feature = "publisher")]
+impl From<publisher::Error> for PyErr {
+    fn from(error: publisher::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<multi::Error> for PyErr {
+    fn from(error: multi::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<state::Error> for PyErr {
+    fn from(error: state::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<tachometer::Error> for PyErr {
+    fn from(error: tachometer::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<live::Error> for PyErr {
+    fn from(error: live::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<dat::Error> for PyErr {
+    fn from(error: dat::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<es::Error> for PyErr {
+    fn from(error: es::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+impl From<evt3::Error> for PyErr {
+    fn from(error: evt3::Error) -> Self {
+        pyo3::exceptions::PyException::new_err(error.to_string())
+    }
+}
+
+// The module does not rely on the GIL (free-threaded builds), pyclasses are Sync and calls
+// that need mutable access to an object borrowed by another thread raise an exception.
+#[pymodule(gil_used = false)]
+#[pyo3(name = "extension")]
+fn figet_spinner(module: &pyo3::Bound<'_, pyo3::types::PyModule>) -> PyResult<()> {
+    module.add_class::<Aedat4Reader>()?;
+    module.add_class::<AedatReader>()?;
+    module.add_class::<ArrowRecordBatch>()?;
+    module.add_class::<Cluster>()?;
+    module.add_class::<DatReader>()?;
+    module.add_class::<EsReader>()?;
+    module.add_class::<Geometry>()?;
+    module.add_class::<LiveRpm>()?;
+    module.add_class::<MultiRpmCalculator>()?;
+    #[cfg(feature = "publisher")]
+    module.add_class::<Publisher>()?;
+    module.add_class::<RadialBin>()?;
+    module.add_class::<Evt3Reader>()?;
+    module.add_class::<RpmCalculator>()?;
+    module.add_class::<RpmConfig>()?;
+    module.add_class::<Statistics>()?;
+    module.add_class::<Timing>()?;
+    module.add_function(wrap_pyfunction!(analyze, module)?)?;
+    module.add_function(wrap_pyfunction!(crop, module)?)?;
+    module.add_function(wrap_pyfunction!(spatial_downsample, module)?)?;
+    module.add_function(wrap_pyfunction!(time_slice, module)?)?;
+    module.add_function(wrap_pyfunction!(write_dat, module)?)?;
+    module.add_function(wrap_pyfunction!(write_es, module)?)?;
+    Ok(())
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum CheckArrayError {
+    #[error("the object is not a numpy array")]
+    PyArrayCheck,
+
+    #[error("expected an array with at least one dimension (got a zero-dimensional array)")]
+    ZeroDimensional,
+
+    #[error("the array is not structured (https://numpy.org/doc/stable/user/basics.rec.html)")]
+    NotStructured,
+
+    #[error("the array must have a field \"{0}\"")]
+    MissingField(String),
+
+    #[error("the field \"{name}\" must have the type \"{expected_type}\" (got \"{actual_type}\")")]
+    Field {
+        name: String,
+        expected_type: String,
+        actual_type: String,
+    },
+
+    #[error(
+        "the array has extra fields (expected {expected}, got {actual}), use strict=False to ignore them"
+    )]
+    ExtraFields { expected: String, actual: String },
+}
+
+impl Into<PyErr> for CheckArrayError {
+    fn into(self) -> PyErr {
+        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(self.to_string())
+    }
+}
+
+// Field offsets are read from the array's dtype, hence padded dtypes (for instance created with
+// align=True) and reordered fields are accepted. Extra fields are ignored unless strict is true.
+pub fn check_array(
+    python: Python,
+    array_type: ArrayType,
+    object: &pyo3::Bound<'_, pyo3::types::PyAny>,
+    strict: bool,
+) -> PyResult<(
+    *mut numpy::npyffi::PyArrayObject,
+    numpy::npyffi::npy_intp,
+    Layout,
+)> {
+    if unsafe { numpy::npyffi::array::PyArray_Check(python, object.as_ptr()) } == 0 {
+        return Err(CheckArrayError::PyArrayCheck.into());
+    }
+    let array = object.as_ptr() as *mut numpy::npyffi::PyArrayObject;
+    let dimensions_length = unsafe { (*array).nd };
+    // multi-dimensional arrays are flattened in C order, whatever their strides (see RawRecords)
+    if dimensions_length == 0 {
+        return Err(CheckArrayError::ZeroDimensional.into());
+    }
+    let fields = unsafe { numpy::npyffi::PyDataType_FIELDS(python, (*array).descr) };
+    if unsafe { pyo3::ffi::PyMapping_Check(fields) } == 0 {
+        return Err(CheckArrayError::NotStructured.into());
+    }
+    let expected_fields = array_type.fields();
+    let mut layout = Layout {
+        fields: expected_fields.clone(),
+        offsets: [0; 11],
+        swapped: [false; 11],
+    };
+    for (index, expected_field) in expected_fields.iter().enumerate() {
+        let Some(actual_field) = expected_field.names().find_map(|null_terminated_name| {
+            let actual_field = unsafe {
+                pyo3::ffi::PyMapping_GetItemString(
+                    fields,
+                    null_terminated_name.as_ptr() as *const std::ffi::c_char,
+                )
+            };
+            if actual_field.is_null() {
+                // clears the KeyError
+                unsafe { pyo3::ffi::PyErr_Clear() };
+                None
+            } else {
+                Some(actual_field)
+            }
+        }) else {
+            return Err(CheckArrayError::MissingField(
+                expected_field
+                    .names()
+                    .map(|null_terminated_name| {
+                        &null_terminated_name[0..null_terminated_name.len() - 1]
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\" or \""),
+            )
+            .into());
+        };
+        let actual_description = unsafe { pyo3::ffi::PyTuple_GetItem(actual_field, 0) }
+            as *mut numpy::npyffi::PyArray_Descr;
+        let little_endian_description = |field_type| {
+            let description = Field {
+                field_type,
+                ..expected_field
+            }
+            .dtype(python);
+            unsafe {
+                (*description).byteorder = b'<' as std::ffi::c_char;
+            }
+            description
+        };
+        let equivalent = |expected_description: *mut numpy::npyffi::PyArray_Descr| {
+            let equivalent = unsafe {
+                numpy::PY_ARRAY_API.PyArray_EquivTypes(
+                    python,
+                    expected_description,
+                    actual_description,
+                ) != 0
+                    && (*expected_description).byteorder == (*actual_description).byteorder
+            };
+            unsafe {
+                pyo3::ffi::Py_DECREF(expected_description as *mut pyo3::ffi::PyObject);
+            }
+            equivalent
+        };
+        // big-endian fields are byte-swapped on read (see Layout::gather)
+        let Some((field_type, swapped)) = expected_field.types().find_map(|field_type| {
+            let expected_description = little_endian_description(field_type);
+            // PyArray_DescrNewByteorder returns a new description, the little-endian one may be
+            // a shared singleton
+            let big_endian_description = unsafe {
+                numpy::PY_ARRAY_API.PyArray_DescrNewByteorder(
+                    python,
+                    expected_description,
+                    b'>' as std::ffi::c_char,
+                )
+            };
+            if equivalent(expected_description) {
+                if !big_endian_description.is_null() {
+                    unsafe {
+                        pyo3::ffi::Py_DECREF(big_endian_description as *mut pyo3::ffi::PyObject);
+                    }
+                }
+                Some((field_type, false))
+            } else if !big_endian_description.is_null() && equivalent(big_endian_description) {
+                Some((field_type, true))
+            } else {
+                None
+            }
+        }) else {
+            let error = CheckArrayError::Field {
+                name: expected_field.name(),
+                expected_type: expected_field
+                    .types()
+                    .map(|field_type| {
+                        let expected_description = little_endian_description(field_type);
+                        let expected_type =
+                            simple_description_to_string(python, expected_description);
+                        unsafe {
+                            pyo3::ffi::Py_DECREF(expected_description as *mut pyo3::ffi::PyObject);
+                        }
+                        expected_type
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\" or \""),
+                actual_type: simple_description_to_string(python, actual_description),
+            };
+            unsafe {
+                pyo3::ffi::Py_DECREF(actual_field);
+            };
+            return Err(error.into());
+        };
+        layout.fields.0[index].field_type = field_type;
+        layout.swapped[index] = swapped;
+        layout.offsets[index] =
+            unsafe { pyo3::ffi::PyLong_AsLong(pyo3::ffi::PyTuple_GetItem(actual_field, 1)) }
+                as usize;
+        unsafe {
+            pyo3::ffi::Py_DECREF(actual_field);
+        }
+    }
+    let expected_fields_length = expected_fields.len();
+    let actual_names = unsafe { numpy::npyffi::PyDataType_NAMES(python, (*array).descr) };
+    let actual_names_length = unsafe { pyo3::ffi::PyTuple_GET_SIZE(actual_names) };
+    if strict && actual_names_length != expected_fields_length as pyo3::ffi::Py_ssize_t {
+        use std::fmt::Write;
+        let mut expected = "[".to_owned();
+        for (index, expected_field) in expected_fields.iter().enumerate() {
+            write!(
+                &mut expected,
+                "\"{}\"{}",
+                &expected_field.null_terminated_name
+                    [0..expected_field.null_terminated_name.len() - 1],
+                if index == expected_fields_length - 1 {
+                    ""
+                } else {
+                    ", "
+                }
+            )
+            .unwrap();
+        }
+        write!(&mut expected, "]").unwrap();
+        let mut actual = "[".to_owned();
+        for index in 0..actual_names_length {
+            let mut length: pyo3::ffi::Py_ssize_t = 0;
+            let data = unsafe {
+                pyo3::ffi::PyUnicode_AsUTF8AndSize(
+                    pyo3::ffi::PyTuple_GET_ITEM(actual_names, index),
+                    &mut length as *mut pyo3::ffi::Py_ssize_t,
+                )
+            } as *const u8;
+            write!(
+                &mut actual,
+                "\"{}\"{}",
+                std::str::from_utf8(unsafe { std::slice::from_raw_parts(data, length as usize) })
+                    .expect("pyo3::ffi::PyUnicode_AsUTF8AndSize returned valid UTF8 bytes"),
+                if index == actual_names_length - 1 {
+                    ""
+                } else {
+                    ", "
+                }
+            )
+            .unwrap();
+        }
+        write!(&mut actual, "]").unwrap();
+        return Err(CheckArrayError::ExtraFields { expected, actual }.into());
+    }
+    let length = (0..dimensions_length)
+        .map(|dimension| unsafe { *((*array).dimensions.offset(dimension as isize)) })
+        .product();
+    Ok((array, length, layout))
+}
+
+fn simple_description_to_string(
+    python: Python,
+    description: *mut numpy::npyffi::PyArray_Descr,
+) -> String {
+    format!(
+        "{}{}{}",
+        unsafe { (*description).byteorder } as u8 as char,
+        unsafe { (*description).type_ } as u8 as char,
+        unsafe { numpy::npyffi::PyDataType_ELSIZE(python, description) }
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayType {
+    Dvs,
+    AedatImu,
+    AedatTrigger,
+    Dat,
+    EsGeneric,
+    EsAtis,
+    EsColor,
+    EvtTrigger,
+    Rpm,
+    Flip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Empty,
+    Bool,
+    F32,
+    U8,
+    I8,
+    U16,
+    I16,
+    I32,
+    U64,
+    I64,
+    F64,
+    Object,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub null_terminated_name: &'static str,
+    pub title: Option<&'static str>,
+    pub field_type: FieldType,
+    pub accepted_types: &'static [FieldType], // also accepted by check_array, converted by the reader
+    pub aliases: &'static [&'static str],     // null-terminated names also accepted by check_array
+}
+
+impl Field {
+    pub const fn new(
+        null_terminated_name: &'static str,
+        title: Option<&'static str>,
+        field_type: FieldType,
+    ) -> Self {
+        Self {
+            null_terminated_name,
+            title,
+            field_type,
+            accepted_types: &[],
+            aliases: &[],
+        }
+    }
+
+    pub const fn aliased(self, aliases: &'static [&'static str]) -> Self {
+        Self { aliases, ..self }
+    }
+
+    // null_terminated_name first, then the aliases.
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        std::iter::once(self.null_terminated_name).chain(self.aliases.iter().copied())
+    }
+
+    pub const fn accepting(self, accepted_types: &'static [FieldType]) -> Self {
+        Self {
+            accepted_types,
+            ..self
+        }
+    }
+
+    // field_type first, then the accepted types.
+    pub fn types(&self) -> impl Iterator<Item = FieldType> + '_ {
+        std::iter::once(self.field_type).chain(self.accepted_types.iter().copied())
+    }
+
+    pub const fn size(&self) -> usize {
+        match self.field_type {
+            FieldType::Empty => 0,
+            FieldType::Bool => 1,
+            FieldType::F32 => 4,
+            FieldType::U8 => 1,
+            FieldType::I8 => 1,
+            FieldType::U16 => 2,
+            FieldType::I16 => 2,
+            FieldType::I32 => 4,
+            FieldType::U64 => 8,
+            FieldType::I64 => 8,
+            FieldType::F64 => 8,
+            FieldType::Object => std::mem::size_of::<usize>(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.null_terminated_name[0..self.null_terminated_name.len() - 1].to_owned()
+    }
+
+    pub fn num(&self, python: Python) -> std::ffi::c_int {
+        match self.field_type {
+            FieldType::Empty => panic!("Field::num called on an empty field"),
+            FieldType::Bool => bool::get_dtype(python).num(),
+            FieldType::F32 => f32::get_dtype(python).num(),
+            FieldType::U8 => u8::get_dtype(python).num(),
+            FieldType::I8 => i8::get_dtype(python).num(),
+            FieldType::U16 => u16::get_dtype(python).num(),
+            FieldType::I16 => i16::get_dtype(python).num(),
+            FieldType::I32 => i32::get_dtype(python).num(),
+            FieldType::U64 => u64::get_dtype(python).num(),
+            FieldType::I64 => i64::get_dtype(python).num(),
+            FieldType::F64 => f64::get_dtype(python).num(),
+            FieldType::Object => numpy::PyArrayDescr::object(python).num(),
+        }
+    }
+
+    pub fn dtype(&self, python: Python) -> *mut numpy::npyffi::PyArray_Descr {
+        let dtype = unsafe { numpy::PY_ARRAY_API.PyArray_DescrFromType(python, self.num(python)) };
+        if dtype.is_null() {
+            panic!("PyArray_DescrFromType failed");
+        }
+        dtype
+    }
+}
+
+const EMPTY: Field = Field {
+    null_terminated_name: "\0",
+    title: None,
+    field_type: FieldType::Empty,
+    accepted_types: &[],
+    aliases: &[],
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fields([Field; 11]);
+
+// Actual field types (see Field::accepting), offsets, and byte orders of a checked array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub fields: Fields,
+    pub offsets: [usize; 11],
+    pub swapped: [bool; 11], // big-endian fields, byte-swapped on read
+}
+
+impl Layout {
+    // (offset in the array's records, offset in the packed record, size, swapped) for every
+    // field, or None if the array's records are packed and little-endian.
+    fn gather(&self) -> Option<Vec<(usize, usize, usize, bool)>> {
+        let mut packed = true;
+        let mut packed_offset = 0;
+        let mut gather = Vec::with_capacity(self.fields.len());
+        for ((field, offset), swapped) in self
+            .fields
+            .iter()
+            .zip(self.offsets.iter())
+            .zip(self.swapped.iter())
+        {
+            packed &= *offset == packed_offset && !swapped;
+            gather.push((*offset, packed_offset, field.size(), *swapped));
+            packed_offset += field.size();
+        }
+        (!packed).then_some(gather)
+    }
+}
+
+impl ArrayType {
+    pub const fn fields(self) -> Fields {
+        Fields(match self {
+            ArrayType::Dvs => [
+                Field::new("t\0", None, FieldType::U64)
+                    .accepting(&[FieldType::I64, FieldType::F64]),
+                Field::new("x\0", None, FieldType::U16),
+                Field::new("y\0", None, FieldType::U16),
+                Field::new("on\0", Some("p"), FieldType::Bool)
+                    .accepting(&[
+                        FieldType::U8,
+                        FieldType::I8,
+                        FieldType::I16,
+                        FieldType::I32,
+                        FieldType::I64,
+                    ])
+                    .aliased(&["p\0", "polarity\0"]),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::AedatImu => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("temperature\0", None, FieldType::F32),
+                Field::new("accelerometer_x\0", None, FieldType::F32),
+                Field::new("accelerometer_y\0", None, FieldType::F32),
+                Field::new("accelerometer_z\0", None, FieldType::F32),
+                Field::new("gyroscope_x\0", None, FieldType::F32),
+                Field::new("gyroscope_y\0", None, FieldType::F32),
+                Field::new("gyroscope_z\0", None, FieldType::F32),
+                Field::new("magnetometer_x\0", None, FieldType::F32),
+                Field::new("magnetometer_y\0", None, FieldType::F32),
+                Field::new("magnetometer_z\0", None, FieldType::F32),
+            ],
+            ArrayType::AedatTrigger => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("source\0", None, FieldType::U8),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::Dat => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("x\0", None, FieldType::U16),
+                Field::new("y\0", None, FieldType::U16),
+                Field::new("payload\0", None, FieldType::U8),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::EsGeneric => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("bytes\0", None, FieldType::Object),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::EsAtis => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("x\0", None, FieldType::U16),
+                Field::new("y\0", None, FieldType::U16),
+                Field::new("exposure\0", Some("e"), FieldType::Bool),
+                Field::new("polarity\0", Some("p"), FieldType::Bool),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::EsColor => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("x\0", None, FieldType::U16),
+                Field::new("y\0", None, FieldType::U16),
+                Field::new("r\0", None, FieldType::U8),
+                Field::new("g\0", None, FieldType::U8),
+                Field::new("b\0", None, FieldType::U8),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::EvtTrigger => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("source\0", None, FieldType::U8),
+                Field::new("rising\0", None, FieldType::Bool),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::Rpm => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("rpm\0", None, FieldType::F64),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+            ArrayType::Flip => [
+                Field::new("t\0", None, FieldType::U64),
+                Field::new("x\0", None, FieldType::U16),
+                Field::new("y\0", None, FieldType::U16),
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+                EMPTY,
+            ],
+        })
+    }
+
+    #[allow(unused)]
+    pub fn dtype(self, python: Python) -> *mut numpy::npyffi::PyArray_Descr {
+        self.fields().dtype(python)
+    }
+
+    pub fn new_array(
+        self,
+        python: Python,
+        length: numpy::npyffi::npy_intp,
+    ) -> *mut numpy::npyffi::PyArrayObject {
+        self.fields().new_array(python, length)
+    }
+}
+
+pub struct FieldIterator<'a> {
+    fields: &'a Fields,
+    index: usize,
+    length: usize,
+}
+
+impl<'a> Iterator for FieldIterator<'a> {
+    type Item = Field;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.length {
+            self.index += 1;
+            Some(self.fields.0[self.index - 1])
+        } else {
+            None
+        }
+    }
+}
+
+impl Fields {
+    pub const fn len(&self) -> usize {
+        let mut index = 0;
+        while index < self.0.len() {
+            if matches!(self.0[index].field_type, FieldType::Empty) {
+                return index;
+            }
+            index += 1;
+        }
+        index
+    }
+
+    pub fn iter(&'_ self) -> FieldIterator<'_> {
+        FieldIterator {
+            fields: self,
+            index: 0,
+            length: self.len(),
+        }
+    }
+
+    pub fn dtype(&self, python: Python) -> *mut numpy::npyffi::PyArray_Descr {
+        unsafe {
+            let dtype_as_list = pyo3::ffi::PyList_New(self.len() as pyo3::ffi::Py_ssize_t);
+            for (index, field) in self.iter().enumerate() {
+                set_dtype_as_list_field(
+                    python,
+                    dtype_as_list,
+                    index,
+                    field.null_terminated_name,
+                    field.title,
+                    field.num(python),
+                );
+            }
+            let mut dtype: *mut numpy::npyffi::PyArray_Descr = std::ptr::null_mut();
+            if numpy::PY_ARRAY_API.PyArray_DescrConverter(python, dtype_as_list, &mut dtype) < 0 {
+                panic!("PyArray_DescrConverter failed");
+            }
+            pyo3::ffi::Py_DECREF(dtype_as_list);
+            dtype
+        }
+    }
+
+    pub fn new_array(
+        &self,
+        python: Python,
+        mut length: numpy::npyffi::npy_intp,
+    ) -> *mut numpy::npyffi::PyArrayObject {
+        let dtype = self.dtype(python);
+        unsafe {
+            numpy::PY_ARRAY_API.PyArray_NewFromDescr(
+                python,
+                numpy::PY_ARRAY_API
+                    .get_type_object(python, numpy::npyffi::array::NpyTypes::PyArray_Type),
+                dtype,
+                1_i32,
+                &mut length,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                0_i32,
+                std::ptr::null_mut(),
+            ) as *mut numpy::npyffi::PyArrayObject
+        }
+    }
+}
+
+unsafe fn set_dtype_as_list_field(
+    python: pyo3::Python,
+    list: *mut pyo3::ffi::PyObject,
+    index: usize,
+    null_terminated_name: &str,
+    title: Option<&str>,
+    numpy_type: std::ffi::c_int,
+) {
+    let tuple = pyo3::ffi::PyTuple_New(2);
+    if pyo3::ffi::PyTuple_SetItem(
+        tuple,
+        0 as pyo3::ffi::Py_ssize_t,
+        match title {
+            Some(title) => {
+                let tuple = pyo3::ffi::PyTuple_New(2);
+                if pyo3::ffi::PyTuple_SetItem(
+                    tuple,
+                    0 as pyo3::ffi::Py_ssize_t,
+                    pyo3::ffi::PyUnicode_FromStringAndSize(
+                        title.as_ptr() as *const std::ffi::c_char,
+                        title.len() as pyo3::ffi::Py_ssize_t,
+                    ),
+                ) < 0
+                {
+                    panic!("PyTuple_SetItem 1 failed");
+                }
+                if pyo3::ffi::PyTuple_SetItem(
+                    tuple,
+                    1 as pyo3::ffi::Py_ssize_t,
+                    pyo3::ffi::PyUnicode_FromStringAndSize(
+                        null_terminated_name.as_ptr() as *const std::ffi::c_char,
+                        (null_terminated_name.len() - 1) as pyo3::ffi::Py_ssize_t,
+                    ),
+                ) < 0
+                {
+                    panic!("PyTuple_SetItem 0 failed");
+                }
+                tuple
+            }
+            None => pyo3::ffi::PyUnicode_FromStringAndSize(
+                null_terminated_name.as_ptr() as *const std::ffi::c_char,
+                (null_terminated_name.len() - 1) as pyo3::ffi::Py_ssize_t,
+            ),
+        },
+    ) < 0
+    {
+        panic!("PyTuple_SetItem 0 failed");
+    }
+    if pyo3::ffi::PyTuple_SetItem(
+        tuple,
+        1 as pyo3::ffi::Py_ssize_t,
+        numpy::PY_ARRAY_API.PyArray_TypeObjectFromType(python, numpy_type),
+    ) < 0
+    {
+        panic!("PyTuple_SetItem 1 failed");
+    }
+    if pyo3::ffi::PyList_SetItem(list, index as pyo3::ffi::Py_ssize_t, tuple) < 0 {
+        panic!("PyList_SetItem failed");
+    }
+}
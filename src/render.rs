@@ -0,0 +1,80 @@
+// Colors match the default colormaps of the UI.
+pub const BACKGROUND_COLOR: [u8; 3] = [25, 25, 25];
+pub const ON_COLOR: [u8; 3] = [249, 255, 231];
+pub const OFF_COLOR: [u8; 3] = [0, 130, 169];
+pub const TIMELINE_COLOR: [u8; 3] = [0, 200, 83];
+pub const CLUSTER_COLOR: [u8; 3] = [255, 61, 0];
+pub const TIMELINE_OPACITY: f32 = 0.3;
+
+// Renders the accepted events of the last t_window µs (fading linearly with age, colored by
+// polarity), tints the blocks of the timelines used by the last sample, and draws the bounding
+// boxes of its clusters. The image is row-major with shape (height, width, 3).
+pub fn render(estimator: &crate::core::RpmEstimator, t_window: u64) -> Vec<u8> {
+    let configuration = estimator.configuration();
+    let width = configuration.width as usize;
+    let height = configuration.height as usize;
+    let t = estimator.last_t();
+    let mut image = Vec::with_capacity(width * height * 3);
+    for signed_t in estimator.surface() {
+        let (color, event_t) = match signed_t.signum() {
+            1 => (ON_COLOR, *signed_t as u64 - 1),
+            -1 => (OFF_COLOR, (-signed_t) as u64 - 1),
+            _ => {
+                image.extend_from_slice(&BACKGROUND_COLOR);
+                continue;
+            }
+        };
+        let age = t.saturating_sub(event_t);
+        if age < t_window {
+            image.extend(blend(
+                BACKGROUND_COLOR,
+                color,
+                1.0 - age as f32 / t_window as f32,
+            ));
+        } else {
+            image.extend_from_slice(&BACKGROUND_COLOR);
+        }
+    }
+    let spatial_downsampling = configuration.spatial_downsampling as usize;
+    for timeline_spectrum in estimator.timeline_spectra() {
+        let x0 = timeline_spectrum.x as usize;
+        let y0 = timeline_spectrum.y as usize;
+        for y in y0..(y0 + spatial_downsampling).min(height) {
+            for x in x0..(x0 + spatial_downsampling).min(width) {
+                let index = (x + y * width) * 3;
+                let color = [image[index], image[index + 1], image[index + 2]];
+                image[index..index + 3].copy_from_slice(&blend(
+                    color,
+                    TIMELINE_COLOR,
+                    TIMELINE_OPACITY,
+                ));
+            }
+        }
+    }
+    if let Some(clusters) = estimator.clusters().last() {
+        for cluster in clusters {
+            let (x0, y0) = (cluster.x0 as usize, cluster.y0 as usize);
+            let (x1, y1) = (cluster.x1 as usize - 1, cluster.y1 as usize - 1);
+            for x in x0..=x1 {
+                for y in [y0, y1] {
+                    image[(x + y * width) * 3..(x + y * width) * 3 + 3]
+                        .copy_from_slice(&CLUSTER_COLOR);
+                }
+            }
+            for y in y0..=y1 {
+                for x in [x0, x1] {
+                    image[(x + y * width) * 3..(x + y * width) * 3 + 3]
+                        .copy_from_slice(&CLUSTER_COLOR);
+                }
+            }
+        }
+    }
+    image
+}
+
+fn blend(background: [u8; 3], foreground: [u8; 3], opacity: f32) -> [u8; 3] {
+    [0, 1, 2].map(|channel| {
+        (background[channel] as f32 * (1.0 - opacity) + foreground[channel] as f32 * opacity)
+            .round() as u8
+    })
+}
@@ -0,0 +1,175 @@
+pub const DEFAULT_SMOOTHING_ALPHA: f64 = 0.2;
+pub const DEFAULT_PROCESS_NOISE: f64 = 10.0; // Hz² s⁻³, frequency acceleration spectral density
+pub const DEFAULT_MEASUREMENT_NOISE: f64 = 1.0; // Hz²
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Smoothing {
+    None,
+    Exponential,
+    Kalman,
+}
+
+impl std::str::FromStr for Smoothing {
+    type Err = crate::core::Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "none" => Ok(Smoothing::None),
+            "exponential" => Ok(Smoothing::Exponential),
+            "kalman" => Ok(Smoothing::Kalman),
+            _ => Err(crate::core::Error::Smoothing(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for Smoothing {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Smoothing::None => "none",
+            Smoothing::Exponential => "exponential",
+            Smoothing::Kalman => "kalman",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Empty,
+    Exponential {
+        frequency: f64,
+    },
+    Kalman {
+        t: u64,
+        frequency: f64,
+        velocity: f64,             // Hz/s
+        covariance: [[f64; 2]; 2], // frequency and velocity
+    },
+}
+
+// Smooths the frequency estimates of consecutive samples, either with an exponential moving
+// average or with a constant-velocity Kalman filter. Samples without a detected peak
+// (frequency 0) do not update the filter, which returns its prediction instead.
+#[derive(Debug, Clone)]
+pub struct Smoother {
+    smoothing: Smoothing,
+    alpha: f64,
+    process_noise: f64,
+    measurement_noise: f64,
+    state: State,
+}
+
+impl Smoother {
+    pub fn new(
+        smoothing: Smoothing,
+        alpha: f64,
+        process_noise: f64,
+        measurement_noise: f64,
+    ) -> Self {
+        Self {
+            smoothing,
+            alpha,
+            process_noise,
+            measurement_noise,
+            state: State::Empty,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.state = State::Empty;
+    }
+
+    // t is the end of the sample window in µs, frequency is in Hz.
+    pub fn update(&mut self, t: u64, frequency: f32) -> f32 {
+        let measured = frequency > 0.0;
+        let frequency = frequency as f64;
+        match (self.smoothing, self.state) {
+            (Smoothing::None, _) => return frequency as f32,
+            (
+                Smoothing::Exponential,
+                State::Exponential {
+                    frequency: previous,
+                },
+            ) => {
+                if measured {
+                    self.state = State::Exponential {
+                        frequency: previous + self.alpha * (frequency - previous),
+                    };
+                }
+            }
+            (Smoothing::Kalman, State::Kalman { .. }) => {
+                self.predict(t);
+                if measured {
+                    self.correct(frequency);
+                }
+            }
+            (Smoothing::Exponential, _) => {
+                if measured {
+                    self.state = State::Exponential { frequency };
+                }
+            }
+            (Smoothing::Kalman, _) => {
+                if measured {
+                    self.state = State::Kalman {
+                        t,
+                        frequency,
+                        velocity: 0.0,
+                        covariance: [[self.measurement_noise, 0.0], [0.0, 0.0]],
+                    };
+                }
+            }
+        }
+        match self.state {
+            State::Empty => 0.0,
+            State::Exponential { frequency } => frequency as f32,
+            State::Kalman { frequency, .. } => frequency.max(0.0) as f32,
+        }
+    }
+
+    fn predict(&mut self, t: u64) {
+        if let State::Kalman {
+            t: previous_t,
+            frequency,
+            velocity,
+            covariance: [[p00, p01], [p10, p11]],
+        } = self.state
+        {
+            let dt = t.saturating_sub(previous_t) as f64 / 1e6;
+            let q = self.process_noise;
+            self.state = State::Kalman {
+                t: t.max(previous_t),
+                frequency: frequency + dt * velocity,
+                velocity,
+                covariance: [
+                    [
+                        p00 + dt * (p10 + p01) + dt * dt * p11 + q * dt.powi(3) / 3.0,
+                        p01 + dt * p11 + q * dt.powi(2) / 2.0,
+                    ],
+                    [p10 + dt * p11 + q * dt.powi(2) / 2.0, p11 + q * dt],
+                ],
+            };
+        }
+    }
+
+    fn correct(&mut self, measurement: f64) {
+        if let State::Kalman {
+            t,
+            frequency,
+            velocity,
+            covariance: [[p00, p01], [p10, p11]],
+        } = self.state
+        {
+            let innovation_covariance = p00 + self.measurement_noise;
+            let gain = [p00 / innovation_covariance, p10 / innovation_covariance];
+            let innovation = measurement - frequency;
+            self.state = State::Kalman {
+                t,
+                frequency: frequency + gain[0] * innovation,
+                velocity: velocity + gain[1] * innovation,
+                covariance: [
+                    [(1.0 - gain[0]) * p00, (1.0 - gain[0]) * p01],
+                    [p10 - gain[1] * p00, p11 - gain[1] * p01],
+                ],
+            };
+        }
+    }
+}
@@ -0,0 +1,175 @@
+pub const MAGIC_NUMBER: &[u8] = b"FSPINSTATE";
+pub const VERSION: u8 = 32;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("the state does not start with the fidget-spinner magic number")]
+    MagicNumber,
+
+    #[error("unsupported state version {0} (expected {VERSION})")]
+    Version(u8),
+
+    #[error("the state ends unexpectedly")]
+    Truncated,
+
+    #[error("invalid state ({0})")]
+    Invalid(&'static str),
+
+    #[error(transparent)]
+    Configuration(#[from] crate::core::Error),
+}
+
+// Little-endian binary encoding, see RpmEstimator::to_state.
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self {
+            bytes: MAGIC_NUMBER.iter().copied().chain([VERSION]).collect(),
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.bytes.push(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn usize(&mut self, value: usize) {
+        self.u64(value as u64);
+    }
+
+    pub fn f64(&mut self, value: f64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.usize(value.len());
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn option_u64(&mut self, value: Option<u64>) {
+        self.bool(value.is_some());
+        self.u64(value.unwrap_or(0));
+    }
+
+    pub fn option_f64(&mut self, value: Option<f64>) {
+        self.bool(value.is_some());
+        self.f64(value.unwrap_or(0.0));
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        if !bytes.starts_with(MAGIC_NUMBER) {
+            return Err(Error::MagicNumber);
+        }
+        let mut decoder = Self {
+            bytes,
+            position: MAGIC_NUMBER.len(),
+        };
+        let version = decoder.u8()?;
+        if version != VERSION {
+            return Err(Error::Version(version));
+        }
+        Ok(decoder)
+    }
+
+    // Returns an error if there are trailing bytes.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.position == self.bytes.len() {
+            Ok(())
+        } else {
+            Err(Error::Invalid("trailing bytes"))
+        }
+    }
+
+    fn take<const LENGTH: usize>(&mut self) -> Result<[u8; LENGTH], Error> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + LENGTH)
+            .ok_or(Error::Truncated)?;
+        self.position += LENGTH;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, Error> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::Invalid("boolean out of range")),
+        }
+    }
+
+    pub fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    pub fn usize(&mut self) -> Result<usize, Error> {
+        usize::try_from(self.u64()?).map_err(|_| Error::Invalid("size out of range"))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_le_bytes(self.take()?))
+    }
+
+    pub fn string(&mut self) -> Result<String, Error> {
+        let length = self.usize()?;
+        let bytes = self
+            .bytes
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or(Error::Truncated)?;
+        self.position += length;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::Invalid("string is not UTF-8"))
+    }
+
+    pub fn option_u64(&mut self) -> Result<Option<u64>, Error> {
+        let is_some = self.bool()?;
+        let value = self.u64()?;
+        Ok(is_some.then_some(value))
+    }
+
+    pub fn option_f64(&mut self) -> Result<Option<f64>, Error> {
+        let is_some = self.bool()?;
+        let value = self.f64()?;
+        Ok(is_some.then_some(value))
+    }
+}
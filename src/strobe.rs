@@ -0,0 +1,50 @@
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("bins must be larger than 0")]
+    Bins,
+
+    #[error("no events are kept for strobe images (strobe_length is 0)")]
+    StrobeLength,
+
+    #[error("the last process call did not produce a sample with a valid frequency")]
+    Frequency,
+}
+
+// Folds the events kept by the estimator (see Configuration::strobe_length) with the rotation
+// period (60 / rpm) of the last valid sample. Each of the bins images accumulates the events of
+// one phase interval, hence shows the rotor as if it were frozen at that angle. The angular
+// reference is that of Sample::phase, so bin 0 starts at the same angle from one call to the
+// next. Counts are normalized by the largest count over all bins. The image is row-major with
+// shape (bins, height, width).
+pub fn strobe(estimator: &crate::core::RpmEstimator, bins: usize) -> Result<Vec<u8>, Error> {
+    if bins == 0 {
+        return Err(Error::Bins);
+    }
+    let configuration = estimator.configuration();
+    if configuration.strobe_length == 0 {
+        return Err(Error::StrobeLength);
+    }
+    let sample = estimator
+        .samples()
+        .iter()
+        .rev()
+        .find(|sample| sample.rpm.is_finite() && sample.rpm > 0.0)
+        .ok_or(Error::Frequency)?;
+    let period = 60e6 / sample.rpm as f64; // µs
+    let reference_t =
+        sample.t as f64 - sample.phase.unwrap_or(0.0) as f64 / std::f64::consts::TAU * period;
+    let pixels = configuration.width as usize * configuration.height as usize;
+    let mut counts = vec![0u32; bins * pixels];
+    for event in estimator.strobe_events() {
+        let turns = (event.t as f64 - reference_t) / period;
+        let bin = ((turns - turns.floor()) * bins as f64) as usize;
+        counts[bin.min(bins - 1) * pixels
+            + event.x as usize
+            + event.y as usize * configuration.width as usize] += 1;
+    }
+    let maximum = counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+    Ok(counts
+        .into_iter()
+        .map(|count| (count as f32 / maximum * 255.0).round() as u8)
+        .collect())
+}
@@ -0,0 +1,123 @@
+pub const SUBSAMPLING_WINDOW: u64 = 10_000; // µs, duration over which the event rate is measured
+const RANDOM_SEED: u64 = 0x9e3779b97f4a7c15; // xorshift64 state after a reset, must not be 0
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsampling {
+    Random,
+    Stratified,
+}
+
+impl std::str::FromStr for Subsampling {
+    type Err = crate::core::Error;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        match string {
+            "random" => Ok(Subsampling::Random),
+            "stratified" => Ok(Subsampling::Stratified),
+            _ => Err(crate::core::Error::Subsampling(string.to_owned())),
+        }
+    }
+}
+
+impl std::fmt::Display for Subsampling {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Subsampling::Random => "random",
+            Subsampling::Stratified => "stratified",
+        })
+    }
+}
+
+// Keeps a fraction threshold / rate of the events when the input rate (measured over the
+// previous SUBSAMPLING_WINDOW) exceeds threshold. Random subsampling keeps each event with that
+// probability, which thins the stream without changing the shape of its spectrum. Stratified
+// subsampling splits the events of each pixel into strata of 1 / fraction consecutive events
+// and keeps one event at a random position in each stratum, so that every part of the sensor
+// loses exactly the same share of its events. Keeping the same position in every stratum would
+// alias the periodic bursts of a passing blade.
+#[derive(Debug, Clone)]
+pub struct Subsampler {
+    width: u16,
+    subsampling: Subsampling,
+    threshold: Option<f64>, // events/s
+    window_start_t: Option<u64>,
+    window_events: u64,
+    fraction: f64,           // kept events / events, measured on the previous window
+    random_state: u64,       // xorshift64
+    strata: Vec<(f32, f32)>, // per pixel, position in the stratum and position of the kept event
+}
+
+impl Subsampler {
+    pub fn new(width: u16, height: u16, subsampling: Subsampling, threshold: Option<f64>) -> Self {
+        let mut subsampler = Self {
+            width,
+            subsampling,
+            threshold,
+            window_start_t: None,
+            window_events: 0,
+            fraction: 1.0,
+            random_state: RANDOM_SEED,
+            strata: if threshold.is_some() && subsampling == Subsampling::Stratified {
+                vec![(0.0, 0.0); width as usize * height as usize]
+            } else {
+                Vec::new()
+            },
+        };
+        subsampler.reset();
+        subsampler
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    pub fn reset(&mut self) {
+        self.window_start_t = None;
+        self.window_events = 0;
+        self.fraction = 1.0;
+        self.random_state = RANDOM_SEED;
+        for index in 0..self.strata.len() {
+            self.strata[index] = (0.0, self.random() as f32);
+        }
+    }
+
+    // Uniform in [0, 1[.
+    fn random(&mut self) -> f64 {
+        self.random_state ^= self.random_state << 13;
+        self.random_state ^= self.random_state >> 7;
+        self.random_state ^= self.random_state << 17;
+        (self.random_state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Returns true if the event is kept (timestamps must be monotonic).
+    pub fn keep(&mut self, x: u16, y: u16, t: u64) -> bool {
+        let Some(threshold) = self.threshold else {
+            return true;
+        };
+        let window_start_t = *self.window_start_t.get_or_insert(t);
+        if t >= window_start_t + SUBSAMPLING_WINDOW {
+            let rate = self.window_events as f64 * 1e6 / (t - window_start_t) as f64; // events/s
+            self.fraction = (threshold / rate).min(1.0);
+            self.window_start_t = Some(t);
+            self.window_events = 0;
+        }
+        self.window_events += 1;
+        if self.fraction >= 1.0 {
+            return true;
+        }
+        match self.subsampling {
+            Subsampling::Random => self.random() < self.fraction,
+            Subsampling::Stratified => {
+                let index = x as usize + y as usize * self.width as usize;
+                self.strata[index].0 += self.fraction as f32;
+                if self.strata[index].0 >= self.strata[index].1 {
+                    self.strata[index].0 -= 1.0;
+                    self.strata[index].1 = self.random() as f32;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+pub const DEFAULT_RPM: f64 = 600.0;
+pub const DEFAULT_BLADES: u32 = 2;
+pub const DEFAULT_BLADE_WIDTH: f64 = 0.25;
+pub const DEFAULT_EDGE_EVENTS: u32 = 4;
+pub const DEFAULT_EDGE_DURATION: u64 = 2000; // µs
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("rpm must be a strictly positive number (got {0})")]
+    Rpm(f64),
+
+    #[error("blades must be larger than 0")]
+    Blades,
+
+    #[error("blade_width must be in the range ]0, 1[ (got {0})")]
+    BladeWidth(f64),
+
+    #[error("noise_rate must be a positive number (got {0})")]
+    NoiseRate(f64),
+
+    #[error("edge_events must be larger than 0")]
+    EdgeEvents,
+}
+
+// Dark propeller rotating in front of a bright background, centred on the sensor. A bar is a
+// propeller with two blades.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Propeller {
+    pub width: u16,
+    pub height: u16,
+    pub rpm: f64,
+    pub blades: u32,
+    pub blade_width: f64, // fraction of the angle between two blades covered by a blade
+    pub radius: Option<f64>, // pixels, half the sensor's smallest dimension if None
+    pub edge_events: u32, // events per pixel per edge crossing
+    pub edge_duration: u64, // µs, the events of an edge crossing are spread over this duration
+    pub jitter: u64,      // µs, maximum timestamp offset
+    pub noise_rate: f64,  // background events per pixel per second
+    pub seed: u64,
+}
+
+impl Default for Propeller {
+    fn default() -> Self {
+        Self {
+            width: crate::core::DEFAULT_WIDTH,
+            height: crate::core::DEFAULT_HEIGHT,
+            rpm: DEFAULT_RPM,
+            blades: DEFAULT_BLADES,
+            blade_width: DEFAULT_BLADE_WIDTH,
+            radius: None,
+            edge_events: DEFAULT_EDGE_EVENTS,
+            edge_duration: DEFAULT_EDGE_DURATION,
+            jitter: 0,
+            noise_rate: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+impl Propeller {
+    // Returns the events in [begin_t, end_t) (µs), sorted by timestamp. Pixels covered by a blade
+    // emit Off events when its leading edge crosses them and On events when its trailing edge
+    // does. The same seed generates the same events.
+    pub fn events(&self, begin_t: u64, end_t: u64) -> Result<Vec<crate::core::Event>, Error> {
+        if !(self.rpm > 0.0 && self.rpm.is_finite()) {
+            return Err(Error::Rpm(self.rpm));
+        }
+        if self.blades == 0 {
+            return Err(Error::Blades);
+        }
+        if !(self.blade_width > 0.0 && self.blade_width < 1.0) {
+            return Err(Error::BladeWidth(self.blade_width));
+        }
+        if !(self.noise_rate >= 0.0 && self.noise_rate.is_finite()) {
+            return Err(Error::NoiseRate(self.noise_rate));
+        }
+        if self.edge_events == 0 {
+            return Err(Error::EdgeEvents);
+        }
+        let mut events = Vec::new();
+        if end_t <= begin_t {
+            return Ok(events);
+        }
+        let mut random = XorShift::new(self.seed);
+        let angular_speed = self.rpm * std::f64::consts::TAU / 60e6; // rad/µs
+        let blade_angle = std::f64::consts::TAU / self.blades as f64;
+        let blade_duration = self.blade_width * blade_angle / angular_speed; // µs
+        let edge_step = self.edge_duration as f64 / self.edge_events as f64; // µs
+        let radius = self
+            .radius
+            .unwrap_or(self.width.min(self.height) as f64 / 2.0);
+        let (center_x, center_y) = (self.width as f64 / 2.0, self.height as f64 / 2.0);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (dx, dy) = (x as f64 + 0.5 - center_x, y as f64 + 0.5 - center_y);
+                if dx.hypot(dy) > radius {
+                    continue;
+                }
+                // the leading edges cross the pixel when the rotation angle is
+                // angle + k * blade_angle
+                let angle = dy.atan2(dx).rem_euclid(std::f64::consts::TAU);
+                let mut k = ((begin_t as f64 * angular_speed - angle) / blade_angle).floor() - 1.0;
+                loop {
+                    let leading_t = (angle + k * blade_angle) / angular_speed;
+                    if leading_t >= end_t as f64 {
+                        break;
+                    }
+                    for (edge_t, polarity) in [
+                        (leading_t, neuromorphic_types::DvsPolarity::Off),
+                        (
+                            leading_t + blade_duration,
+                            neuromorphic_types::DvsPolarity::On,
+                        ),
+                    ] {
+                        for index in 0..self.edge_events {
+                            let t = edge_t + index as f64 * edge_step + random.jitter(self.jitter);
+                            if t >= begin_t as f64 && t < end_t as f64 {
+                                events.push(crate::core::Event {
+                                    t: t as u64,
+                                    x,
+                                    y,
+                                    polarity,
+                                });
+                            }
+                        }
+                    }
+                    k += 1.0;
+                }
+            }
+        }
+        // background activity is a Poisson process over the whole sensor
+        let pixels = self.width as usize * self.height as usize;
+        let rate = self.noise_rate * pixels as f64 / 1e6; // events/µs
+        if rate > 0.0 {
+            let mut t = begin_t as f64;
+            loop {
+                t -= (1.0 - random.unit()).ln() / rate;
+                if t >= end_t as f64 {
+                    break;
+                }
+                let index = ((random.unit() * pixels as f64) as usize).min(pixels - 1);
+                events.push(crate::core::Event {
+                    t: t as u64,
+                    x: (index % self.width as usize) as u16,
+                    y: (index / self.width as usize) as u16,
+                    polarity: if random.unit() < 0.5 {
+                        neuromorphic_types::DvsPolarity::Off
+                    } else {
+                        neuromorphic_types::DvsPolarity::On
+                    },
+                });
+            }
+        }
+        events.sort_by_key(|event| event.t);
+        Ok(events)
+    }
+}
+
+// xorshift64*, good enough for test data and free of dependencies.
+struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        Self {
+            // the state must not be zero
+            state: (seed ^ 0x9e3779b97f4a7c15).max(1),
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    // Uniform in [0, 1).
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in [-jitter, jitter].
+    fn jitter(&mut self, jitter: u64) -> f64 {
+        if jitter == 0 {
+            0.0
+        } else {
+            (self.unit() * 2.0 - 1.0) * jitter as f64
+        }
+    }
+}
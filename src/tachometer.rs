@@ -0,0 +1,89 @@
+pub const DEFAULT_PULSES_PER_REVOLUTION: u32 = 1;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("pulses_per_revolution must be larger than zero")]
+    PulsesPerRevolution,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comparison {
+    pub t: u64, // µs
+    pub estimated: f32,
+    pub reference: Option<f32>,
+    pub error: Option<f32>,
+}
+
+// Computes a reference RPM from the rising edges of an external tachometer (usually wired to
+// the camera's trigger input) and aligns it with the estimator's sample windows.
+#[derive(Debug, Clone, Default)]
+pub struct Tachometer {
+    previous_rising_t: Option<u64>,
+    previous_sample_t: u64,
+    pending_rising_ts: Vec<u64>,
+}
+
+impl Tachometer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.previous_rising_t = None;
+        self.previous_sample_t = 0;
+        self.pending_rising_ts.clear();
+    }
+
+    // The reference of a sample is the average period of the edges in its window (edges after
+    // the last sample are kept for the next call). Samples without any complete period in their
+    // window have no reference.
+    pub fn compare(
+        &mut self,
+        samples: &[crate::core::Sample],
+        rising_ts: &[u64],
+        pulses_per_revolution: u32,
+    ) -> Result<Vec<Comparison>, Error> {
+        if pulses_per_revolution == 0 {
+            return Err(Error::PulsesPerRevolution);
+        }
+        self.pending_rising_ts.extend_from_slice(rising_ts);
+        self.pending_rising_ts.sort_unstable();
+        let mut comparisons = Vec::with_capacity(samples.len());
+        let mut rising_index = 0;
+        for sample in samples {
+            let mut periods_sum = 0;
+            let mut periods_count = 0;
+            while rising_index < self.pending_rising_ts.len()
+                && self.pending_rising_ts[rising_index] <= sample.t
+            {
+                let rising_t = self.pending_rising_ts[rising_index];
+                if let Some(previous_rising_t) = self.previous_rising_t {
+                    if rising_t > previous_rising_t && rising_t > self.previous_sample_t {
+                        periods_sum += rising_t - previous_rising_t;
+                        periods_count += 1;
+                    }
+                }
+                self.previous_rising_t = Some(rising_t);
+                rising_index += 1;
+            }
+            let reference = if periods_count > 0 {
+                Some(
+                    (60e6 * periods_count as f64
+                        / (periods_sum as f64 * pulses_per_revolution as f64))
+                        as f32,
+                )
+            } else {
+                None
+            };
+            comparisons.push(Comparison {
+                t: sample.t,
+                estimated: sample.rpm,
+                reference,
+                error: reference.map(|reference| sample.rpm - reference),
+            });
+            self.previous_sample_t = sample.t;
+        }
+        self.pending_rising_ts.drain(0..rising_index);
+        Ok(comparisons)
+    }
+}
@@ -0,0 +1,307 @@
+pub const RING_ACTIVITY_FRACTION: f64 = 0.25; // of the largest activity
+pub const DIRECTION_AGREEMENT: f64 = 0.25; // minimum |agreement| in [0, 1], see direction
+
+// Position and size of the spinner, in sensor pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Track {
+    pub x: f32,
+    pub y: f32,
+    // 2 × the standard deviation along the major axis, which is the radius of a uniformly
+    // active disk (seen at any angle)
+    pub radius: f32,
+}
+
+// Activity-weighted mean and covariance of the timelines' block centres. Returns None if no
+// timeline is active.
+pub fn estimate(
+    activities_and_indices: &[(f64, usize)],
+    downsampled_width: u16,
+    spatial_downsampling: u16,
+) -> Option<Track> {
+    let mut weights = 0.0;
+    let mut x = 0.0;
+    let mut y = 0.0;
+    let mut squared_x = 0.0;
+    let mut squared_y = 0.0;
+    let mut xy = 0.0;
+    for (activity, index) in activities_and_indices.iter() {
+        if *activity <= 0.0 {
+            continue;
+        }
+        let block_x =
+            ((index % downsampled_width as usize) as f64 + 0.5) * spatial_downsampling as f64;
+        let block_y =
+            ((index / downsampled_width as usize) as f64 + 0.5) * spatial_downsampling as f64;
+        weights += activity;
+        x += activity * block_x;
+        y += activity * block_y;
+        squared_x += activity * block_x * block_x;
+        squared_y += activity * block_y * block_y;
+        xy += activity * block_x * block_y;
+    }
+    if weights <= 0.0 {
+        return None;
+    }
+    let (mean_x, mean_y) = (x / weights, y / weights);
+    let variance_x = squared_x / weights - mean_x * mean_x;
+    let variance_y = squared_y / weights - mean_y * mean_y;
+    let covariance = xy / weights - mean_x * mean_y;
+    let major_variance = (variance_x + variance_y) / 2.0
+        + (((variance_x - variance_y) / 2.0).powi(2) + covariance.powi(2)).sqrt();
+    Some(Track {
+        x: mean_x as f32,
+        y: mean_y as f32,
+        radius: (2.0 * major_variance.max(0.0).sqrt()) as f32,
+    })
+}
+
+// Moves roi (without resizing it) so that it is centred on the track, clamped to the sensor.
+// The ROI is left as is if its centre is within threshold pixels of the track on both axes.
+pub fn recenter(
+    roi: crate::core::Roi,
+    track: Track,
+    width: u16,
+    height: u16,
+    threshold: f32,
+) -> crate::core::Roi {
+    let recenter_axis = |start: u16, end: u16, centre: f32, size: u16| {
+        let length = end - start;
+        if ((start as f32 + end as f32) / 2.0 - centre).abs() <= threshold {
+            return start;
+        }
+        (centre - length as f32 / 2.0)
+            .round()
+            .clamp(0.0, (size - length) as f32) as u16
+    };
+    let x0 = recenter_axis(roi.x0, roi.x1, track.x, width);
+    let y0 = recenter_axis(roi.y0, roi.y1, track.y, height);
+    crate::core::Roi {
+        x0,
+        y0,
+        x1: x0 + (roi.x1 - roi.x0),
+        y1: y0 + (roi.y1 - roi.y0),
+    }
+}
+
+// Apparent ellipse of the rotor disc, in sensor pixels. A disc seen at an angle projects to an
+// ellipse whose minor axis is the major axis times the cosine of the tilt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geometry {
+    pub x: f32,
+    pub y: f32,
+    pub semi_major: f32,
+    pub semi_minor: f32,
+    pub angle: f32, // rad, in [-π/2, π/2], orientation of the major axis (x towards y)
+    // rad, in [0, π/2], angle between the rotation plane and the sensor plane, acos(semi_minor /
+    // semi_major) (semi_minor / blade length is more accurate if the blade length is known)
+    pub tilt: f32,
+}
+
+// Fits an ellipse to the outline of the high-activity timelines (activity larger than
+// RING_ACTIVITY_FRACTION times the largest, indexed by timeline). The outline's points are
+// the active timelines with an inactive (or missing) 4-neighbour. The conic
+// a x² + b xy + c y² + d x + e y = 1 is fitted to the points (relative to their mean) with
+// linear least squares. Returns None if the outline has fewer than 5 points or if the conic is
+// not an ellipse.
+pub fn fit_ellipse(
+    activities: &[f64],
+    downsampled_width: u16,
+    spatial_downsampling: u16,
+) -> Option<Geometry> {
+    let width = downsampled_width as usize;
+    let height = activities.len() / width;
+    let maximum = activities.iter().copied().fold(0.0, f64::max);
+    if maximum <= 0.0 {
+        return None;
+    }
+    let threshold = maximum * RING_ACTIVITY_FRACTION;
+    let active = |x: usize, y: usize| activities[x + y * width] >= threshold;
+    let mut points = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            if active(x, y)
+                && (x == 0
+                    || y == 0
+                    || x == width - 1
+                    || y == height - 1
+                    || !active(x - 1, y)
+                    || !active(x + 1, y)
+                    || !active(x, y - 1)
+                    || !active(x, y + 1))
+            {
+                points.push((
+                    (x as f64 + 0.5) * spatial_downsampling as f64,
+                    (y as f64 + 0.5) * spatial_downsampling as f64,
+                ));
+            }
+        }
+    }
+    if points.len() < 5 {
+        return None;
+    }
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / points.len() as f64;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    // normal equations of the least squares problem
+    let mut system = [[0.0f64; 6]; 5];
+    for (x, y) in points.iter() {
+        let (x, y) = (x - mean_x, y - mean_y);
+        let row = [x * x, x * y, y * y, x, y];
+        for (equation, value) in system.iter_mut().zip(row.iter()) {
+            for (coefficient, other_value) in equation.iter_mut().zip(row.iter()) {
+                *coefficient += value * other_value;
+            }
+            equation[5] += value;
+        }
+    }
+    let [a, b, c, d, e] = solve(system)?;
+    // centre, where the gradient of the conic is zero
+    let determinant = 4.0 * a * c - b * b;
+    if determinant <= 0.0 {
+        return None;
+    }
+    let centre_x = (b * e - 2.0 * c * d) / determinant;
+    let centre_y = (b * d - 2.0 * a * e) / determinant;
+    // the conic is a x'² + b x'y' + c y'² = scale around the centre
+    let scale = 1.0 - (d * centre_x + e * centre_y) / 2.0;
+    let half_difference = (((a - c) / 2.0).powi(2) + (b / 2.0).powi(2)).sqrt();
+    let (small, large) = (
+        (a + c) / 2.0 - half_difference,
+        (a + c) / 2.0 + half_difference,
+    );
+    if small * scale <= 0.0 || large * scale <= 0.0 {
+        return None;
+    }
+    // outline block centres are half a block inside the edge
+    let semi_major = (scale / small).sqrt() + spatial_downsampling as f64 / 2.0;
+    let semi_minor = (scale / large).sqrt() + spatial_downsampling as f64 / 2.0;
+    // 0.5 atan2(b, a - c) is the direction of the larger eigenvalue (the minor axis if the
+    // scale is positive)
+    let mut angle = 0.5 * b.atan2(a - c) + std::f64::consts::FRAC_PI_2;
+    if scale < 0.0 {
+        angle -= std::f64::consts::FRAC_PI_2;
+    }
+    if angle > std::f64::consts::FRAC_PI_2 {
+        angle -= std::f64::consts::PI;
+    }
+    Some(Geometry {
+        x: (centre_x + mean_x) as f32,
+        y: (centre_y + mean_y) as f32,
+        semi_major: semi_major as f32,
+        semi_minor: semi_minor as f32,
+        angle: angle as f32,
+        tilt: (semi_minor / semi_major).clamp(0.0, 1.0).acos() as f32,
+    })
+}
+
+// Rotation direction as seen by the sensor (whose y axis points down).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Clockwise,
+    Counterclockwise,
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Direction::Clockwise => "clockwise",
+            Direction::Counterclockwise => "counterclockwise",
+        })
+    }
+}
+
+// Estimates the rotation direction from the phases of the timelines (in turns of the detected
+// frequency, the later the flips the smaller the phase) and their positions (pixels) around the
+// centre (x, y). Blades reach the timelines in order of increasing angle if the rotation is
+// clockwise, hence the phase decreases with the angle. Pairs of timelines closer than half a
+// period of maximum_blade_count blades are compared (farther pairs may wrap around), the
+// agreement is the sum of the phase differences times the angle differences, normalized by the
+// sum of their absolute values. Returns None if the agreement's magnitude is smaller than
+// DIRECTION_AGREEMENT.
+pub fn direction(
+    phases_and_positions: &[(f64, f64, f64)],
+    x: f32,
+    y: f32,
+    maximum_blade_count: usize,
+) -> Option<Direction> {
+    let maximum_angle = std::f64::consts::PI / maximum_blade_count.max(1) as f64; // rad
+    let angles: Vec<Option<f64>> = phases_and_positions
+        .iter()
+        .map(|(_, position_x, position_y)| {
+            let (dx, dy) = (position_x - x as f64, position_y - y as f64);
+            // angles are meaningless on the rotation axis
+            (dx.hypot(dy) >= 1.0).then(|| dy.atan2(dx))
+        })
+        .collect();
+    let mut sum = 0.0;
+    let mut absolute_sum = 0.0;
+    for (index, ((phase, _, _), angle)) in
+        phases_and_positions.iter().zip(angles.iter()).enumerate()
+    {
+        let Some(angle) = angle else {
+            continue;
+        };
+        for ((other_phase, _, _), other_angle) in phases_and_positions
+            .iter()
+            .zip(angles.iter())
+            .skip(index + 1)
+        {
+            let Some(other_angle) = other_angle else {
+                continue;
+            };
+            let angle_difference = (other_angle - angle + std::f64::consts::PI)
+                .rem_euclid(std::f64::consts::TAU)
+                - std::f64::consts::PI;
+            if angle_difference.abs() >= maximum_angle {
+                continue;
+            }
+            let phase_difference = (other_phase - phase + 0.5).rem_euclid(1.0) - 0.5;
+            sum += phase_difference * angle_difference;
+            absolute_sum += (phase_difference * angle_difference).abs();
+        }
+    }
+    if absolute_sum <= 0.0 {
+        return None;
+    }
+    let agreement = sum / absolute_sum;
+    if agreement <= -DIRECTION_AGREEMENT {
+        Some(Direction::Clockwise)
+    } else if agreement >= DIRECTION_AGREEMENT {
+        Some(Direction::Counterclockwise)
+    } else {
+        None
+    }
+}
+
+// Gaussian elimination with partial pivoting of an augmented 5 × 5 system. Returns None if
+// the system is singular.
+fn solve(mut system: [[f64; 6]; 5]) -> Option<[f64; 5]> {
+    for column in 0..5 {
+        let pivot = (column..5)
+            .max_by(|a, b| {
+                system[*a][column]
+                    .abs()
+                    .total_cmp(&system[*b][column].abs())
+            })
+            .expect("the range is not empty");
+        if system[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        system.swap(column, pivot);
+        let (upper, lower) = system.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for row in lower.iter_mut() {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot_value) in row[column..].iter_mut().zip(pivot_row[column..].iter()) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    let mut solution = [0.0; 5];
+    for row in (0..5).rev() {
+        let sum: f64 = (row + 1..5)
+            .map(|index| system[row][index] * solution[index])
+            .sum();
+        solution[row] = (system[row][5] - sum) / system[row][row];
+    }
+    Some(solution)
+}
@@ -0,0 +1,119 @@
+use fidget_spinner::budget::Degradation;
+use fidget_spinner::core::{Configuration, Event, Parameters, RpmEstimator};
+use fidget_spinner::synthetic::Propeller;
+
+#[test]
+fn next() {
+    let max_latency = std::time::Duration::from_millis(10);
+    let over = std::time::Duration::from_millis(11);
+    let within = std::time::Duration::from_millis(7);
+    let under = std::time::Duration::from_millis(4);
+    let mut degradation = Degradation::None;
+    for expected in [
+        Degradation::FewerTimelines,
+        Degradation::SkippedWindows,
+        Degradation::CoarseGrid,
+        Degradation::CoarseGrid,
+    ] {
+        degradation = degradation.next(over, max_latency);
+        assert_eq!(degradation, expected);
+    }
+    assert_eq!(
+        degradation.next(within, max_latency),
+        Degradation::CoarseGrid
+    );
+    for expected in [
+        Degradation::SkippedWindows,
+        Degradation::FewerTimelines,
+        Degradation::None,
+        Degradation::None,
+    ] {
+        degradation = degradation.next(under, max_latency);
+        assert_eq!(degradation, expected);
+    }
+    assert_eq!(
+        Degradation::SkippedWindows.applied(),
+        [Degradation::FewerTimelines, Degradation::SkippedWindows]
+    );
+    assert!(Degradation::None.applied().is_empty());
+}
+
+#[test]
+fn degraded_estimates() {
+    let propeller = Propeller {
+        width: 64,
+        height: 64,
+        jitter: 50,
+        ..Default::default()
+    };
+    let parameters = Parameters {
+        amplitude_threshold: 0.0,
+        frequency_multiplier: 1.0 / propeller.blades as f32,
+        ..Default::default()
+    };
+    // every call exceeds the budget, hence the level goes up by one per call
+    let mut estimator = RpmEstimator::new(Configuration {
+        width: propeller.width,
+        height: propeller.height,
+        max_latency_ms: Some(1e-9),
+        ..Default::default()
+    })
+    .unwrap();
+    let chunk = 1_000_000;
+    let mut samples = Vec::new();
+    for (index, expected) in [
+        Degradation::None,
+        Degradation::FewerTimelines,
+        Degradation::SkippedWindows,
+        Degradation::CoarseGrid,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        assert_eq!(estimator.degradation(), expected);
+        let begin_t = index as u64 * chunk;
+        estimator
+            .process(
+                &propeller.events(begin_t, begin_t + chunk).unwrap(),
+                &parameters,
+            )
+            .unwrap();
+        assert!(estimator
+            .samples()
+            .iter()
+            .all(|sample| sample.degradation == expected));
+        samples.push(estimator.samples().to_vec());
+    }
+    // SkippedWindows and CoarseGrid merge every other window into the next one
+    assert_eq!(samples[1].len(), 10);
+    assert_eq!(samples[2].len(), 5);
+    assert_eq!(samples[3].len(), 5);
+    for (index, samples) in samples.iter().enumerate().skip(1) {
+        let estimated = samples.last().unwrap().rpm as f64;
+        assert!(
+            (estimated - propeller.rpm).abs() < propeller.rpm * 0.05,
+            "expected {} RPM in chunk {index}, got {estimated}",
+            propeller.rpm
+        );
+    }
+}
+
+#[test]
+fn failed_calls_count() {
+    let mut estimator = RpmEstimator::new(Configuration {
+        width: 16,
+        height: 16,
+        max_latency_ms: Some(1e-9),
+        ..Default::default()
+    })
+    .unwrap();
+    let event = Event {
+        t: 0,
+        x: 16,
+        y: 0,
+        polarity: neuromorphic_types::DvsPolarity::On,
+    };
+    assert!(estimator.process(&[event], &Parameters::default()).is_err());
+    assert_eq!(estimator.degradation(), Degradation::FewerTimelines);
+    assert!(estimator.statistics().processing_duration > std::time::Duration::ZERO);
+}
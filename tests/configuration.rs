@@ -0,0 +1,52 @@
+use fidget_spinner::core::{Configuration, Error};
+
+#[test]
+fn hop_duration() {
+    let configuration = Configuration::builder().hop_duration(0.05).build().unwrap();
+    assert!((configuration.sampling_frequency - 20.0).abs() < 1e-9);
+    for hop_duration in [0.0, -0.1, f64::NAN, f64::INFINITY] {
+        assert!(matches!(
+            Configuration::builder().hop_duration(hop_duration).build(),
+            Err(Error::HopDuration(_))
+        ));
+    }
+    // a later sampling_frequency replaces the hop duration
+    let configuration = Configuration::builder()
+        .hop_duration(0.0)
+        .sampling_frequency(5.0)
+        .build()
+        .unwrap();
+    assert_eq!(configuration.sampling_frequency, 5.0);
+}
+
+#[test]
+fn spectral_smoothing() {
+    assert!(Configuration::builder()
+        .spectral_smoothing(Some(0.5))
+        .build()
+        .is_ok());
+    for spectral_smoothing in [0.0, 1.5, f64::NAN] {
+        assert!(matches!(
+            Configuration::builder()
+                .spectral_smoothing(Some(spectral_smoothing))
+                .build(),
+            Err(Error::SpectralSmoothing(_))
+        ));
+    }
+}
+
+#[test]
+fn smoothing_alpha() {
+    assert!(Configuration::builder()
+        .smoothing_alpha(0.3)
+        .build()
+        .is_ok());
+    for smoothing_alpha in [0.0, 1.5, f64::NAN] {
+        assert!(matches!(
+            Configuration::builder()
+                .smoothing_alpha(smoothing_alpha)
+                .build(),
+            Err(Error::SmoothingAlpha(_))
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::Write;
+
+fn temporary_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("fidget-spinner-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn aedat2_timestamp_wrap() {
+    let path = temporary_path("wrap.aedat");
+    {
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"#!AER-DAT2.0\r\n# AEChip: DAVIS240C\r\n")
+            .unwrap();
+        let dvs = (2 << 22) | (1 << 12);
+        let aps = 1 << 31;
+        for (address, t) in [
+            (dvs, 100u32),
+            (aps, 50), // APS records are not monotonic and must not count as a wrap
+            (dvs, 200),
+            (dvs, u32::MAX - 10),
+            (dvs, 5), // wrap
+        ] {
+            file.write_all(&(address as u32).to_be_bytes()).unwrap();
+            file.write_all(&t.to_be_bytes()).unwrap();
+        }
+    }
+    let decoder = fidget_spinner::aedat::Decoder::new(&path, 2).unwrap();
+    let mut ts = Vec::new();
+    for chunk in decoder {
+        for event in chunk.unwrap().events {
+            assert_eq!((event.x, event.y), (1, 2));
+            ts.push(event.t);
+        }
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(ts, [100, 200, (1 << 32) - 11, (1 << 32) + 5]);
+}
+
+#[test]
+fn dat_timestamp_wrap() {
+    let path = temporary_path("wrap.dat");
+    {
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(b"% Version 2\n% geometry 16x16\n").unwrap();
+        file.write_all(&[0, 8]).unwrap();
+        for t in [
+            100u32,
+            90, // small reorder, not a wrap
+            200,
+            u32::MAX - 10,
+            5,             // wrap
+            u32::MAX - 20, // late event from the previous epoch
+            6,
+        ] {
+            file.write_all(&t.to_le_bytes()).unwrap();
+            file.write_all(&(1u32 | (2 << 14)).to_le_bytes()).unwrap();
+        }
+    }
+    let decoder = fidget_spinner::dat::Decoder::new(&path, 3).unwrap();
+    let mut ts = Vec::new();
+    for events in decoder {
+        for event in events.unwrap() {
+            assert_eq!((event.x, event.y), (1, 2));
+            ts.push(event.t);
+        }
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        ts,
+        [
+            100,
+            90,
+            200,
+            (1 << 32) - 11,
+            (1 << 32) + 5,
+            (1 << 32) - 21,
+            (1 << 32) + 6
+        ]
+    );
+}
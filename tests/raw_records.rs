@@ -0,0 +1,162 @@
+use fidget_spinner::core::Event;
+use fidget_spinner::python::{ArrayType, Layout, RawRecords};
+
+// These tests exercise the record layout logic without a Python interpreter, the numpy side of
+// check_array is covered by tests/test_check_array.py.
+
+fn event(t: u64, x: u16, y: u16, on: bool) -> Event {
+    Event {
+        t,
+        x,
+        y,
+        polarity: if on {
+            neuromorphic_types::DvsPolarity::On
+        } else {
+            neuromorphic_types::DvsPolarity::Off
+        },
+    }
+}
+
+fn dvs_layout(offsets: [usize; 4], swapped: bool) -> Layout {
+    let mut layout = Layout {
+        fields: ArrayType::Dvs.fields(),
+        offsets: [0; 11],
+        swapped: [false; 11],
+    };
+    layout.offsets[0..4].copy_from_slice(&offsets);
+    // the polarity is a single byte
+    layout.swapped[0..3].fill(swapped);
+    layout
+}
+
+// DvsEvent does not implement PartialEq.
+fn fields(events: impl IntoIterator<Item = Event>) -> Vec<(u64, u16, u16, u8)> {
+    events
+        .into_iter()
+        .map(|event| (event.t, event.x, event.y, event.polarity as u8))
+        .collect()
+}
+
+// start is the offset of the first record in data (in bytes).
+fn read(
+    data: &[u8],
+    start: usize,
+    length: isize,
+    stride: isize,
+    dimensions_and_strides: Option<Vec<(isize, isize)>>,
+    layout: &Layout,
+) -> Vec<(u64, u16, u16, u8)> {
+    fields(unsafe {
+        RawRecords::<Event>::from_raw_parts(
+            data.as_ptr().add(start),
+            length as _,
+            stride,
+            dimensions_and_strides.map(|dimensions_and_strides| {
+                dimensions_and_strides
+                    .into_iter()
+                    .map(|(dimension, stride)| (dimension as _, stride))
+                    .collect()
+            }),
+            layout,
+        )
+    })
+}
+
+fn packed(events: &[Event]) -> Vec<u8> {
+    let mut data = Vec::new();
+    for event in events {
+        data.extend_from_slice(&{ event.t }.to_le_bytes());
+        data.extend_from_slice(&{ event.x }.to_le_bytes());
+        data.extend_from_slice(&{ event.y }.to_le_bytes());
+        data.push(matches!(event.polarity, neuromorphic_types::DvsPolarity::On) as u8);
+    }
+    data
+}
+
+fn events() -> Vec<Event> {
+    vec![
+        event(1, 2, 3, true),
+        event(0x0102030405060708, 0x0a0b, 0x0c0d, false),
+        event(u64::MAX, u16::MAX, 0, true),
+    ]
+}
+
+#[test]
+fn packed_records() {
+    let events = events();
+    let data = packed(&events);
+    let layout = dvs_layout([0, 8, 10, 12], false);
+    assert_eq!(read(&data, 0, 3, 13, None, &layout), fields(events));
+}
+
+#[test]
+fn reordered_and_padded_fields() {
+    // polarity at 0, y at 2, t at 8, x at 16, 24 bytes per record
+    let events = events();
+    let mut data = vec![0xffu8; events.len() * 24];
+    for (index, event) in events.iter().enumerate() {
+        let record = &mut data[index * 24..(index + 1) * 24];
+        record[0] = matches!(event.polarity, neuromorphic_types::DvsPolarity::On) as u8;
+        record[2..4].copy_from_slice(&{ event.y }.to_le_bytes());
+        record[8..16].copy_from_slice(&{ event.t }.to_le_bytes());
+        record[16..18].copy_from_slice(&{ event.x }.to_le_bytes());
+    }
+    let layout = dvs_layout([8, 16, 2, 0], false);
+    assert_eq!(read(&data, 0, 3, 24, None, &layout), fields(events));
+}
+
+#[test]
+fn big_endian_fields() {
+    let events = events();
+    let mut data = Vec::new();
+    for event in events.iter() {
+        data.extend_from_slice(&{ event.t }.to_be_bytes());
+        data.extend_from_slice(&{ event.x }.to_be_bytes());
+        data.extend_from_slice(&{ event.y }.to_be_bytes());
+        data.push(matches!(event.polarity, neuromorphic_types::DvsPolarity::On) as u8);
+    }
+    let layout = dvs_layout([0, 8, 10, 12], true);
+    assert_eq!(read(&data, 0, 3, 13, None, &layout), fields(events));
+}
+
+#[test]
+fn strides() {
+    let events: Vec<Event> = (0..12)
+        .map(|index| event(index, index as u16, 0, index % 2 == 0))
+        .collect();
+    let data = packed(&events);
+    let layout = dvs_layout([0, 8, 10, 12], false);
+
+    // every other record (a[::2]) and reversed records (a[::-1])
+    assert_eq!(
+        read(&data, 0, 6, 26, None, &layout),
+        fields(events.iter().copied().step_by(2))
+    );
+    assert_eq!(
+        read(&data, 11 * 13, 12, -13, None, &layout),
+        fields(events.iter().copied().rev())
+    );
+
+    // the transpose of a 3 x 4 array is read in C order
+    assert_eq!(
+        read(&data, 0, 12, 13, Some(vec![(4, 13), (3, 4 * 13)]), &layout),
+        fields(
+            (0..4)
+                .flat_map(|column| (0..3).map(move |row| row * 4 + column))
+                .map(|index| events[index])
+        )
+    );
+
+    // a[1:, ::2] on a 3 x 4 array
+    assert_eq!(
+        read(
+            &data,
+            4 * 13,
+            4,
+            13,
+            Some(vec![(2, 4 * 13), (2, 2 * 13)]),
+            &layout
+        ),
+        fields([4, 6, 8, 10].into_iter().map(|index| events[index]))
+    );
+}
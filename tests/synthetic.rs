@@ -0,0 +1,259 @@
+use fidget_spinner::core::{Configuration, Event, Flip, Parameters, RpmEstimator};
+use fidget_spinner::synthetic::Propeller;
+
+// DvsEvent does not implement PartialEq.
+fn fields(events: &[Event]) -> Vec<(u64, u16, u16, u8)> {
+    events
+        .iter()
+        .map(|event| (event.t, event.x, event.y, event.polarity as u8))
+        .collect()
+}
+
+fn estimate(propeller: Propeller) -> f32 {
+    let mut estimator = RpmEstimator::new(Configuration {
+        width: propeller.width,
+        height: propeller.height,
+        ..Default::default()
+    })
+    .unwrap();
+    estimator
+        .process(
+            &propeller.events(0, 2_500_000).unwrap(),
+            &Parameters {
+                amplitude_threshold: 0.0,
+                // the estimator measures the blade pass frequency
+                frequency_multiplier: 1.0 / propeller.blades as f32,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    estimator.samples().last().unwrap().rpm
+}
+
+#[test]
+fn propeller() {
+    for (rpm, blades) in [(600.0, 2), (900.0, 1)] {
+        let estimated = estimate(Propeller {
+            width: 64,
+            height: 64,
+            rpm,
+            blades,
+            jitter: 50,
+            ..Default::default()
+        });
+        assert!(
+            (estimated as f64 - rpm).abs() < rpm * 0.02,
+            "expected {rpm} RPM, got {estimated}"
+        );
+    }
+}
+
+#[test]
+fn noisy_propeller() {
+    let estimated = estimate(Propeller {
+        width: 64,
+        height: 64,
+        jitter: 100,
+        noise_rate: 2.0,
+        ..Default::default()
+    });
+    assert!(
+        (estimated as f64 - fidget_spinner::synthetic::DEFAULT_RPM).abs() < 12.0,
+        "expected {} RPM, got {estimated}",
+        fidget_spinner::synthetic::DEFAULT_RPM
+    );
+}
+
+#[test]
+fn rotor_at_the_border() {
+    // a 2 blocks wide strip of the rotor, every block is on the border
+    let propeller = Propeller {
+        width: 64,
+        height: 64,
+        jitter: 50,
+        ..Default::default()
+    };
+    let events: Vec<_> = propeller
+        .events(0, 2_500_000)
+        .unwrap()
+        .into_iter()
+        .filter(|event| event.x < 8)
+        .collect();
+    let mut estimator = RpmEstimator::new(Configuration {
+        width: 8,
+        height: propeller.height,
+        ..Default::default()
+    })
+    .unwrap();
+    estimator
+        .process(
+            &events,
+            &Parameters {
+                amplitude_threshold: 0.0,
+                frequency_multiplier: 1.0 / propeller.blades as f32,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let estimated = estimator.samples().last().unwrap().rpm;
+    assert!(
+        (estimated as f64 - propeller.rpm).abs() < propeller.rpm * 0.02,
+        "expected {} RPM, got {estimated}",
+        propeller.rpm
+    );
+}
+
+#[test]
+fn deterministic() {
+    let propeller = Propeller {
+        width: 32,
+        height: 32,
+        jitter: 100,
+        noise_rate: 10.0,
+        seed: 42,
+        ..Default::default()
+    };
+    let events = propeller.events(0, 200_000).unwrap();
+    assert!(!events.is_empty());
+    assert!(events.windows(2).all(|pair| pair[0].t <= pair[1].t));
+    assert_eq!(
+        fields(&events),
+        fields(&propeller.events(0, 200_000).unwrap())
+    );
+}
+
+#[test]
+fn bent_blade() {
+    // three single-blade rotors make a three-blade rotor, the third blade lags by delay µs
+    let single = Propeller {
+        width: 64,
+        height: 64,
+        rpm: 600.0,
+        blades: 1,
+        ..Default::default()
+    }
+    .events(0, 2_600_000)
+    .unwrap();
+    let imbalance_index = |delay: u64| {
+        let mut events: Vec<_> = (0..3)
+            .flat_map(|blade| {
+                let offset = blade * 100_000 / 3 + if blade == 2 { delay } else { 0 };
+                single.iter().map(move |event| Event {
+                    t: event.t + offset,
+                    ..*event
+                })
+            })
+            .filter(|event| event.t >= 100_000)
+            .collect();
+        events.sort_by_key(|event| event.t);
+        let mut estimator = RpmEstimator::new(Configuration {
+            width: 64,
+            height: 64,
+            blades: Some(3),
+            ..Default::default()
+        })
+        .unwrap();
+        estimator.process(&events, &Parameters::default()).unwrap();
+        estimator.samples().last().unwrap().imbalance_index
+    };
+    let balanced = imbalance_index(0);
+    let bent = imbalance_index(6_000);
+    assert!(balanced < 0.05, "expected a balanced rotor, got {balanced}");
+    assert!(bent > 0.3, "expected an imbalance, got {bent}");
+}
+
+#[test]
+fn gap_in_the_stream() {
+    // the FFT window (1024 samples at 512 Hz) lasts 2 s
+    let propeller = Propeller {
+        width: 64,
+        height: 64,
+        jitter: 50,
+        ..Default::default()
+    };
+    let events = propeller.events(0, 2_500_000).unwrap();
+    let parameters = Parameters {
+        amplitude_threshold: 0.0,
+        frequency_multiplier: 1.0 / propeller.blades as f32,
+        ..Default::default()
+    };
+    for (t_end, expect_nan) in [(3_500_000, false), (5_000_000, true)] {
+        let mut estimator = RpmEstimator::new(Configuration {
+            width: propeller.width,
+            height: propeller.height,
+            ..Default::default()
+        })
+        .unwrap();
+        estimator.process(&events, &parameters).unwrap();
+        estimator.flush(Some(t_end), &parameters).unwrap();
+        let rpm = estimator.samples().last().unwrap().rpm;
+        assert_eq!(
+            rpm.is_nan(),
+            expect_nan,
+            "flush at t={t_end} µs, got {rpm} RPM"
+        );
+    }
+}
+
+#[test]
+fn recorded_flips() {
+    let propeller = Propeller {
+        width: 64,
+        height: 64,
+        jitter: 50,
+        ..Default::default()
+    };
+    let events = propeller.events(0, 500_000).unwrap();
+    let parameters = Parameters::default();
+    let flips = |flips_capacity: usize| {
+        let mut estimator = RpmEstimator::new(Configuration {
+            width: propeller.width,
+            height: propeller.height,
+            flips_capacity,
+            ..Default::default()
+        })
+        .unwrap();
+        estimator.set_record_flips(true);
+        estimator.process(&events, &parameters).unwrap();
+        estimator
+    };
+    let mut estimator = flips(fidget_spinner::core::DEFAULT_FLIPS_CAPACITY);
+    assert_eq!(estimator.statistics().dropped_flips, 0);
+    let all_flips = estimator.clone().take_flips();
+    assert!(!all_flips.is_empty());
+    assert!(all_flips.windows(2).all(|pair| pair[0].t <= pair[1].t));
+    let Flip { x, y, .. } = all_flips[0];
+    let block_flips = estimator.take_block_flips(x + 1, y + 1).unwrap();
+    assert_eq!(
+        block_flips,
+        all_flips
+            .iter()
+            .copied()
+            .filter(|flip| flip.x == x && flip.y == y)
+            .collect::<Vec<_>>()
+    );
+    assert!(estimator.take_block_flips(x, y).unwrap().is_empty());
+    assert_eq!(
+        estimator.take_flips().len(),
+        all_flips.len() - block_flips.len()
+    );
+
+    // the oldest flips of full blocks are dropped
+    let mut estimator = flips(2);
+    let kept_flips = estimator.take_flips();
+    assert_eq!(
+        kept_flips.len() as u64 + estimator.statistics().dropped_flips,
+        all_flips.len() as u64
+    );
+    assert_eq!(
+        kept_flips
+            .iter()
+            .filter(|flip| flip.x == x && flip.y == y)
+            .count(),
+        2
+    );
+    assert!(kept_flips
+        .iter()
+        .filter(|flip| flip.x == x && flip.y == y)
+        .all(|flip| flip.t >= block_flips[block_flips.len() - 2].t));
+}
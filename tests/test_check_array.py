@@ -0,0 +1,293 @@
+"""Golden and property tests for the extension's array checks (check_array in src/python.rs).
+
+Build the extension first (maturin develop), then run pytest from the repository root.
+"""
+
+import random
+import re
+
+import numpy as np
+import pytest
+
+from fidget_spinner import extension
+
+IMU_FIELDS = [
+    "t",
+    "temperature",
+    "accelerometer_x",
+    "accelerometer_y",
+    "accelerometer_z",
+    "gyroscope_x",
+    "gyroscope_y",
+    "gyroscope_z",
+    "magnetometer_x",
+    "magnetometer_y",
+    "magnetometer_z",
+]
+
+IMU_DTYPE = np.dtype([("t", "<u8")] + [(name, "<f4") for name in IMU_FIELDS[1:]])
+
+DVS_DTYPE = np.dtype([("t", "<u8"), ("x", "<u2"), ("y", "<u2"), ("on", "?")])
+
+WIDTH = 64
+HEIGHT = 64
+
+
+def calculator() -> extension.RpmCalculator:
+    return extension.RpmCalculator(width=WIDTH, height=HEIGHT)
+
+
+def check_imu(array: object, strict: bool = True):
+    return calculator().process_imu(array, strict=strict)  # type: ignore
+
+
+def check_dvs(array: object, strict: bool = True):
+    return calculator().process(array, strict=strict)  # type: ignore
+
+
+def raises(message: str):
+    return pytest.raises(RuntimeError, match=f"^{re.escape(message)}$")
+
+
+def raises_exception(message: str):
+    return pytest.raises(Exception, match=f"^{re.escape(message)}$")
+
+
+def dvs_events(length: int = 4096, seed: int = 0) -> np.ndarray:
+    generator = np.random.default_rng(seed)
+    events = np.zeros(length, dtype=DVS_DTYPE)
+    events["t"] = np.sort(generator.integers(0, 1_000_000, length))
+    events["x"] = generator.integers(0, WIDTH, length)
+    events["y"] = generator.integers(0, HEIGHT, length)
+    events["on"] = generator.integers(0, 2, length).astype(bool)
+    return events
+
+
+def state_after(events: object) -> bytes:
+    rpm_calculator = calculator()
+    rpm_calculator.process(events)  # type: ignore
+    return rpm_calculator.__getstate__()
+
+
+def converted(events: np.ndarray, dtype: np.dtype) -> np.ndarray:
+    result = np.zeros(len(events), dtype=dtype)
+    for name in DVS_DTYPE.names:  # type: ignore
+        result[name] = events[name]
+    return result
+
+
+# golden error messages
+
+
+def test_not_an_array():
+    with raises("the object is not a numpy array"):
+        check_imu([0, 1, 2])
+    with raises("the object is not a numpy array"):
+        check_dvs(42)
+
+
+def test_zero_dimensional():
+    with raises(
+        "expected an array with at least one dimension (got a zero-dimensional array)"
+    ):
+        check_imu(np.zeros((), dtype=IMU_DTYPE))
+
+
+def test_multi_dimensional():
+    assert check_imu(np.zeros((4, 3), dtype=IMU_DTYPE)) == []
+    assert check_imu(np.zeros(8, dtype=IMU_DTYPE)[::2]) == []
+    assert check_imu(np.zeros((4, 6), dtype=IMU_DTYPE)[:, ::2]) == []
+
+
+def test_not_structured():
+    message = "the array is not structured (https://numpy.org/doc/stable/user/basics.rec.html)"
+    with raises(message):
+        check_imu(np.zeros(4, dtype=np.float32))
+    with raises(message):
+        check_dvs(np.zeros(4, dtype=np.uint64))
+
+
+def test_missing_field():
+    dtype = np.dtype([(name, IMU_DTYPE[name]) for name in IMU_FIELDS if name != "gyroscope_z"])
+    with raises('the array must have a field "gyroscope_z"'):
+        check_imu(np.zeros(4, dtype=dtype))
+    with raises('the array must have a field "on" or "p" or "polarity"'):
+        check_dvs(np.zeros(4, dtype=[("t", "<u8"), ("x", "<u2"), ("y", "<u2")]))
+
+
+def test_wrong_type():
+    dtype = np.dtype(
+        [
+            (name, "<f8" if name == "gyroscope_z" else IMU_DTYPE[name])
+            for name in IMU_FIELDS
+        ]
+    )
+    with pytest.raises(
+        RuntimeError,
+        match=r'^the field "gyroscope_z" must have the type "<f4" \(got "[<=]f8"\)$',
+    ):
+        check_imu(np.zeros(4, dtype=dtype))
+
+
+def test_wrong_type_lists_accepted_types():
+    with pytest.raises(
+        RuntimeError,
+        match=r'^the field "x" must have the type "<H2" \(got "[<=]f4"\)$',
+    ):
+        check_dvs(
+            np.zeros(4, dtype=[("t", "<u8"), ("x", "<f4"), ("y", "<u2"), ("on", "?")])
+        )
+    with pytest.raises(
+        RuntimeError,
+        match=r'^the field "t" must have the type "<[LQ]8" or "<[lq]8" or "<d8" \(got "[<=]u4"\)$',
+    ):
+        check_dvs(
+            np.zeros(4, dtype=[("t", "<u4"), ("x", "<u2"), ("y", "<u2"), ("on", "?")])
+        )
+
+
+def test_big_endian():
+    dtype = np.dtype(
+        [
+            (name, ">f4" if name == "gyroscope_z" else IMU_DTYPE[name])
+            for name in IMU_FIELDS
+        ]
+    )
+    assert check_imu(np.zeros(4, dtype=dtype)) == []
+    assert check_imu(np.zeros(4, dtype=IMU_DTYPE.newbyteorder(">"))) == []
+
+
+def test_extra_field():
+    dtype = np.dtype(IMU_DTYPE.descr + [("extra", "<u4")])
+    expected = ", ".join(f'"{name}"' for name in IMU_FIELDS)
+    actual = ", ".join(f'"{name}"' for name in IMU_FIELDS + ["extra"])
+    with raises(
+        f"the array has extra fields (expected [{expected}], got [{actual}]), use strict=False to ignore them"
+    ):
+        check_imu(np.zeros(4, dtype=dtype))
+    assert check_imu(np.zeros(4, dtype=dtype), strict=False) == []
+
+
+def test_titles():
+    # the title of a field is a second key in the dtype's fields, not an extra field
+    dtype = np.dtype(
+        {
+            "names": ["t", "x", "y", "on"],
+            "formats": ["<u8", "<u2", "<u2", "?"],
+            "titles": [None, None, None, "p"],
+        }
+    )
+    events = dvs_events()
+    assert state_after(converted(events, dtype)) == state_after(events)
+
+
+# properties, any layout of the same values must be read identically
+
+
+@pytest.mark.parametrize("seed", range(8))
+def test_reordered_fields(seed: int):
+    names = list(DVS_DTYPE.names)  # type: ignore
+    random.Random(seed).shuffle(names)
+    dtype = np.dtype([(name, DVS_DTYPE[name]) for name in names])
+    events = dvs_events(seed=seed)
+    assert state_after(converted(events, dtype)) == state_after(events)
+
+
+@pytest.mark.parametrize("seed", range(8))
+def test_padded_fields(seed: int):
+    generator = random.Random(seed)
+    offsets = []
+    offset = 0
+    for name in DVS_DTYPE.names:  # type: ignore
+        offset += generator.randint(0, 7)
+        offsets.append(offset)
+        offset += DVS_DTYPE[name].itemsize
+    dtype = np.dtype(
+        {
+            "names": list(DVS_DTYPE.names),  # type: ignore
+            "formats": [DVS_DTYPE[name] for name in DVS_DTYPE.names],  # type: ignore
+            "offsets": offsets,
+            "itemsize": offset + generator.randint(0, 7),
+        }
+    )
+    events = dvs_events(seed=seed)
+    assert state_after(converted(events, dtype)) == state_after(events)
+
+
+def test_aligned_fields():
+    events = dvs_events()
+    aligned = np.dtype(DVS_DTYPE.descr, align=True)
+    assert aligned.itemsize > DVS_DTYPE.itemsize
+    assert state_after(converted(events, aligned)) == state_after(events)
+
+
+def test_strided_array():
+    events = dvs_events(8192)
+    assert state_after(events[::2]) == state_after(np.ascontiguousarray(events[::2]))
+
+
+def test_non_contiguous_arrays():
+    events = dvs_events(8192).reshape((64, 128))
+    for view in (events[:, ::3], events[::2, 1::2], events.T, events[::-1, ::-2]):
+        assert not view.flags.c_contiguous
+        assert state_after(view) == state_after(np.ascontiguousarray(view))
+
+
+def test_memmap(tmp_path):
+    events = dvs_events(8192)
+    path = tmp_path / "events.bin"
+    events.tofile(path)
+    mapped = np.memmap(path, dtype=DVS_DTYPE, mode="r")
+    assert state_after(mapped) == state_after(events)
+    assert state_after(mapped[1000:5000:3]) == state_after(events[1000:5000:3])
+    del mapped
+
+
+def test_read_only_output():
+    rpm_calculator = calculator()
+    spectrum = np.zeros(len(rpm_calculator.frequencies()), dtype=np.float32)
+    spectrum.flags.writeable = False
+    with raises_exception("spectrum must be writeable (and not borrowed by another call)"):
+        rpm_calculator.process(dvs_events(), spectrum=spectrum)  # type: ignore
+    # strided output arrays are supported
+    strided = np.zeros(len(spectrum) * 2, dtype=np.float32)
+    rpm_calculator.process(dvs_events(), spectrum=strided[::2])  # type: ignore
+    assert np.all(strided[1::2] == 0.0)
+
+
+def test_big_endian_fields():
+    events = dvs_events()
+    expected = state_after(events)
+    assert state_after(events.astype(DVS_DTYPE.newbyteorder(">"))) == expected
+    dtype = np.dtype([("t", ">u8"), ("x", "<u2"), ("y", ">u2"), ("on", "?")])
+    assert state_after(converted(events, dtype)) == expected
+    # big-endian converted fields
+    dtype = np.dtype([("t", ">i8"), ("x", ">u2"), ("y", ">u2"), ("polarity", ">i2")])
+    result = np.zeros(len(events), dtype=dtype)
+    result["t"] = events["t"]
+    result["x"] = events["x"]
+    result["y"] = events["y"]
+    result["polarity"] = np.where(events["on"], 1, -1)
+    assert state_after(result) == expected
+
+
+def test_accepted_types():
+    events = dvs_events()
+    expected = state_after(events)
+    for t_format, t_scale in (("<i8", 1), ("<f8", 1e-6)):
+        for name, polarity_format, off in (
+            ("on", "u1", 0),
+            ("p", "i1", -1),
+            ("polarity", "<i2", -1),
+            ("polarity", "<i4", 0),
+            ("polarity", "<i8", -1),
+        ):
+            dtype = np.dtype(
+                [("t", t_format), ("x", "<u2"), ("y", "<u2"), (name, polarity_format)]
+            )
+            result = np.zeros(len(events), dtype=dtype)
+            result["t"] = events["t"] * t_scale
+            result["x"] = events["x"]
+            result["y"] = events["y"]
+            result[name] = np.where(events["on"], 1, off)
+            assert state_after(result) == expected, (t_format, name, polarity_format)
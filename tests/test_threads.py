@@ -0,0 +1,192 @@
+"""Concurrency tests for the extension (free-threaded and regular builds).
+
+Concurrent calls on the same calculator must either serialize or raise a RuntimeError (the
+calculator is borrowed by the other call), never crash or corrupt the calculator.
+
+Build the extension first (maturin develop), then run pytest from the repository root.
+"""
+
+import asyncio
+import copy
+import itertools
+import sys
+import sysconfig
+import threading
+
+import numpy as np
+import pytest
+
+from fidget_spinner import extension
+
+DVS_DTYPE = np.dtype([("t", "<u8"), ("x", "<u2"), ("y", "<u2"), ("on", "?")])
+
+WIDTH = 64
+HEIGHT = 64
+THREADS = 4
+
+
+def calculator() -> extension.RpmCalculator:
+    return extension.RpmCalculator(width=WIDTH, height=HEIGHT)
+
+
+def dvs_events(begin_t: int, end_t: int, length: int, seed: int = 0) -> np.ndarray:
+    generator = np.random.default_rng(seed)
+    events = np.zeros(length, dtype=DVS_DTYPE)
+    events["t"] = np.sort(generator.integers(begin_t, end_t, length))
+    events["x"] = generator.integers(0, WIDTH, length)
+    events["y"] = generator.integers(0, HEIGHT, length)
+    events["on"] = generator.integers(0, 2, length).astype(bool)
+    return events
+
+
+def state_after(*packets: np.ndarray) -> bytes:
+    rpm_calculator = calculator()
+    for packet in packets:
+        rpm_calculator.process(packet)  # type: ignore
+    return rpm_calculator.__getstate__()
+
+
+def run_concurrently(*functions) -> list[object]:
+    """Calls the functions in parallel threads and returns their results or exceptions."""
+    barrier = threading.Barrier(len(functions))
+    results: list[object] = [None] * len(functions)
+
+    def run(index: int):
+        barrier.wait()
+        try:
+            results[index] = functions[index]()
+        except Exception as exception:
+            results[index] = exception
+
+    threads = [threading.Thread(target=run, args=(index,)) for index in range(len(functions))]
+    for thread in threads:
+        thread.start()
+    for thread in threads:
+        thread.join()
+    return results
+
+
+def is_borrow_error(result: object) -> bool:
+    return isinstance(result, RuntimeError) and "borrowed" in str(result)
+
+
+def test_concurrent_process():
+    rpm_calculator = calculator()
+    packets = [
+        dvs_events(index * 1_000_000, (index + 1) * 1_000_000, 200_000, seed=index)
+        for index in range(THREADS)
+    ]
+    results = run_concurrently(
+        *(lambda packet=packet: rpm_calculator.process(packet) for packet in packets)
+    )
+    succeeded = [
+        packet
+        for packet, result in zip(packets, results)
+        if not isinstance(result, Exception)
+    ]
+    assert all(
+        is_borrow_error(result) for result in results if isinstance(result, Exception)
+    ), results
+    assert len(succeeded) > 0
+    # calls that did not raise ran one after the other, in an unknown order
+    state = rpm_calculator.__getstate__()
+    assert any(
+        state == state_after(*order) for order in itertools.permutations(succeeded)
+    )
+    # the calculator is still usable
+    rpm_calculator.process(dvs_events(10_000_000, 11_000_000, 4096))  # type: ignore
+
+
+def test_concurrent_reads():
+    rpm_calculator = calculator()
+    packet = dvs_events(0, 2_000_000, 400_000)
+    results = run_concurrently(
+        lambda: rpm_calculator.process(packet),
+        *(rpm_calculator.confidences for _ in range(THREADS - 1)),
+    )
+    assert not isinstance(results[0], Exception), results[0]
+    for result in results[1:]:
+        assert isinstance(result, list) or is_borrow_error(result), result
+    assert rpm_calculator.__getstate__() == state_after(packet)
+
+
+def test_independent_calculators():
+    packets = [dvs_events(0, 2_000_000, 200_000, seed=seed) for seed in range(THREADS)]
+    calculators = [calculator() for _ in packets]
+    results = run_concurrently(
+        *(
+            lambda rpm_calculator=rpm_calculator, packet=packet: rpm_calculator.process(packet)
+            for rpm_calculator, packet in zip(calculators, packets)
+        )
+    )
+    assert not any(isinstance(result, Exception) for result in results), results
+    for rpm_calculator, packet in zip(calculators, packets):
+        assert rpm_calculator.__getstate__() == state_after(packet)
+
+
+def test_cloned_calculators():
+    warmup = dvs_events(0, 1_000_000, 200_000)
+    packet = dvs_events(1_000_000, 3_000_000, 400_000, seed=1)
+    rpm_calculator = calculator()
+    rpm_calculator.process(warmup)  # type: ignore
+    clones = [rpm_calculator.clone() for _ in range(THREADS)]
+    clones.append(copy.deepcopy(rpm_calculator))
+    results = run_concurrently(
+        *(
+            lambda rpm_calculator=rpm_calculator: rpm_calculator.process(packet)
+            for rpm_calculator in clones
+        )
+    )
+    assert not any(isinstance(result, Exception) for result in results), results
+    expected = state_after(warmup, packet)
+    for clone in clones:
+        assert clone.__getstate__() == expected
+    # the original is unaffected
+    assert rpm_calculator.__getstate__() == state_after(warmup)
+
+
+def test_process_async():
+    packet = dvs_events(0, 2_000_000, 400_000)
+    rpm_calculator = calculator()
+
+    async def main():
+        ticks = 0
+        pending = asyncio.ensure_future(rpm_calculator.process_async(packet))
+        while not pending.done():
+            ticks += 1
+            await asyncio.sleep(0)
+        return ticks, await pending
+
+    ticks, samples = asyncio.run(main())
+    # the event loop was not blocked while the events were processed
+    assert ticks > 0
+    expected = calculator()
+    expected_samples = expected.process(packet)  # type: ignore
+    assert np.array_equal(samples, expected_samples)  # type: ignore
+    assert rpm_calculator.__getstate__() == expected.__getstate__()
+
+
+def test_live_rpm_configuration():
+    # LiveRpm takes the same configuration as RpmCalculator, either an RpmConfig or keyword
+    # arguments
+    packets = iter([dvs_events(0, 1_000_000, 100_000)])
+    config = extension.RpmConfig(width=WIDTH, height=HEIGHT, sampling_frequency=20.0)
+    live_rpm = extension.LiveRpm(lambda: next(packets, None), config)
+    live_rpm.stop()
+    assert live_rpm.config().sampling_frequency == 20.0
+    live_rpm = extension.LiveRpm(lambda: None, width=WIDTH, height=HEIGHT, hop_duration=0.05)
+    live_rpm.stop()
+    assert live_rpm.config().sampling_frequency == pytest.approx(20.0)
+    with pytest.raises(Exception, match="cannot be combined"):
+        extension.LiveRpm(lambda: None, config, width=WIDTH)
+    with pytest.raises(Exception, match="hop_duration"):
+        extension.LiveRpm(lambda: None, width=WIDTH, height=HEIGHT, hop_duration=0.0)
+
+
+@pytest.mark.skipif(
+    not sysconfig.get_config_var("Py_GIL_DISABLED"),
+    reason="requires a free-threaded build of Python",
+)
+def test_gil_not_enabled():
+    # importing a module that requires the GIL re-enables it
+    assert not sys._is_gil_enabled()  # type: ignore